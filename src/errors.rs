@@ -1,6 +1,5 @@
 use std::fmt;
 use std::error::Error;
-use crate::models::common::GameMode;
 
 #[derive(Debug)]
 pub enum ParseError<GameMode: fmt::Display + 'static> {
@@ -13,6 +12,7 @@ pub enum ParseError<GameMode: fmt::Display + 'static> {
 #[derive(Debug)]
 pub enum WriteError<GameMode: fmt::Display + 'static> {
     InvalidKeyCount(u8, String, String),
+    InvalidMode(GameMode, String),
     Unimpl(GameMode),
 }
 
@@ -31,6 +31,7 @@ impl<GameMode: fmt::Display + 'static> fmt::Display for WriteError<GameMode> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::InvalidKeyCount(key_count, avaibable_key_counts, format) => write!(f, "Failed to write because {key_count}k is not supported, {format} only supports {avaibable_key_counts}"),
+            Self::InvalidMode(mode, format) => write!(f, "Failed to write because {mode} charts are not supported by {format}"),
             Self::Unimpl(_gamemode) => {unimplemented!()},
         }
     }
@@ -9,7 +9,7 @@ pub type Chart = crate::models::chart::Chart;
 pub(crate) mod parsers;
 pub(crate) mod writers;
 pub mod models;
-pub mod utils;
+pub(crate) mod utils;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod parse {
@@ -20,15 +20,160 @@ pub mod parse {
         parsers::osu::from_osu(raw_chart)
     }
 
+    #[inline]
+    pub fn from_osu_as_mania(raw_chart: &str, key_count: u8) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::osu::from_osu_as_mania(raw_chart, key_count)
+    }
+
     #[inline]
     pub fn from_sm(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
         parsers::stepmania::from_sm(raw_chart)
     }
 
+    #[inline]
+    pub fn from_sm_all(raw_chart: &str) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::stepmania::from_sm_all(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_sma(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::stepmania::from_sma(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_ssc(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::stepmania::from_ssc(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_ssc_all(raw_chart: &str) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::stepmania::from_ssc_all(raw_chart)
+    }
+
     #[inline]
     pub fn from_qua(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
         parsers::quaver::from_qua(raw_chart)
     }
+
+    #[inline]
+    pub fn from_bms(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::bms::from_bms(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_bmson(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::bms::from_bmson(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_mc(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::malody::from_mc(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_ojn(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::o2jam::from_ojn(raw_chart, raw_ojm)
+    }
+
+    #[inline]
+    pub fn from_ojn_all(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::o2jam::from_ojn_all(raw_chart, raw_ojm)
+    }
+
+    #[inline]
+    pub fn from_dtx(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::dtx::from_dtx(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_dtx_with_layout(raw_chart: &str, layout: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::dtx::from_dtx_with_layout(raw_chart, layout)
+    }
+
+    #[inline]
+    pub fn from_ksh(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::ksh::from_ksh(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_chart(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::clone_hero::from_chart(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_chart_all(raw_chart: &str) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::clone_hero::from_chart_all(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_notes_mid(raw_mid: &[u8]) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::clone_hero::from_notes_mid(raw_mid)
+    }
+
+    #[inline]
+    pub fn from_notes_mid_all(raw_mid: &[u8]) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::clone_hero::from_notes_mid_all(raw_mid)
+    }
+
+    #[inline]
+    pub fn from_midi(raw_midi: &[u8], options: &crate::models::midi::MidiOptions) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::midi::from_midi(raw_midi, options)
+    }
+
+    #[inline]
+    pub fn from_fnf(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::fnf::from_fnf(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_fnf_both_sides(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::fnf::from_fnf_both_sides(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_beat_saber(raw_info: &str, raw_chart: &str, key_count: u8) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::beat_saber::from_beat_saber(raw_info, raw_chart, key_count)
+    }
+
+    #[inline]
+    pub fn from_tja(raw_chart: &str) -> Result<crate::Chart, Box<dyn std::error::Error>> {
+        parsers::tja::from_tja(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_tja_all(raw_chart: &str) -> Result<Vec<crate::Chart>, Box<dyn std::error::Error>> {
+        parsers::tja::from_tja_all(raw_chart)
+    }
+
+    #[inline]
+    pub fn from_osz(raw_osz: &[u8]) -> Result<crate::models::mapset::Mapset, Box<dyn std::error::Error>> {
+        parsers::osz::from_osz(raw_osz)
+    }
+
+    #[inline]
+    pub fn from_qp(raw_qp: &[u8]) -> Result<crate::models::mapset::Mapset, Box<dyn std::error::Error>> {
+        parsers::qp::from_qp(raw_qp)
+    }
+
+    #[inline]
+    pub fn from_sm_folder(path: impl AsRef<std::path::Path>) -> Result<crate::models::mapset::Mapset, Box<dyn std::error::Error>> {
+        parsers::sm_folder::from_sm_folder(path.as_ref())
+    }
+
+    #[inline]
+    pub fn from_sm_pack(path: impl AsRef<std::path::Path>) -> Result<crate::models::mapset::Pack, Box<dyn std::error::Error>> {
+        parsers::sm_folder::from_sm_pack(path.as_ref())
+    }
+
+    #[inline]
+    pub fn from_sm_folder_files(files: &[crate::models::mapset::AssetFile]) -> Result<crate::models::mapset::Mapset, Box<dyn std::error::Error>> {
+        parsers::sm_folder::from_sm_folder_files(files)
+    }
+
+    #[inline]
+    pub fn from_sm_pack_files(files: &[crate::models::mapset::AssetFile]) -> Result<crate::models::mapset::Pack, Box<dyn std::error::Error>> {
+        parsers::sm_folder::from_sm_pack_files(files)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -45,6 +190,14 @@ pub mod parse {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn parse_from_osu_as_mania(raw_chart: &str, key_count: u8) -> Result<crate::Chart, JsError> {
+        match parsers::osu::from_osu_as_mania(raw_chart, key_count) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
     #[wasm_bindgen]
     pub fn parse_from_sm(raw_chart: &str) -> Result<crate::Chart, JsError> {
         match parsers::stepmania::from_sm(raw_chart) {
@@ -53,6 +206,38 @@ pub mod parse {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn parse_from_sm_all(raw_chart: &str) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::stepmania::from_sm_all(raw_chart) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_sma(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::stepmania::from_sma(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_ssc(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::stepmania::from_ssc(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_ssc_all(raw_chart: &str) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::stepmania::from_ssc_all(raw_chart) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
     #[wasm_bindgen]
     pub fn parse_from_qua(raw_chart: &str) -> Result<crate::Chart, JsError> {
         match parsers::quaver::from_qua(raw_chart) {
@@ -60,6 +245,182 @@ pub mod parse {
             Err(e) => Err(JsError::new(&e.to_string()))
         }
     }
+
+    #[wasm_bindgen]
+    pub fn parse_from_bms(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::bms::from_bms(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_bmson(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::bms::from_bmson(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_mc(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::malody::from_mc(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_ojn(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<crate::Chart, JsError> {
+        match parsers::o2jam::from_ojn(raw_chart, raw_ojm) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_ojn_all(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::o2jam::from_ojn_all(raw_chart, raw_ojm) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_dtx(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::dtx::from_dtx(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_dtx_with_layout(raw_chart: &str, layout: &str) -> Result<crate::Chart, JsError> {
+        match parsers::dtx::from_dtx_with_layout(raw_chart, layout) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_ksh(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::ksh::from_ksh(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_chart(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::clone_hero::from_chart(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_chart_all(raw_chart: &str) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::clone_hero::from_chart_all(raw_chart) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_notes_mid(raw_mid: &[u8]) -> Result<crate::Chart, JsError> {
+        match parsers::clone_hero::from_notes_mid(raw_mid) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_notes_mid_all(raw_mid: &[u8]) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::clone_hero::from_notes_mid_all(raw_mid) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_midi(raw_midi: &[u8], options: &crate::models::midi::MidiOptions) -> Result<crate::Chart, JsError> {
+        match parsers::midi::from_midi(raw_midi, options) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_fnf(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::fnf::from_fnf(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_fnf_both_sides(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::fnf::from_fnf_both_sides(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_beat_saber(raw_info: &str, raw_chart: &str, key_count: u8) -> Result<crate::Chart, JsError> {
+        match parsers::beat_saber::from_beat_saber(raw_info, raw_chart, key_count) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_tja(raw_chart: &str) -> Result<crate::Chart, JsError> {
+        match parsers::tja::from_tja(raw_chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_tja_all(raw_chart: &str) -> Result<Vec<crate::Chart>, JsError> {
+        match parsers::tja::from_tja_all(raw_chart) {
+            Ok(charts) => Ok(charts),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_osz(raw_osz: &[u8]) -> Result<crate::models::mapset::Mapset, JsError> {
+        match parsers::osz::from_osz(raw_osz) {
+            Ok(mapset) => Ok(mapset),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_qp(raw_qp: &[u8]) -> Result<crate::models::mapset::Mapset, JsError> {
+        match parsers::qp::from_qp(raw_qp) {
+            Ok(mapset) => Ok(mapset),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_sm_folder(files: Vec<crate::models::mapset::AssetFile>) -> Result<crate::models::mapset::Mapset, JsError> {
+        match parsers::sm_folder::from_sm_folder_files(&files) {
+            Ok(mapset) => Ok(mapset),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn parse_from_sm_pack(files: Vec<crate::models::mapset::AssetFile>) -> Result<crate::models::mapset::Pack, JsError> {
+        match parsers::sm_folder::from_sm_pack_files(&files) {
+            Ok(pack) => Ok(pack),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -76,10 +437,75 @@ pub mod write {
         writers::stepmania::to_sm(chart)
     }
 
+    #[inline]
+    pub fn to_sma(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::stepmania::to_sma(chart)
+    }
+
+    #[inline]
+    pub fn to_sm_with_sv_stops(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::stepmania::to_sm_with_sv_stops(chart)
+    }
+
+    #[inline]
+    pub fn to_sma_with_sv_stops(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::stepmania::to_sma_with_sv_stops(chart)
+    }
+
+    #[inline]
+    pub fn to_ssc(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::stepmania::to_ssc(chart)
+    }
+
     #[inline]
     pub fn to_qua(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
         writers::quaver::to_qua(chart)
     }
+
+    #[inline]
+    pub fn to_bms(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::bms::to_bms(chart)
+    }
+
+    #[inline]
+    pub fn to_bmson(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::bms::to_bmson(chart)
+    }
+
+    #[inline]
+    pub fn to_mc(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::malody::to_mc(chart)
+    }
+
+    #[inline]
+    pub fn to_fnf(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::fnf::to_fnf(chart)
+    }
+
+    #[inline]
+    pub fn to_beat_saber(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::beat_saber::to_beat_saber(chart)
+    }
+
+    #[inline]
+    pub fn to_beat_saber_info(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::beat_saber::to_beat_saber_info(chart)
+    }
+
+    #[inline]
+    pub fn to_tja(chart: &crate::Chart) -> Result<String, Box<dyn std::error::Error>> {
+        writers::tja::to_tja(chart)
+    }
+
+    #[inline]
+    pub fn to_osz(charts: &[crate::Chart], assets: &[crate::models::mapset::AssetFile]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        writers::osz::to_osz(charts, assets)
+    }
+
+    #[inline]
+    pub fn to_qp(charts: &[crate::Chart], assets: &[crate::models::mapset::AssetFile]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        writers::qp::to_qp(charts, assets)
+    }
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -104,6 +530,38 @@ pub mod write {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn write_to_sma(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::stepmania::to_sma(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_sm_with_sv_stops(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::stepmania::to_sm_with_sv_stops(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_sma_with_sv_stops(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::stepmania::to_sma_with_sv_stops(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_ssc(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::stepmania::to_ssc(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
     #[wasm_bindgen]
     pub fn write_to_qua(chart: &crate::Chart) -> Result<String, JsError> {
         match writers::quaver::to_qua(chart) {
@@ -111,4 +569,76 @@ pub mod write {
             Err(e) => Err(JsError::new(&e.to_string()))
         }
     }
+
+    #[wasm_bindgen]
+    pub fn write_to_bms(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::bms::to_bms(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_bmson(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::bms::to_bmson(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_mc(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::malody::to_mc(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_fnf(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::fnf::to_fnf(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_beat_saber(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::beat_saber::to_beat_saber(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_beat_saber_info(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::beat_saber::to_beat_saber_info(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_tja(chart: &crate::Chart) -> Result<String, JsError> {
+        match writers::tja::to_tja(chart) {
+            Ok(chart) => Ok(chart),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_osz(charts: Vec<crate::Chart>, assets: Vec<crate::models::mapset::AssetFile>) -> Result<Vec<u8>, JsError> {
+        match writers::osz::to_osz(&charts, &assets) {
+            Ok(osz) => Ok(osz),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn write_to_qp(charts: Vec<crate::Chart>, assets: Vec<crate::models::mapset::AssetFile>) -> Result<Vec<u8>, JsError> {
+        match writers::qp::to_qp(&charts, &assets) {
+            Ok(qp) => Ok(qp),
+            Err(e) => Err(JsError::new(&e.to_string()))
+        }
+    }
 }
@@ -18,6 +18,9 @@ pub struct Chart {
     pub hitobjects: HitObjects,
     #[wasm_bindgen(getter_with_clone)]
     pub soundbank: Option<SoundBank>,
+    /// parts of the source chart that couldn't be kept
+    #[wasm_bindgen(getter_with_clone)]
+    pub warnings: Vec<String>,
 }
 
 impl Chart {
@@ -34,6 +37,7 @@ impl Chart {
             timing_points,
             hitobjects,
             soundbank,
+            warnings: Vec::new(),
         }
     }
 }
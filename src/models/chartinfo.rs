@@ -1,4 +1,118 @@
 use crate::wasm_bindgen;
+use crate::models::common::GameMode;
+
+/// stepmania specific info about a chart (a single #NOTES or #NOTEDATA block)
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct StepsInfo {
+    #[wasm_bindgen(getter_with_clone)]
+    pub steps_type: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub description: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub difficulty: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub meter: u32,
+    #[wasm_bindgen(getter_with_clone)]
+    pub radar_values: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl StepsInfo {
+    pub fn new(
+        steps_type: String,
+        description: String,
+        difficulty: String,
+        meter: u32,
+        radar_values: Vec<f32>,
+    ) -> Self {
+        Self {
+            steps_type,
+            description,
+            difficulty,
+            meter,
+            radar_values,
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            steps_type: "dance-single".to_string(),
+            description: String::new(),
+            difficulty: "Edit".to_string(),
+            meter: 1,
+            radar_values: vec![0.0; 5],
+        }
+    }
+}
+
+/// stepmania steps types and how many columns they have,
+/// when several share a key count the first one is what gets written
+const STEPS_TYPES: &[(&str, u8)] = &[
+    ("dance-threepanel", 3),
+    ("dance-single", 4),
+    ("pump-single", 5),
+    ("dance-solo", 6),
+    ("kb7-single", 7),
+    ("dance-double", 8),
+    ("pnm-nine", 9),
+    ("pump-double", 10),
+    ("beat-double5", 12),
+    ("beat-double7", 16),
+    ("dance-couple", 8),
+    ("dance-routine", 8),
+    ("pump-halfdouble", 6),
+    ("pump-couple", 10),
+    ("pump-routine", 10),
+    ("ez2-single", 5),
+    ("ez2-real", 7),
+    ("ez2-double", 10),
+    ("para-single", 5),
+    ("ds3ddx-single", 8),
+    ("beat-single5", 6),
+    ("beat-single7", 8),
+    ("maniax-single", 4),
+    ("maniax-double", 8),
+    ("techno-single4", 4),
+    ("techno-single5", 5),
+    ("techno-single8", 8),
+    ("techno-double4", 8),
+    ("techno-double5", 10),
+    ("techno-double8", 16),
+    ("pnm-five", 5),
+    ("lights-cabinet", 6),
+    ("kickbox-human", 4),
+    ("kickbox-quadarm", 4),
+    ("kickbox-insect", 6),
+    ("kickbox-arachnid", 8),
+];
+
+impl StepsInfo {
+    /// key count of a steps type, None if it's not a known one
+    pub fn steps_type_key_count(steps_type: &str) -> Option<u8> {
+        STEPS_TYPES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(steps_type.trim()))
+            .map(|(_, key_count)| *key_count)
+    }
+
+    /// default steps type for a key count, None if no steps type has that many columns
+    pub fn key_count_steps_type(key_count: u8) -> Option<&'static str> {
+        STEPS_TYPES.iter()
+            .find(|(_, count)| *count == key_count)
+            .map(|(name, _)| *name)
+    }
+
+    /// every key count that has a steps type, formatted like "3k, 4k, ..."
+    pub fn supported_key_counts() -> String {
+        let mut key_counts: Vec<u8> = STEPS_TYPES.iter().map(|(_, key_count)| *key_count).collect();
+        key_counts.sort_unstable();
+        key_counts.dedup();
+        key_counts.iter()
+            .map(|key_count| format!("{}k", key_count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
 
 #[wasm_bindgen]
 #[derive(Debug, Clone)]
@@ -15,6 +129,10 @@ pub struct ChartInfo {
     pub preview_time: i32,
     #[wasm_bindgen(getter_with_clone)]
     pub key_count: u8,
+    #[wasm_bindgen(getter_with_clone)]
+    pub steps_info: Option<StepsInfo>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub game_mode: GameMode,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -35,6 +153,8 @@ impl ChartInfo {
             audio_offset,
             preview_time,
             key_count,
+            steps_info: None,
+            game_mode: GameMode::Mania,
         }
     }
 
@@ -46,6 +166,8 @@ impl ChartInfo {
             audio_offset: 0,
             preview_time: 0,
             key_count: 4,
+            steps_info: None,
+            game_mode: GameMode::Mania,
         }
     }
 }
\ No newline at end of file
@@ -36,11 +36,19 @@ pub type Measure = Vec<Row>;
 pub enum TimingChangeType {
     Bpm,
     Sv,
-    Stop
+    /// value is the duration in ms, notes on the stop are hit before it
+    Stop,
+    TimeSignature,
+    /// value is the duration in ms, notes on the delay are hit after it
+    Delay,
+    /// value is the length in beats, everything inside of it is skipped
+    Warp,
+    /// value is how many hold ticks there are per beat
+    TickCount,
 }
 
-#[allow(unused)]
-#[derive(Debug)]
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameMode {
     Mania,
     Taiko,
@@ -67,6 +75,19 @@ pub enum KeyType {
     Mine,
     Fake,
     Unknown,
+    /// a slider that has to be tapped repeatedly instead of held, formats without them get a normal slider
+    Roll,
+    /// a note hit on release, formats without them get a normal note
+    Lift,
+    /// taiko notes, these only show up in taiko charts which have a single column
+    Don,
+    Kat,
+    BigDon,
+    BigKat,
+    /// taiko sliders, the end time is stored in slider_end_time and there is no slider end
+    Drumroll,
+    BigDrumroll,
+    Spinner,
 }
 
 #[wasm_bindgen]
@@ -104,6 +125,14 @@ impl Key {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn roll(value: Option<i32>) -> Self {
+        Self {
+            key_type: KeyType::Roll,
+            slider_end_time: value,
+        }
+    }
+
     #[wasm_bindgen]
     pub fn slider_end() -> Self {
         Self {
@@ -128,6 +157,70 @@ impl Key {
         }
     }
 
+    #[wasm_bindgen]
+    pub fn lift() -> Self {
+        Self {
+            key_type: KeyType::Lift,
+            slider_end_time: None,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn don() -> Self {
+        Self {
+            key_type: KeyType::Don,
+            slider_end_time: None,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn kat() -> Self {
+        Self {
+            key_type: KeyType::Kat,
+            slider_end_time: None,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn big_don() -> Self {
+        Self {
+            key_type: KeyType::BigDon,
+            slider_end_time: None,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn big_kat() -> Self {
+        Self {
+            key_type: KeyType::BigKat,
+            slider_end_time: None,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn drumroll(value: Option<i32>) -> Self {
+        Self {
+            key_type: KeyType::Drumroll,
+            slider_end_time: value,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn big_drumroll(value: Option<i32>) -> Self {
+        Self {
+            key_type: KeyType::BigDrumroll,
+            slider_end_time: value,
+        }
+    }
+
+    #[wasm_bindgen]
+    pub fn spinner(value: Option<i32>) -> Self {
+        Self {
+            key_type: KeyType::Spinner,
+            slider_end_time: value,
+        }
+    }
+
     #[wasm_bindgen]
     pub fn unknown() -> Self {
         Self {
@@ -5,9 +5,16 @@ use crate::models::common::{
     KeyType,
 };
 use crate::models::sound::{
+    KeySound,
     KeySoundRow,
 };
 
+/// beats two notes can be apart and still be on the same row
+const BEAT_ROW_MARGIN: f32 = 0.0001;
+
+/// beat, column, key, keysound
+pub(crate) type BeatNote = (f32, usize, Key, Option<KeySound>);
+
 #[derive(Debug)]
 #[repr(C, align(64))]
 pub struct HitObjectView<'a> {
@@ -71,6 +78,37 @@ impl HitObjects {
         }
     }
 
+    /// groups notes on the same beat into rows, for formats that place their notes on beats,
+    /// notes on beats that aren't numbers or in columns past the key count are left out
+    pub(crate) fn from_beat_notes(mut notes: Vec<BeatNote>, key_count: usize, time_at: impl Fn(f32) -> i32) -> Self {
+        notes.retain(|(beat, column, _, _)| beat.is_finite() && *column < key_count);
+        notes.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting notes"));
+
+        let mut hitobjects = Self::with_capacity(notes.len());
+        let mut i = 0;
+        while i < notes.len() {
+            let row_beat = notes[i].0;
+            let mut row: Row = vec![Key::empty(); key_count];
+            let mut keysounds: Vec<Option<KeySound>> = vec![None; key_count];
+
+            while i < notes.len() && notes[i].0 - row_beat < BEAT_ROW_MARGIN {
+                let (_, column, key, key_sound) = notes[i];
+                row[column] = key;
+                keysounds[column] = key_sound;
+                i += 1;
+            }
+
+            let keysound_row = if keysounds.iter().all(Option::is_none) {
+                KeySoundRow::empty()
+            } else {
+                KeySoundRow::with_unwrap(&keysounds)
+            };
+            hitobjects.add_hitobject(time_at(row_beat), row_beat, keysound_row, row);
+        }
+
+        hitobjects
+    }
+
     #[inline]
     pub fn add_hitobject(&mut self, time: i32, beat: f32, hitsound: KeySoundRow, row: Row) {
         if row.iter().all(|&key| key.key_type == KeyType::Empty) { return; }
@@ -91,6 +129,7 @@ impl HitObjects {
             .map(|(((time, beat), keysound), row)| (time, beat, keysound, row))
     }
 
+    #[allow(mismatched_lifetime_syntaxes)]
     pub fn iter_views(&self) -> impl Iterator<Item = HitObjectView> {
         self.times
             .iter()
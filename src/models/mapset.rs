@@ -0,0 +1,61 @@
+use crate::wasm_bindgen;
+use crate::models::chart::Chart;
+
+/// a file charts reference, like their song, background or samples
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Asset {
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    /// a chart references it but it isn't in the archive
+    #[wasm_bindgen(getter_with_clone)]
+    pub is_missing: bool,
+}
+
+/// a file that gets packed next to the charts of an archive
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct AssetFile {
+    #[wasm_bindgen(getter_with_clone)]
+    pub path: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub data: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl AssetFile {
+    #[wasm_bindgen(constructor)]
+    pub fn new(path: String, data: Vec<u8>) -> Self {
+        Self { path, data }
+    }
+}
+
+/// every chart in an archive and the assets they reference
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Mapset {
+    #[wasm_bindgen(getter_with_clone)]
+    pub charts: Vec<Chart>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub assets: Vec<Asset>,
+}
+
+/// a song folder of a pack that couldn't be parsed
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct FailedSong {
+    #[wasm_bindgen(getter_with_clone)]
+    pub folder: String,
+    #[wasm_bindgen(getter_with_clone)]
+    pub error: String,
+}
+
+/// every song of a pack that could be parsed and the ones that couldn't
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct Pack {
+    #[wasm_bindgen(getter_with_clone)]
+    pub mapsets: Vec<Mapset>,
+    #[wasm_bindgen(getter_with_clone)]
+    pub failed_songs: Vec<FailedSong>,
+}
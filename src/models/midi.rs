@@ -0,0 +1,24 @@
+use crate::wasm_bindgen;
+
+/// which notes of a MIDI file become the chart
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct MidiOptions {
+    /// indices of the tracks notes are taken from, every track when empty
+    #[wasm_bindgen(getter_with_clone)]
+    pub tracks: Vec<u32>,
+    /// the pitch of each column from left to right, every pitch used gets a column from lowest to highest when empty
+    #[wasm_bindgen(getter_with_clone)]
+    pub pitches: Vec<u8>,
+    /// notes held for at least this many beats become long notes, 0 keeps every note a normal note
+    #[wasm_bindgen(getter_with_clone)]
+    pub hold_threshold: f32,
+}
+
+#[wasm_bindgen]
+impl MidiOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new(tracks: Vec<u32>, pitches: Vec<u8>, hold_threshold: f32) -> Self {
+        Self { tracks, pitches, hold_threshold }
+    }
+}
\ No newline at end of file
@@ -5,4 +5,6 @@ pub mod metadata;
 pub mod hitobjects;
 pub mod timing_points;
 pub mod timeline;
-pub mod sound;
\ No newline at end of file
+pub mod sound;
+pub mod midi;
+pub mod mapset;
\ No newline at end of file
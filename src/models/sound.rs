@@ -147,6 +147,7 @@ impl KeySoundRow {
         &self.sounds
     }
 
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.sounds.len()
     }
@@ -188,6 +189,7 @@ pub struct SoundBank {
 #[wasm_bindgen]
 impl SoundBank {
     #[wasm_bindgen(constructor)]
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
             audio_tracks: Vec::new(),
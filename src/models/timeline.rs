@@ -63,6 +63,7 @@ where
     }
 
     #[inline]
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
             timeline: Vec::new(),
@@ -120,6 +121,7 @@ where
     }
 
     #[inline]
+    #[allow(clippy::unnecessary_sort_by)]
     pub fn sort(&mut self) {
         if !self.is_sorted {
             self.timeline.sort_unstable_by(|a, b| a.time().cmp(&b.time()));
@@ -129,6 +131,7 @@ where
 }
 
 impl HitObjectTimeline {
+    #[allow(clippy::collapsible_match)]
     pub fn to_hitobjects(&mut self, hitobjects: &mut HitObjects,
         offset: i32, key_count: usize,
         bpms_times: &[i32], bpms: &[f32]) {
@@ -164,6 +167,11 @@ impl HitObjectTimeline {
                                 temp_row[column] = Key::slider_end();
                             }
                         },
+                        KeyType::Don | KeyType::Kat | KeyType::BigDon | KeyType::BigKat
+                        | KeyType::Drumroll | KeyType::BigDrumroll | KeyType::Spinner
+                        if temp_row[column].key_type == KeyType::Empty => {
+                            temp_row[column] = obj.key;
+                        },
                         _ => {}
                     }
                 }
@@ -198,6 +206,7 @@ impl HitObjectTimeline {
 }
 
 impl TimingPointTimeline {
+    #[allow(clippy::single_match)]
     pub fn to_timing_points(&mut self, timing_points: &mut TimingPoints, offset: i32) {
         if self.timeline.is_empty() {
             return;
@@ -229,10 +238,7 @@ impl TimingPointTimeline {
             
             timing_points.times.push(time);
             timing_points.beats.push(beat);
-            timing_points.changes.push(TimingChange {
-                value: timing_point.value,
-                change_type: timing_point.change_type,
-            });
+            timing_points.changes.push(TimingChange::new(timing_point.change_type, timing_point.value));
         }
     }
 }
@@ -11,10 +11,59 @@ pub struct TimingPointView<'a> {
     pub value: &'a f32,
 }
 
+/// a time signature as it's written, its measures last numerator * 4 / denominator beats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    pub fn new(numerator: u32, denominator: u32) -> Self {
+        Self { numerator, denominator }
+    }
+
+    /// for formats that only know how many beats a measure lasts,
+    /// the smallest denominator up to 64 that keeps the numerator whole, 192 if none of them do
+    pub fn from_beats(beats: f32) -> Self {
+        let denominator = [4, 8, 16, 32, 64].into_iter()
+            .find(|denominator| {
+                let numerator = beats * *denominator as f32 / 4.0;
+                (numerator - numerator.round()).abs() < 0.001
+            })
+            .unwrap_or(192);
+        Self::new((beats * denominator as f32 / 4.0).round().max(0.0) as u32, denominator)
+    }
+
+    #[inline]
+    pub fn beats(&self) -> f32 {
+        self.numerator as f32 * 4.0 / self.denominator as f32
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TimingChange {
     pub change_type: TimingChangeType,
+    /// time signatures keep the beats their measures last here
     pub value: f32,
+    /// the numerator and denominator of time signatures, None for every other change
+    pub time_signature: Option<TimeSignature>,
+}
+
+impl TimingChange {
+    /// time signatures made this way get the smallest denominator for their length, see `TimeSignature::from_beats`
+    pub fn new(change_type: TimingChangeType, value: f32) -> Self {
+        let time_signature = (change_type == TimingChangeType::TimeSignature).then(|| TimeSignature::from_beats(value));
+        Self { change_type, value, time_signature }
+    }
+
+    pub fn time_signature(time_signature: TimeSignature) -> Self {
+        Self {
+            change_type: TimingChangeType::TimeSignature,
+            value: time_signature.beats(),
+            time_signature: Some(time_signature),
+        }
+    }
 }
 
 // TODO: add wasm bindings for Timings
@@ -73,7 +122,7 @@ impl TimingPoints {
             .map(|((time, beat), change)| (time, beat, change))
     }
 
-    pub fn iter_views(&self) -> impl Iterator<Item = TimingPointView> + '_ {
+    pub fn iter_views(&self) -> impl Iterator<Item = TimingPointView<'_>> + '_ {
         self.times
             .iter()
             .zip(self.beats.iter())
@@ -86,11 +135,11 @@ impl TimingPoints {
             })
     }
     
-    pub fn bpm_changes_views(&self) -> impl Iterator<Item = TimingPointView> + '_ {
+    pub fn bpm_changes_views(&self) -> impl Iterator<Item = TimingPointView<'_>> + '_ {
         self.iter_views().filter(|v| matches!(v.change_type, TimingChangeType::Bpm))
     }
     
-    pub fn sv_changes_views(&self) -> impl Iterator<Item = TimingPointView> + '_ {
+    pub fn sv_changes_views(&self) -> impl Iterator<Item = TimingPointView<'_>> + '_ {
         self.iter_views().filter(|v| matches!(v.change_type, TimingChangeType::Sv))
     }
 
@@ -110,6 +159,80 @@ impl TimingPoints {
             .filter(|(_, _, change)| matches!(change.change_type, TimingChangeType::Sv))
     }
 
+    /// time, beat, change
+    pub fn stop_changes_zipped(
+        &self,
+    ) -> impl Iterator<Item = (&i32, &f32, &TimingChange)> + '_ {
+        self.iter_zipped()
+            .filter(|(_, _, change)| matches!(change.change_type, TimingChangeType::Stop))
+    }
+
+    /// time, beat, change
+    pub fn delay_changes_zipped(
+        &self,
+    ) -> impl Iterator<Item = (&i32, &f32, &TimingChange)> + '_ {
+        self.iter_zipped()
+            .filter(|(_, _, change)| matches!(change.change_type, TimingChangeType::Delay))
+    }
+
+    /// time, beat, change
+    pub fn warp_changes_zipped(
+        &self,
+    ) -> impl Iterator<Item = (&i32, &f32, &TimingChange)> + '_ {
+        self.iter_zipped()
+            .filter(|(_, _, change)| matches!(change.change_type, TimingChangeType::Warp))
+    }
+
+    /// time, multiplier
+    /// stops and delays get unwrapped into a 0x sv when they start and an sv restoring the previous multiplier when they end,
+    /// for formats that don't have stops
+    pub fn sv_changes_with_stops(&self) -> Vec<(i32, f32)> {
+        let mut result: Vec<(i32, f32)> = Vec::with_capacity(self.changes.len());
+        let mut current_sv = 1.0;
+
+        for (time, _, change) in self.iter_zipped() {
+            match change.change_type {
+                TimingChangeType::Sv => {
+                    current_sv = change.value;
+                    result.push((*time, change.value));
+                },
+                TimingChangeType::Stop | TimingChangeType::Delay => {
+                    result.push((*time, 0.0));
+                    result.push((*time + change.value as i32, current_sv));
+                },
+                _ => {},
+            }
+        }
+
+        result.sort_by_key(|(time, _)| *time);
+        result
+    }
+
+    /// bpm, multiplier
+    /// what is active at a time, the multiplier goes back to 1 on every bpm change like in osu!
+    pub fn bpm_and_sv_at(&self, time: i32) -> (f32, f32) {
+        let mut bpm = self.bpm_changes_zipped()
+            .next()
+            .map_or(120.0, |(_, _, change)| change.value);
+        let mut multiplier = 1.0;
+
+        for (change_time, _, change) in self.iter_zipped() {
+            if *change_time > time {
+                break;
+            }
+            match change.change_type {
+                TimingChangeType::Bpm => {
+                    bpm = change.value;
+                    multiplier = 1.0;
+                },
+                TimingChangeType::Sv => multiplier = change.value,
+                _ => {},
+            }
+        }
+
+        (bpm, multiplier)
+    }
+
     pub fn is_bpms_empty(&self) -> bool {
         !self.changes.iter()
             .any(|change| matches!(change.change_type, TimingChangeType::Bpm))
@@ -120,6 +243,11 @@ impl TimingPoints {
             .any(|change| matches!(change.change_type, TimingChangeType::Sv))
     }
 
+    pub fn is_stops_empty(&self) -> bool {
+        !self.changes.iter()
+            .any(|change| matches!(change.change_type, TimingChangeType::Stop))
+    }
+
     pub fn bpms(&self) -> Vec<f32> {
         self.changes.iter()
             .filter(|change| matches!(change.change_type, TimingChangeType::Bpm))
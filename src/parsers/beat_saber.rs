@@ -0,0 +1,186 @@
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::utils::time::{process_bpms_and_stops, process_timing_points};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::json::JsonValue;
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+/// notes go on a grid 4 lines wide and 3 layers tall
+const GRID_LINES: usize = 4;
+const GRID_LAYERS: usize = 3;
+const GRID_CELLS: usize = GRID_LINES * GRID_LAYERS;
+/// v2 notes with this type are bombs, the others are red or blue
+const V2_BOMB_TYPE: i64 = 3;
+/// v2 events with this type change the bpm
+const V2_BPM_EVENT_TYPE: i64 = 100;
+
+/// what a grid object turns into
+#[derive(Clone, Copy, PartialEq)]
+enum GridObject {
+    Note,
+    Bomb,
+}
+
+/// the column a grid cell goes in, cells are counted line by line from the bottom left
+/// so 4K gets one column per line and 12K one per cell
+#[inline]
+fn grid_column(line: i64, layer: i64, key_count: usize) -> usize {
+    // mapping extensions put objects far outside the grid, they go on its closest edge
+    let cell = line.clamp(0, GRID_LINES as i64 - 1) as usize * GRID_LAYERS + layer.clamp(0, GRID_LAYERS as i64 - 1) as usize;
+    cell * key_count / GRID_CELLS
+}
+
+#[inline]
+fn number(object: &JsonValue, key: &str) -> f32 {
+    object.get(key).and_then(JsonValue::as_f32).unwrap_or(0.0)
+}
+
+/// v3 leaves out fields that are 0
+#[inline]
+fn integer(object: &JsonValue, key: &str) -> i64 {
+    object.get(key).and_then(JsonValue::as_f64).map_or(0, |value| value as i64)
+}
+
+#[inline]
+fn objects<'a>(parent: &'a JsonValue, key: &str) -> impl Iterator<Item = &'a JsonValue> {
+    parent.get(key).into_iter().flat_map(JsonValue::iter_array)
+}
+
+/// parses a Beat Saber v2 or v3 difficulty (.dat) with the timing and metadata from its Info.dat,
+/// the grid gets projected onto `key_count` columns and walls become long notes on the columns they cover
+pub(crate) fn from_beat_saber(raw_info: &str, raw_chart: &str, key_count: u8) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, chart::Chart};
+
+    if raw_chart.trim().is_empty() || raw_info.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    if !(1..=GRID_CELLS as u8).contains(&key_count) {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(
+            format!("Beat Saber's grid only has {GRID_CELLS} cells to make columns from, {key_count}k was asked for"))));
+    }
+    let info = JsonValue::parse(raw_info.trim_start_matches('\u{feff}'))
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid Beat Saber Info.dat: {e}")))?;
+    let difficulty = JsonValue::parse(raw_chart.trim_start_matches('\u{feff}'))
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid Beat Saber difficulty: {e}")))?;
+
+    let text = |key: &str| info.get(key).and_then(JsonValue::as_str).unwrap_or("");
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    metadata.title = text("_songName").or_default_empty(ChartDefaults::TITLE);
+    metadata.alt_title = text("_songSubName").to_string();
+    metadata.artist = text("_songAuthorName").or_default_empty(ChartDefaults::ARTIST);
+    metadata.creator = text("_levelAuthorName").or_default_empty(ChartDefaults::CREATOR);
+    chartinfo.song_path = text("_songFilename").to_string();
+    chartinfo.bg_path = text("_coverImageFilename").to_string();
+    chartinfo.audio_offset = (number(&info, "_songTimeOffset") * 1000.0).round() as i32;
+    chartinfo.preview_time = (number(&info, "_previewStartTime") * 1000.0).round() as i32;
+    chartinfo.key_count = key_count;
+
+    // the difficulty file doesn't say which one it is, so it can only be known when there's just one
+    let difficulties: Vec<&JsonValue> = objects(&info, "_difficultyBeatmapSets")
+        .flat_map(|set| objects(set, "_difficultyBeatmaps"))
+        .collect();
+    chartinfo.difficulty_name = match difficulties.as_slice() {
+        [difficulty] => difficulty.get("_customData")
+            .and_then(|custom_data| custom_data.get("_difficultyLabel"))
+            .or_else(|| difficulty.get("_difficulty"))
+            .and_then(JsonValue::as_str)
+            .unwrap_or("")
+            .or_default_empty(ChartDefaults::DIFFICULTY_NAME),
+        _ => ChartDefaults::DIFFICULTY_NAME.to_string(),
+    };
+
+    let key_count = key_count as usize;
+    // (beat, column, object) and (start beat, end beat, column)
+    let mut grid_objects: Vec<(f32, usize, GridObject)> = Vec::new();
+    let mut walls: Vec<(f32, f32, usize)> = Vec::new();
+    let mut bpm_changes: Vec<(f32, f32)> = Vec::new();
+    let mut add_wall = |beat: f32, duration: f32, line: i64, width: i64, layer: i64| {
+        if duration <= 0.0 {
+            return;
+        }
+        let mut columns: Vec<usize> = (line..line + width.max(1))
+            .filter(|line| (0..GRID_LINES as i64).contains(line))
+            .map(|line| grid_column(line, layer, key_count))
+            .collect();
+        columns.dedup();
+        walls.extend(columns.into_iter().map(|column| (beat, beat + duration, column)));
+    };
+
+    if difficulty.get("_notes").is_some() {
+        for note in objects(&difficulty, "_notes") {
+            let object = if integer(note, "_type") == V2_BOMB_TYPE { GridObject::Bomb } else { GridObject::Note };
+            grid_objects.push((number(note, "_time"), grid_column(integer(note, "_lineIndex"), integer(note, "_lineLayer"), key_count), object));
+        }
+        for obstacle in objects(&difficulty, "_obstacles") {
+            // full height walls start on the bottom layer and crouch walls on the top one
+            let layer = if integer(obstacle, "_type") == 1 { GRID_LAYERS as i64 - 1 } else { 0 };
+            add_wall(number(obstacle, "_time"), number(obstacle, "_duration"), integer(obstacle, "_lineIndex"), integer(obstacle, "_width"), layer);
+        }
+        bpm_changes.extend(objects(&difficulty, "_events")
+            .filter(|event| integer(event, "_type") == V2_BPM_EVENT_TYPE)
+            .map(|event| (number(event, "_time"), number(event, "_floatValue"))));
+        bpm_changes.extend(difficulty.get("_customData")
+            .into_iter()
+            .flat_map(|custom_data| objects(custom_data, "_BPMChanges"))
+            .map(|change| (number(change, "_time"), number(change, "_BPM"))));
+    } else {
+        for note in objects(&difficulty, "colorNotes") {
+            grid_objects.push((number(note, "b"), grid_column(integer(note, "x"), integer(note, "y"), key_count), GridObject::Note));
+        }
+        for bomb in objects(&difficulty, "bombNotes") {
+            grid_objects.push((number(bomb, "b"), grid_column(integer(bomb, "x"), integer(bomb, "y"), key_count), GridObject::Bomb));
+        }
+        for obstacle in objects(&difficulty, "obstacles") {
+            add_wall(number(obstacle, "b"), number(obstacle, "d"), integer(obstacle, "x"), integer(obstacle, "w"), integer(obstacle, "y"));
+        }
+        bpm_changes.extend(objects(&difficulty, "bpmEvents").map(|event| (number(event, "b"), number(event, "m"))));
+    }
+
+    if grid_objects.is_empty() && walls.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("Beat Saber difficulty has no notes or walls".to_string())));
+    }
+
+    // walls that overlap an earlier one in the same column are dropped and so are notes inside of them
+    walls.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting walls"));
+    let mut long_notes: Vec<(f32, f32, usize)> = Vec::with_capacity(walls.len());
+    for wall in walls {
+        let (start_beat, _, column) = wall;
+        if !long_notes.iter().any(|(_, end_beat, long_note_column)| *long_note_column == column && start_beat <= *end_beat) {
+            long_notes.push(wall);
+        }
+    }
+    grid_objects.retain(|(beat, column, _)| !long_notes.iter()
+        .any(|(start_beat, end_beat, long_note_column)| long_note_column == column && (*start_beat..=*end_beat).contains(beat)));
+
+    let initial_bpm = info.get("_beatsPerMinute").and_then(JsonValue::as_f32).filter(|bpm| *bpm > 0.0).unwrap_or(120.0);
+    let bpms_and_stops = process_bpms_and_stops(bpm_changes, Vec::new(), initial_bpm);
+    let start_time = chartinfo.audio_offset;
+    let timing_points = process_timing_points(&bpms_and_stops, &[], start_time);
+    let (beats, bpms, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, start_time, (beats, bpms, change_types));
+
+    // a note and a bomb in the same cell can end up in the same column, the note goes last so it's the one kept
+    grid_objects.sort_by_key(|(_, _, object)| *object == GridObject::Note);
+    let mut notes: Vec<BeatNote> = Vec::with_capacity(grid_objects.len() + long_notes.len() * 2);
+    for (beat, column, object) in grid_objects {
+        let key = match object {
+            GridObject::Note => Key::normal(),
+            GridObject::Bomb => Key::mine(),
+        };
+        notes.push((beat, column, key, None));
+    }
+    for (start_beat, end_beat, column) in long_notes {
+        notes.push((start_beat, column, Key::slider_start(Some(time_at(end_beat))), None));
+        notes.push((end_beat, column, Key::slider_end(), None));
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(notes, key_count, time_at);
+
+    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, None))
+}
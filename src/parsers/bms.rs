@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::models::sound::{KeySound, SoundEffect};
+use crate::utils::string::{
+    StrDefaultExtension,
+    StrNumericDefaultExtension,
+};
+use crate::utils::time::{
+    BpmsAndStops,
+    process_bpms_and_stops,
+    process_timing_points,
+    measure_time_signatures,
+};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// what every #RANDOM rolls, always the first branch so the same file always gives the same chart
+const RANDOM_VALUE: u32 = 1;
+/// #STOPxx values are in 192nds of a 4/4 measure
+const STOP_UNITS_PER_BEAT: f32 = 48.0;
+/// beats two objects can be apart and still be on the same row
+const ROW_MARGIN: f32 = 0.0001;
+
+/// lanes in the order they become columns, scratch goes on the outer side of each player's side
+const LANES_5K: &[&str] = &["16", "11", "12", "13", "14", "15"];
+const LANES_7K: &[&str] = &["16", "11", "12", "13", "14", "15", "18", "19"];
+const LANES_10K: &[&str] = &["16", "11", "12", "13", "14", "15", "21", "22", "23", "24", "25", "26"];
+const LANES_14K: &[&str] = &["16", "11", "12", "13", "14", "15", "18", "19", "21", "22", "23", "24", "25", "28", "29", "26"];
+/// pop'n music charts have 9 buttons and no scratch
+const LANES_PMS: &[&str] = &["11", "12", "13", "14", "15", "22", "23", "24", "25"];
+/// pulses in a beat when a bmson doesn't set its resolution
+const BMSON_DEFAULT_RESOLUTION: f64 = 240.0;
+
+/// an object in a channel, `length` is how many beats its cell takes up
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BmsObject<'a> {
+    pub(crate) beat: f32,
+    pub(crate) length: f32,
+    pub(crate) value: &'a str,
+}
+
+/// objects of every channel, and the length and start beat of every measure
+pub(crate) type ChannelObjects<'a> = (HashMap<String, Vec<BmsObject<'a>>>, Vec<f32>, Vec<f32>);
+
+#[derive(Debug, Clone, Copy)]
+struct BmsNote<'a> {
+    beat: f32,
+    key: Key,
+    end_beat: Option<f32>,
+    value: &'a str,
+}
+
+#[inline]
+pub(crate) fn parse_base36(raw: &str) -> Option<usize> {
+    usize::from_str_radix(raw, 36).ok()
+}
+
+/// LN (5x, 6x) and mine (Dx, Ex) channels share their lanes with the visible 1x and 2x channels
+#[inline]
+fn lane_channel(channel: &str) -> Option<String> {
+    let (player, key) = channel.split_at(1);
+    let player = match player {
+        "1" | "5" | "D" => "1",
+        "2" | "6" | "E" => "2",
+        _ => return None,
+    };
+    Some(format!("{player}{key}"))
+}
+
+/// `#mmmcc:data` lines hold the objects, everything else starting with # is a header
+#[inline]
+pub(crate) fn is_channel_line(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.len() >= 7
+        && bytes[1..4].iter().all(u8::is_ascii_digit)
+        && bytes[6] == b':'
+}
+
+/// the text pushed to `Chart::warnings` when a chart rolls a #RANDOM
+pub(crate) const RANDOM_WARNING: &str = "#RANDOM always takes its first branch, the other branches were dropped";
+
+/// keeps the lines in the taken branch of every #RANDOM block, they can be nested,
+/// and whether there was a #RANDOM to roll
+pub(crate) fn resolve_random(raw_chart: &str) -> (Vec<&str>, bool) {
+    let mut random_values: Vec<u32> = Vec::new();
+    let mut has_random = false;
+    // (taking this branch, took a branch of this #IF already)
+    let mut branches: Vec<(bool, bool)> = Vec::new();
+    let mut lines = Vec::new();
+
+    for line in raw_chart.lines().map(str::trim).filter(|line| line.starts_with('#')) {
+        let (command, value) = line.split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, value)| (command, value.trim()));
+        let value: Option<u32> = value.parse().ok();
+
+        match command.to_ascii_uppercase().as_str() {
+            "#RANDOM" => {
+                random_values.push(RANDOM_VALUE);
+                has_random = true;
+            },
+            "#SETRANDOM" => random_values.push(value.unwrap_or(RANDOM_VALUE)),
+            "#ENDRANDOM" => { random_values.pop(); },
+            "#IF" => {
+                let is_taken = value.is_some() && random_values.last() == value.as_ref();
+                branches.push((is_taken, is_taken));
+            },
+            "#ELSEIF" => {
+                if let Some(branch) = branches.last_mut() {
+                    let is_taken = !branch.1 && value.is_some() && random_values.last() == value.as_ref();
+                    *branch = (is_taken, branch.1 || is_taken);
+                }
+            },
+            "#ELSE" => {
+                if let Some(branch) = branches.last_mut() {
+                    *branch = (!branch.1, true);
+                }
+            },
+            "#ENDIF" | "#END" => { branches.pop(); },
+            _ if branches.iter().all(|(is_taken, _)| *is_taken) => lines.push(line),
+            _ => {},
+        }
+    }
+
+    (lines, has_random)
+}
+
+fn choose_lanes(channels: &[String], player: Option<&str>) -> &'static [&'static str] {
+    let is_used = |lanes: &[&str]| lanes.iter().any(|lane| channels.iter().any(|channel| channel == lane));
+
+    let has_second_player = is_used(&["21", "22", "23", "24", "25", "26", "28", "29"]);
+    let has_extra_keys = is_used(&["18", "19", "28", "29"]);
+    // pop'n charts put their last 4 buttons on the second player's side but never use scratch or its first key,
+    // a 10k chart can skip those too so it also has to say it's for a single player, which a 10k chart never does
+    let is_pms = player == Some("1")
+        && is_used(&["22", "23", "24", "25"])
+        && !is_used(&["16", "21", "26", "18", "19", "28", "29"]);
+    let is_double = player == Some("3");
+
+    match (is_pms, has_second_player || is_double, has_extra_keys) {
+        (true, _, _) => LANES_PMS,
+        (false, true, true) => LANES_14K,
+        (false, true, false) => LANES_10K,
+        (false, false, true) => LANES_7K,
+        (false, false, false) => LANES_5K,
+    }
+}
+
+/// bmson lanes (x) in the order they become columns, "generic-nkeys" and unknown modes use lanes 1 to n
+fn bmson_lanes(mode_hint: &str) -> Option<&'static [u32]> {
+    Some(match mode_hint {
+        "beat-5k" => &[8, 1, 2, 3, 4, 5],
+        "beat-7k" => &[8, 1, 2, 3, 4, 5, 6, 7],
+        "beat-10k" => &[8, 1, 2, 3, 4, 5, 9, 10, 11, 12, 13, 16],
+        "beat-14k" => &[8, 1, 2, 3, 4, 5, 6, 7, 9, 10, 11, 12, 13, 14, 15, 16],
+        "popn-5k" => &[1, 2, 3, 4, 5],
+        "popn-9k" => &[1, 2, 3, 4, 5, 6, 7, 8, 9],
+        _ => return None,
+    })
+}
+
+pub(crate) fn process_timing(
+    channel_objects: &HashMap<String, Vec<BmsObject>>,
+    bpm_table: &HashMap<usize, f32>,
+    stop_table: &HashMap<usize, f32>,
+    initial_bpm: f32,
+) -> BpmsAndStops {
+    let mut bpm_changes: Vec<(f32, f32)> = Vec::new();
+    for object in channel_objects.get("03").into_iter().flatten() {
+        if let Ok(bpm) = u32::from_str_radix(object.value, 16) {
+            bpm_changes.push((object.beat, bpm as f32));
+        }
+    }
+    for object in channel_objects.get("08").into_iter().flatten() {
+        if let Some(bpm) = parse_base36(object.value).and_then(|index| bpm_table.get(&index)) {
+            bpm_changes.push((object.beat, *bpm));
+        }
+    }
+
+    let stops = channel_objects.get("09").into_iter().flatten()
+        .filter_map(|object| parse_base36(object.value)
+            .and_then(|index| stop_table.get(&index))
+            .map(|units| (object.beat, units / STOP_UNITS_PER_BEAT)))
+        .collect();
+
+    process_bpms_and_stops(bpm_changes, stops, initial_bpm)
+}
+
+/// places the objects of every `#mmmcc:data` line on beats, every measure is 4 beats unless channel 02 says otherwise
+pub(crate) fn process_channel_objects<'a>(
+    channel_lines: Vec<(usize, String, &'a str)>,
+    measure_length_changes: &HashMap<usize, f32>,
+) -> ChannelObjects<'a> {
+    let measure_count = channel_lines.iter().map(|(measure, _, _)| measure + 1)
+        .chain(measure_length_changes.keys().map(|measure| measure + 1))
+        .max()
+        .unwrap_or(0);
+    let measure_lengths: Vec<f32> = (0..measure_count)
+        .map(|measure| measure_length_changes.get(&measure).copied().filter(|length| *length > 0.0).unwrap_or(1.0))
+        .collect();
+    let measure_beats: Vec<f32> = measure_lengths.iter()
+        .scan(0.0, |beat, length| {
+            let measure_beat = *beat;
+            *beat += length * 4.0;
+            Some(measure_beat)
+        })
+        .collect();
+
+    let mut channel_objects: HashMap<String, Vec<BmsObject>> = HashMap::new();
+    for (measure, channel, data) in channel_lines {
+        let cell_count = data.len() / 2;
+        if cell_count == 0 {
+            continue;
+        }
+        let measure_beats_length = measure_lengths[measure] * 4.0;
+        let cell_length = measure_beats_length / cell_count as f32;
+
+        let objects = channel_objects.entry(channel).or_default();
+        for i in 0..cell_count {
+            // cells that aren't two base 36 digits are broken and skipped
+            let Some(value) = data.get(i * 2..i * 2 + 2) else {
+                continue;
+            };
+            if value == "00" || !value.bytes().all(|byte| byte.is_ascii_alphanumeric()) {
+                continue;
+            }
+            objects.push(BmsObject {
+                beat: measure_beats[measure] + i as f32 * cell_length,
+                length: cell_length,
+                value,
+            });
+        }
+    }
+    for objects in channel_objects.values_mut() {
+        objects.sort_by(|a, b| a.beat.partial_cmp(&b.beat).expect("Comparison between NaN values when sorting objects"));
+    }
+
+    (channel_objects, measure_lengths, measure_beats)
+}
+
+/// turns the objects of a lane's channels into notes, pairing up long note starts and ends
+fn process_lane_notes<'a>(
+    visible: &[BmsObject<'a>],
+    long_notes: &[BmsObject<'a>],
+    mines: &[BmsObject<'a>],
+    ln_type: u8,
+    ln_obj: Option<usize>,
+) -> Vec<BmsNote<'a>> {
+    let mut notes: Vec<BmsNote> = Vec::with_capacity(visible.len() + long_notes.len() + mines.len());
+
+    // with #LNOBJ a note using that keysound ends the long note started by the note before it
+    for object in visible {
+        let is_ln_end = ln_obj.is_some() && parse_base36(object.value) == ln_obj;
+        match notes.last_mut() {
+            Some(start) if is_ln_end && start.end_beat.is_none() => {
+                start.key = Key::slider_start(None);
+                start.end_beat = Some(object.beat);
+            },
+            _ if is_ln_end => {},
+            _ => notes.push(BmsNote { beat: object.beat, key: Key::normal(), end_beat: None, value: object.value }),
+        }
+    }
+
+    if ln_type == 2 {
+        // #LNTYPE 2 holds for as long as the cells next to each other aren't empty
+        let mut i = 0;
+        while i < long_notes.len() {
+            let start = long_notes[i];
+            let mut end_beat = start.beat + start.length;
+            while i + 1 < long_notes.len() && (long_notes[i + 1].beat - end_beat).abs() < ROW_MARGIN {
+                i += 1;
+                end_beat = long_notes[i].beat + long_notes[i].length;
+            }
+            notes.push(BmsNote { beat: start.beat, key: Key::slider_start(None), end_beat: Some(end_beat), value: start.value });
+            i += 1;
+        }
+    } else {
+        // #LNTYPE 1 has a start and an end object for every long note
+        for pair in long_notes.chunks_exact(2) {
+            notes.push(BmsNote { beat: pair[0].beat, key: Key::slider_start(None), end_beat: Some(pair[1].beat), value: pair[0].value });
+        }
+    }
+
+    for object in mines {
+        notes.push(BmsNote { beat: object.beat, key: Key::mine(), end_beat: None, value: "00" });
+    }
+
+    notes
+}
+
+/// parses .bms, .bme, .bml and .pms charts, the layout (5k, 7k, 10k, 14k with scratch or 9 button pop'n) comes from the channels used.
+/// #RANDOM always rolls 1 so the same file always gives the same chart, the chart gets a warning when it has one
+pub(crate) fn from_bms(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, sound::SoundBank, chart::Chart};
+
+    let (lines, has_random) = resolve_random(raw_chart);
+    if lines.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut soundbank = SoundBank::new();
+
+    let mut initial_bpm = 130.0;
+    let mut bpm_table: HashMap<usize, f32> = HashMap::new();
+    let mut stop_table: HashMap<usize, f32> = HashMap::new();
+    let mut ln_type = 1;
+    let mut ln_obj: Option<usize> = None;
+    let mut player: Option<&str> = None;
+    let mut subtitle = String::new();
+    let mut difficulty = String::new();
+
+    let mut channel_lines: Vec<(usize, String, &str)> = Vec::with_capacity(lines.len());
+    let mut measure_length_changes: HashMap<usize, f32> = HashMap::new();
+
+    for line in lines {
+        if is_channel_line(line) {
+            let measure: usize = line[1..4].parse()?;
+            let channel = line[4..6].to_ascii_uppercase();
+            let data = line[7..].trim();
+            if channel == "02" {
+                measure_length_changes.insert(measure, data.or_default_empty_as(1.0));
+            } else {
+                channel_lines.push((measure, channel, data));
+            }
+            continue;
+        }
+
+        let (header, value) = line[1..].split_once(char::is_whitespace)
+            .map_or((&line[1..], ""), |(header, value)| (header, value.trim()));
+        let header = header.to_ascii_uppercase();
+
+        match header.as_str() {
+            "TITLE" => metadata.title = value.or_default_empty(ChartDefaults::TITLE),
+            "SUBTITLE" => subtitle = value.to_string(),
+            "ARTIST" => metadata.artist = value.or_default_empty(ChartDefaults::ARTIST),
+            // usually who made the chart, like "obj: someone"
+            "SUBARTIST" => metadata.creator = value.or_default_empty(ChartDefaults::CREATOR),
+            "GENRE" => metadata.genre = value.or_default_empty(ChartDefaults::GENRE),
+            "BPM" => initial_bpm = value.or_default_empty_as(initial_bpm),
+            "DIFFICULTY" => difficulty = match value {
+                "1" => "Beginner",
+                "2" => "Normal",
+                "3" => "Hyper",
+                "4" => "Another",
+                "5" => "Insane",
+                _ => "",
+            }.to_string(),
+            "STAGEFILE" => chartinfo.bg_path = value.to_string(),
+            "BACKBMP" if chartinfo.bg_path.is_empty() => chartinfo.bg_path = value.to_string(),
+            "PLAYER" => player = Some(value),
+            "LNTYPE" => ln_type = value.or_default_empty_as(1),
+            "LNOBJ" => ln_obj = parse_base36(value),
+            _ if header.len() == 5 && header.starts_with("WAV") => {
+                if let Some(index) = parse_base36(&header[3..]) {
+                    soundbank.add_sound_sample_with_index(index, value.to_string());
+                }
+            },
+            _ if header.len() == 5 && header.starts_with("BPM") || header.len() == 7 && header.starts_with("EXBPM") => {
+                if let Some(index) = parse_base36(&header[header.len() - 2..]) {
+                    bpm_table.insert(index, value.or_default_empty_as(initial_bpm));
+                }
+            },
+            _ if header.len() == 6 && header.starts_with("STOP") => {
+                if let Some(index) = parse_base36(&header[4..]) {
+                    stop_table.insert(index, value.or_default_empty_as(0.0));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    chartinfo.difficulty_name = if !subtitle.is_empty() {
+        subtitle
+    } else {
+        difficulty.or_default_empty(ChartDefaults::DIFFICULTY_NAME)
+    };
+
+    let (channel_objects, measure_lengths, measure_beats) = process_channel_objects(channel_lines, &measure_length_changes);
+
+    let bpms_and_stops = process_timing(&channel_objects, &bpm_table, &stop_table, initial_bpm);
+    let timing_points = process_timing_points(&bpms_and_stops, &measure_time_signatures(&measure_lengths, &measure_beats), 0);
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms_and_durations, change_types));
+
+    // the bgm channel holds keysounds that play on their own
+    for object in channel_objects.get("01").into_iter().flatten() {
+        if let Some(index) = parse_base36(object.value).filter(|index| soundbank.get_sound_sample(*index).is_some()) {
+            soundbank.add_sound_effect(SoundEffect::new(time_at(object.beat), 100, index));
+        }
+    }
+
+    let used_lanes: Vec<String> = channel_objects.iter()
+        .filter(|(_, objects)| !objects.is_empty())
+        .filter_map(|(channel, _)| lane_channel(channel))
+        .collect();
+    let lanes = choose_lanes(&used_lanes, player);
+    let key_count = lanes.len();
+    chartinfo.key_count = key_count as u8;
+
+    let mut objects: Vec<BeatNote> = Vec::new();
+    for (column, lane) in lanes.iter().enumerate() {
+        let (player, key) = lane.split_at(1);
+        let ln_player = if player == "1" { "5" } else { "6" };
+        let mine_player = if player == "1" { "D" } else { "E" };
+        let get_objects = |channel: String| channel_objects.get(&channel).map_or(&[][..], Vec::as_slice);
+
+        let notes = process_lane_notes(
+            get_objects(lane.to_string()),
+            get_objects(format!("{ln_player}{key}")),
+            get_objects(format!("{mine_player}{key}")),
+            ln_type,
+            ln_obj,
+        );
+
+        for note in notes {
+            let key_sound = parse_base36(note.value)
+                .filter(|index| soundbank.get_sound_sample(*index).is_some())
+                .map(|index| KeySound::with_custom(100, index, None));
+
+            match note.end_beat {
+                Some(end_beat) => {
+                    objects.push((note.beat, column, Key::slider_start(Some(time_at(end_beat))), key_sound));
+                    objects.push((end_beat, column, Key::slider_end(), None));
+                },
+                None => objects.push((note.beat, column, note.key, key_sound)),
+            }
+        }
+    }
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    let mut chart = Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank));
+    if has_random {
+        chart.warnings.push(RANDOM_WARNING.to_string());
+    }
+
+    Ok(chart)
+}
+
+
+/// parses bmson charts, notes are placed in pulses and every sound channel is a sample
+pub(crate) fn from_bmson(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, sound::SoundBank, chart::Chart};
+
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    let bmson = JsonValue::parse(raw_chart)
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid bmson: {e}")))?;
+    let info = bmson.get("info")
+        .ok_or_else(|| errors::ParseError::<GameMode>::InvalidChart("Missing info in bmson".to_string()))?;
+    let info_str = |key: &str| info.get(key).and_then(JsonValue::as_str).unwrap_or("");
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut soundbank = SoundBank::new();
+
+    metadata.title = info_str("title").or_default_empty(ChartDefaults::TITLE);
+    metadata.artist = info_str("artist").or_default_empty(ChartDefaults::ARTIST);
+    metadata.genre = info_str("genre").or_default_empty(ChartDefaults::GENRE);
+    let subartists: Vec<&str> = info.get("subartists").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(JsonValue::as_str)
+        .collect();
+    metadata.creator = subartists.join(", ").or_default_empty(ChartDefaults::CREATOR);
+
+    chartinfo.difficulty_name = [info_str("chart_name"), info_str("subtitle")].into_iter()
+        .find(|name| !name.trim().is_empty())
+        .unwrap_or("")
+        .or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+    chartinfo.bg_path = [info_str("back_image"), info_str("eyecatch_image")].into_iter()
+        .find(|path| !path.is_empty())
+        .unwrap_or("")
+        .to_string();
+
+    let resolution = info.get("resolution").and_then(JsonValue::as_f64)
+        .filter(|resolution| *resolution > 0.0)
+        .unwrap_or(BMSON_DEFAULT_RESOLUTION);
+    let to_beat = |value: &JsonValue, key: &str| value.get(key)
+        .and_then(JsonValue::as_f64)
+        .map(|pulses| (pulses / resolution) as f32);
+    let initial_bpm = info.get("init_bpm").and_then(JsonValue::as_f32).unwrap_or(130.0);
+
+    let bpm_changes: Vec<(f32, f32)> = bmson.get("bpm_events").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(|event| Some((to_beat(event, "y")?, event.get("bpm")?.as_f32()?)))
+        .collect();
+    let stops: Vec<(f32, f32)> = bmson.get("stop_events").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(|event| Some((to_beat(event, "y")?, to_beat(event, "duration")?)))
+        .filter(|(_, length)| *length > 0.0)
+        .collect();
+    let bpms_and_stops = process_bpms_and_stops(bpm_changes, stops, initial_bpm);
+
+    // bar lines are where each measure starts
+    let mut line_beats: Vec<f32> = bmson.get("lines").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(|line| to_beat(line, "y"))
+        .collect();
+    line_beats.sort_by(|a, b| a.partial_cmp(b).expect("Comparison between NaN values when sorting bar lines"));
+    line_beats.dedup();
+    let measure_lengths: Vec<f32> = line_beats.windows(2).map(|pair| (pair[1] - pair[0]) / 4.0).collect();
+    let timing_points = process_timing_points(&bpms_and_stops, &measure_time_signatures(&measure_lengths, &line_beats), 0);
+
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms_and_durations, change_types));
+
+    let sound_channels = bmson.get("sound_channels").map_or(&[][..], |channels| channels.as_array().unwrap_or(&[]));
+    let mode_hint = info_str("mode_hint");
+    let generic_key_count = mode_hint.strip_prefix("generic-")
+        .and_then(|keys| keys.strip_suffix("keys"))
+        .and_then(|keys| keys.parse::<u32>().ok());
+    let lanes: Vec<u32> = match (bmson_lanes(mode_hint), generic_key_count) {
+        (Some(lanes), _) => lanes.to_vec(),
+        (None, Some(key_count)) => (1..=key_count).collect(),
+        (None, None) => {
+            let max_lane = sound_channels.iter()
+                .flat_map(|channel| channel.get("notes").into_iter().flat_map(JsonValue::iter_array))
+                .filter_map(|note| note.get("x").and_then(JsonValue::as_i64))
+                .max()
+                .unwrap_or(7);
+            (1..=max_lane.max(1) as u32).collect()
+        },
+    };
+    let key_count = lanes.len();
+    chartinfo.key_count = key_count as u8;
+
+    let mut objects: Vec<BeatNote> = Vec::new();
+    for channel in sound_channels {
+        let name = channel.get("name").and_then(JsonValue::as_str).unwrap_or("");
+        let sample = (!name.is_empty()).then(|| soundbank.add_sound_sample(name.to_string()));
+
+        // "c" only says whether the sample restarts, which doesn't change the notes
+        for note in channel.get("notes").into_iter().flat_map(JsonValue::iter_array) {
+            let Some(beat) = to_beat(note, "y") else {
+                continue;
+            };
+            let column = note.get("x")
+                .and_then(JsonValue::as_i64)
+                .and_then(|x| lanes.iter().position(|lane| *lane as i64 == x));
+            let length = to_beat(note, "l").unwrap_or(0.0);
+
+            match column {
+                Some(column) => {
+                    let key_sound = sample.map(|index| KeySound::with_custom(100, index, None));
+                    if length > 0.0 {
+                        objects.push((beat, column, Key::slider_start(Some(time_at(beat + length))), key_sound));
+                        objects.push((beat + length, column, Key::slider_end(), None));
+                    } else {
+                        objects.push((beat, column, Key::normal(), key_sound));
+                    }
+                },
+                // x of 0 or null is bgm, and notes in lanes the mode doesn't have get played the same way
+                None => if let Some(index) = sample {
+                    soundbank.add_sound_effect(SoundEffect::new(time_at(beat), 100, index));
+                },
+            }
+        }
+    }
+    soundbank.sound_effects.sort_by_key(|sound_effect| sound_effect.time);
+
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank)))
+}
\ No newline at end of file
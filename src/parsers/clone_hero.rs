@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::utils::time::{BpmsAndStops, process_bpms_and_stops, process_timing_points};
+use crate::parsers::midi::read_midi;
+use crate::utils::string::{
+    StrDefaultExtension,
+    StrNumericDefaultExtension,
+};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+const DEFAULT_RESOLUTION: f32 = 192.0;
+const DIFFICULTIES: [&str; 4] = ["Easy", "Medium", "Hard", "Expert"];
+/// the 5 frets, open notes get a sixth column after them when a chart has any
+const FRET_COUNT: usize = 5;
+/// white 1 to 3 then black 1 to 3
+const GHL_FRET_COUNT: usize = 6;
+
+/// .chart section suffixes and .mid track names of the guitar-like instruments, and if they're 6 fret
+const INSTRUMENTS: [(&str, &str, &str, bool); 7] = [
+    ("Single", "PART GUITAR", "Guitar", false),
+    ("DoubleGuitar", "PART GUITAR COOP", "Guitar Co-op", false),
+    ("DoubleBass", "PART BASS", "Bass", false),
+    ("DoubleRhythm", "PART RHYTHM", "Rhythm", false),
+    ("Keys", "PART KEYS", "Keys", false),
+    ("GHLGuitar", "PART GUITAR GHL", "GHL Guitar", true),
+    ("GHLBass", "PART BASS GHL", "GHL Bass", true),
+];
+
+/// lowest note of each difficulty in a .mid, 5 fret green is one above it and 6 fret open is on it
+const MIDI_DIFFICULTY_NOTES: [u8; 4] = [59, 71, 83, 95];
+const GHL_MIDI_DIFFICULTY_NOTES: [u8; 4] = [58, 70, 82, 94];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Fret {
+    Column(usize),
+    Open,
+}
+
+/// a fret note as (beat, fret, sustain length in beats)
+type FretNote = (f32, Fret, f32);
+
+/// `.chart` frets, 5 and 6 are forced and tap flags and 7 is open
+#[inline]
+fn chart_fret(fret: u32, is_ghl: bool) -> Option<Fret> {
+    match (fret, is_ghl) {
+        (0..=4, _) => Some(Fret::Column(fret as usize)),
+        (8, true) => Some(Fret::Column(5)),
+        (7, _) => Some(Fret::Open),
+        _ => None,
+    }
+}
+
+#[inline]
+fn midi_fret(key: u8, difficulty: usize, is_ghl: bool) -> Option<Fret> {
+    let (lowest_note, fret_count) = if is_ghl {
+        (GHL_MIDI_DIFFICULTY_NOTES[difficulty], GHL_FRET_COUNT)
+    } else {
+        (MIDI_DIFFICULTY_NOTES[difficulty], FRET_COUNT)
+    };
+    match key.checked_sub(lowest_note)? as usize {
+        0 => Some(Fret::Open),
+        fret if fret <= fret_count => Some(Fret::Column(fret - 1)),
+        _ => None,
+    }
+}
+
+/// turns frets into columns, sustains that run into the next note on their fret can't be held and become normal notes
+fn build_chart(
+    metadata: &models::metadata::Metadata,
+    chartinfo: &models::chartinfo::ChartInfo,
+    notes: Vec<FretNote>,
+    is_ghl: bool,
+    bpms_and_stops: &BpmsAndStops,
+    time_signatures: &[(f32, f32)],
+) -> models::chart::Chart {
+    use models::{hitobjects::{HitObjects, BeatNote}, chart::Chart};
+
+    let has_open_notes = notes.iter().any(|(_, fret, _)| *fret == Fret::Open);
+    let key_count = if is_ghl {
+        GHL_FRET_COUNT
+    } else if has_open_notes {
+        FRET_COUNT + 1
+    } else {
+        FRET_COUNT
+    };
+
+    let mut chartinfo = chartinfo.clone();
+    chartinfo.key_count = key_count as u8;
+
+    let start_time = chartinfo.audio_offset;
+    let timing_points = process_timing_points(bpms_and_stops, time_signatures, start_time);
+    let (beats, bpms_and_durations, change_types) = bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, start_time, (beats, bpms_and_durations, change_types));
+
+    let mut columns: Vec<(f32, usize, f32)> = notes.into_iter()
+        .filter_map(|(beat, fret, length)| match fret {
+            Fret::Column(column) => Some((beat, column, length)),
+            Fret::Open if !is_ghl => Some((beat, FRET_COUNT, length)),
+            Fret::Open => None,
+        })
+        .collect();
+    columns.sort_by(|a, b| (a.1, a.0).partial_cmp(&(b.1, b.0)).expect("Comparison between NaN values when sorting notes"));
+    columns.dedup_by(|later, earlier| later.1 == earlier.1 && later.0 == earlier.0);
+
+    let mut objects: Vec<BeatNote> = Vec::with_capacity(columns.len());
+    for (i, (beat, column, length)) in columns.iter().enumerate() {
+        let next_beat = columns.get(i + 1).filter(|next| next.1 == *column).map(|next| next.0);
+        let end_beat = beat + length;
+        if *length > 0.0 && next_beat.is_none_or(|next_beat| end_beat < next_beat) {
+            objects.push((*beat, *column, Key::slider_start(Some(time_at(end_beat))), None));
+            objects.push((end_beat, *column, Key::slider_end(), None));
+        } else {
+            objects.push((*beat, *column, Key::normal(), None));
+        }
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    let mut chart = Chart::new(metadata.clone(), chartinfo, timing_points, hitobjects, None);
+    if is_ghl && has_open_notes {
+        chart.warnings.push("Open notes of 6 fret Clone Hero charts have no column and were dropped".to_string());
+    }
+
+    chart
+}
+
+/// sections of a .chart as their name and lines, every section is `[Name]` then its lines between { and }
+fn chart_sections(raw_chart: &str) -> Vec<(&str, Vec<&str>)> {
+    let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+    for line in raw_chart.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            sections.push((name, Vec::new()));
+        } else if let Some((_, lines)) = sections.last_mut() {
+            if !line.is_empty() && line != "{" && line != "}" {
+                lines.push(line);
+            }
+        }
+    }
+    sections
+}
+
+/// every line is `key = value`, with the tick as the key in everything but [Song]
+#[inline]
+fn key_value<'a>(line: &&'a str) -> Option<(&'a str, &'a str)> {
+    line.split_once('=').map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// difficulty index, instrument name and if it's 6 fret from a section name like ExpertSingle
+fn chart_instrument(section: &str) -> Option<(usize, &'static str, bool)> {
+    let (difficulty, suffix) = DIFFICULTIES.iter()
+        .enumerate()
+        .find_map(|(i, difficulty)| Some((i, section.strip_prefix(difficulty)?)))?;
+    let (_, _, instrument, is_ghl) = INSTRUMENTS.iter().find(|(chart_suffix, ..)| *chart_suffix == suffix)?;
+    Some((difficulty, instrument, *is_ghl))
+}
+
+/// parses every guitar-like difficulty of a Clone Hero .chart, drums and other instruments are skipped
+pub(crate) fn from_chart_all(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo};
+
+    let raw_chart = raw_chart.trim_start_matches('\u{feff}');
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let sections = chart_sections(raw_chart);
+    let section_lines = |name: &str| sections.iter()
+        .find(|(section, _)| *section == name)
+        .map(|(_, lines)| lines.as_slice())
+        .unwrap_or_default();
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut resolution = DEFAULT_RESOLUTION;
+
+    for (key, value) in section_lines("Song").iter().filter_map(key_value) {
+        let value = value.trim_matches('"');
+        match key {
+            "Name" => metadata.title = value.or_default_empty(ChartDefaults::TITLE),
+            "Artist" => metadata.artist = value.or_default_empty(ChartDefaults::ARTIST),
+            "Charter" => metadata.creator = value.or_default_empty(ChartDefaults::CREATOR),
+            "Genre" => metadata.genre = value.or_default_empty(ChartDefaults::GENRE),
+            "MusicStream" => chartinfo.song_path = value.to_string(),
+            "Resolution" => resolution = value.or_default_empty_as(DEFAULT_RESOLUTION).max(1.0),
+            // both are in seconds
+            "Offset" => chartinfo.audio_offset = (value.or_default_empty_as(0.0_f32) * 1000.0).round() as i32,
+            "PreviewStart" => chartinfo.preview_time = (value.or_default_empty_as(0.0_f32) * 1000.0).round() as i32,
+            _ => {},
+        }
+    }
+
+    let mut bpm_changes: Vec<(f32, f32)> = Vec::new();
+    let mut time_signatures: Vec<(f32, f32)> = Vec::new();
+    for (tick, event) in section_lines("SyncTrack").iter().filter_map(key_value) {
+        let Ok(tick) = tick.parse::<u32>() else {
+            continue;
+        };
+        let beat = tick as f32 / resolution;
+        let mut fields = event.split_whitespace();
+        match (fields.next(), fields.next().and_then(|value| value.parse::<f32>().ok())) {
+            // bpms are in thousandths
+            (Some("B"), Some(bpm)) => bpm_changes.push((beat, bpm / 1000.0)),
+            // the denominator is the power of 2 it is, 4 when it's left out
+            (Some("TS"), Some(numerator)) => {
+                let denominator_power = fields.next().and_then(|value| value.parse::<i32>().ok()).unwrap_or(2);
+                time_signatures.push((beat, numerator * 4.0 / 2f32.powi(denominator_power)));
+            },
+            _ => {},
+        }
+    }
+    time_signatures.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting time signatures"));
+    let mut previous_length = 4.0;
+    time_signatures.retain(|(_, length)| {
+        let is_change = *length != previous_length;
+        previous_length = *length;
+        is_change
+    });
+
+    let bpms_and_stops = process_bpms_and_stops(bpm_changes, Vec::new(), 120.0);
+
+    let mut charts = Vec::new();
+    for (section, lines) in &sections {
+        let Some((difficulty, instrument, is_ghl)) = chart_instrument(section) else {
+            continue;
+        };
+
+        let notes: Vec<FretNote> = lines.iter()
+            .filter_map(key_value)
+            .filter_map(|(tick, event)| {
+                let mut fields = event.split_whitespace();
+                if fields.next()? != "N" {
+                    return None;
+                }
+                let tick: u32 = tick.parse().ok()?;
+                let fret = chart_fret(fields.next()?.parse().ok()?, is_ghl)?;
+                let length: u32 = fields.next().and_then(|length| length.parse().ok()).unwrap_or(0);
+                Some((tick as f32 / resolution, fret, length as f32 / resolution))
+            })
+            .collect();
+        if notes.is_empty() {
+            continue;
+        }
+
+        chartinfo.difficulty_name = format!("{} {instrument}", DIFFICULTIES[difficulty]);
+        charts.push(build_chart(&metadata, &chartinfo, notes, is_ghl, &bpms_and_stops, &time_signatures));
+    }
+
+    if charts.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No guitar, bass, rhythm or keys notes in Clone Hero chart".to_string())));
+    }
+    Ok(charts)
+}
+
+/// parses the first guitar-like difficulty of a Clone Hero .chart
+pub(crate) fn from_chart(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    from_chart_all(raw_chart).map(|mut charts| charts.swap_remove(0))
+}
+
+/// parses every guitar-like difficulty of a Clone Hero notes.mid, the rest of its metadata is in song.ini
+pub(crate) fn from_notes_mid_all(raw_mid: &[u8]) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo};
+
+    let midi = read_midi(raw_mid)?;
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    // the tempo track's name is the song's
+    metadata.title = midi.tracks.first()
+        .map_or("", |track| track.name.as_str())
+        .or_default_empty(ChartDefaults::TITLE);
+
+    let bpms_and_stops = midi.bpms();
+    let time_signatures = midi.time_signatures();
+    // notes shorter than a third of a beat are never sustains
+    let sustain_cutoff = midi.resolution as u32 / 3;
+
+    let mut charts = Vec::new();
+    for (_, track_name, instrument, is_ghl) in INSTRUMENTS {
+        let Some(track) = midi.tracks.iter().find(|track| track.name == track_name) else {
+            continue;
+        };
+
+        let mut difficulty_notes: HashMap<usize, Vec<FretNote>> = HashMap::new();
+        for note in track.notes() {
+            for difficulty in 0..DIFFICULTIES.len() {
+                if let Some(fret) = midi_fret(note.key, difficulty, is_ghl) {
+                    let length = note.end_tick.saturating_sub(note.tick);
+                    let length = if length > sustain_cutoff { midi.beat_at(length) } else { 0.0 };
+                    difficulty_notes.entry(difficulty).or_default().push((midi.beat_at(note.tick), fret, length));
+                }
+            }
+        }
+
+        // each track has its difficulties from easiest to hardest like a .chart
+        for (difficulty, difficulty_name) in DIFFICULTIES.iter().enumerate() {
+            let Some(notes) = difficulty_notes.remove(&difficulty) else {
+                continue;
+            };
+            chartinfo.difficulty_name = format!("{difficulty_name} {instrument}");
+            charts.push(build_chart(&metadata, &chartinfo, notes, is_ghl, &bpms_and_stops, &time_signatures));
+        }
+    }
+
+    if charts.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No guitar, bass, rhythm or keys tracks in Clone Hero notes.mid".to_string())));
+    }
+    Ok(charts)
+}
+
+/// parses the first guitar-like difficulty of a Clone Hero notes.mid
+pub(crate) fn from_notes_mid(raw_mid: &[u8]) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    from_notes_mid_all(raw_mid).map(|mut charts| charts.swap_remove(0))
+}
\ No newline at end of file
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::models::sound::{KeySound, SoundEffect};
+use crate::parsers::bms::{
+    parse_base36,
+    is_channel_line,
+    resolve_random,
+    RANDOM_WARNING,
+    process_channel_objects,
+    process_timing,
+};
+use crate::utils::time::{
+    process_timing_points,
+    measure_time_signatures,
+};
+use crate::utils::string::{
+    StrDefaultExtension,
+    StrNumericDefaultExtension,
+};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+/// DTXMania's 10 lane drum layout, lanes sharing a column are joined with +
+pub(crate) const DEFAULT_LAYOUT: &str = "LC,HH+HHO,LP+LBD,SD,HT,BD,LT,FT,CY,RD";
+
+/// drum lanes and their channels
+const DRUM_LANES: [(&str, &str); 12] = [
+    ("HH", "11"), ("SD", "12"), ("BD", "13"), ("HT", "14"), ("LT", "15"), ("CY", "16"),
+    ("FT", "17"), ("HHO", "18"), ("RD", "19"), ("LC", "1A"), ("LP", "1B"), ("LBD", "1C"),
+];
+/// red, green, blue and open, guitar channels are 20 to 27 and bass channels A0 to A7 with R = 4, G = 2 and B = 1
+const GUITAR_LANES: [&str; 4] = ["GR", "GG", "GB", "GO"];
+const BASS_LANES: [&str; 4] = ["BR", "BG", "BB", "BO"];
+
+/// lanes a channel puts notes in, guitar and bass channels can press more than one button at once
+fn channel_lanes(channel: &str) -> Vec<&'static str> {
+    if let Some((lane, _)) = DRUM_LANES.iter().find(|(_, lane_channel)| *lane_channel == channel) {
+        return vec![lane];
+    }
+
+    let (lanes, buttons) = match channel.as_bytes() {
+        [b'2', buttons @ b'0'..=b'7'] => (&GUITAR_LANES, buttons - b'0'),
+        [b'A', buttons @ b'0'..=b'7'] => (&BASS_LANES, buttons - b'0'),
+        _ => return Vec::new(),
+    };
+    if buttons == 0 {
+        return vec![lanes[3]];
+    }
+    [4, 2, 1].iter()
+        .zip(lanes)
+        .filter(|(bit, _)| buttons & *bit != 0)
+        .map(|(_, lane)| *lane)
+        .collect()
+}
+
+/// sound effect channels, 61 to 92 are SE1 to SE32 and 01 is the bgm
+#[inline]
+fn is_sound_effect_channel(channel: &str) -> bool {
+    channel == "01" || u8::from_str_radix(channel, 16).is_ok_and(|channel| (0x61..=0x92).contains(&channel))
+}
+
+/// the column of every lane in a layout like "LC,HH+HHO,SD"
+fn parse_layout(layout: &str) -> Result<(HashMap<&str, usize>, usize), errors::ParseError<GameMode>> {
+    let mut lane_columns = HashMap::new();
+    let columns: Vec<&str> = layout.split(',').map(str::trim).filter(|column| !column.is_empty()).collect();
+
+    for (column, lanes) in columns.iter().enumerate() {
+        for lane in lanes.split('+').map(str::trim) {
+            let is_known = DRUM_LANES.iter().any(|(drum_lane, _)| *drum_lane == lane)
+                || GUITAR_LANES.contains(&lane)
+                || BASS_LANES.contains(&lane);
+            if !is_known {
+                return Err(errors::ParseError::InvalidChart(format!("Unknown DTX lane '{lane}' in the layout")));
+            }
+            lane_columns.insert(lane, column);
+        }
+    }
+
+    if columns.is_empty() {
+        return Err(errors::ParseError::InvalidChart("The DTX layout has no lanes".to_string()));
+    }
+    Ok((lane_columns, columns.len()))
+}
+
+/// parses DTXMania charts with each column of `layout` holding the lanes joined with + in it,
+/// notes in lanes the layout leaves out still play their sounds. #RANDOM always rolls 1 like in `from_bms`
+pub(crate) fn from_dtx_with_layout(raw_chart: &str, layout: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, sound::SoundBank, chart::Chart};
+
+    let (lane_columns, key_count) = parse_layout(layout)?;
+
+    // everything after a ; is a comment
+    let uncommented_chart: String = raw_chart.lines()
+        .map(|line| line.split_once(';').map_or(line, |(line, _)| line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (lines, has_random) = resolve_random(&uncommented_chart);
+    if lines.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut soundbank = SoundBank::new();
+
+    let mut initial_bpm = 120.0;
+    let mut bpm_table: HashMap<usize, f32> = HashMap::new();
+    let mut volumes: HashMap<usize, u8> = HashMap::new();
+    let mut levels: HashMap<String, String> = HashMap::new();
+
+    let mut channel_lines: Vec<(usize, String, &str)> = Vec::with_capacity(lines.len());
+    let mut measure_length_changes: HashMap<usize, f32> = HashMap::new();
+
+    for line in lines {
+        if is_channel_line(line) {
+            let measure: usize = line[1..4].parse()?;
+            let channel = line[4..6].to_ascii_uppercase();
+            let data = line[7..].trim();
+            if channel == "02" {
+                measure_length_changes.insert(measure, data.or_default_empty_as(1.0));
+            } else {
+                channel_lines.push((measure, channel, data));
+            }
+            continue;
+        }
+
+        // headers are written as both "#TITLE: value" and "#TITLE value"
+        let (header, value) = line[1..].split_once(|c: char| c == ':' || c.is_whitespace())
+            .map_or((&line[1..], ""), |(header, value)| (header, value.trim_start_matches(':').trim()));
+        let header = header.to_ascii_uppercase();
+
+        match header.as_str() {
+            "TITLE" => metadata.title = value.or_default_empty(ChartDefaults::TITLE),
+            "ARTIST" => metadata.artist = value.or_default_empty(ChartDefaults::ARTIST),
+            "GENRE" => metadata.genre = value.or_default_empty(ChartDefaults::GENRE),
+            "BPM" => initial_bpm = value.or_default_empty_as(initial_bpm),
+            "DLEVEL" | "GLEVEL" | "BLEVEL" => { levels.insert(header.clone(), value.to_string()); },
+            "PREIMAGE" => chartinfo.bg_path = value.to_string(),
+            "STAGEFILE" | "BACKGROUND" | "WALL" if chartinfo.bg_path.is_empty() => chartinfo.bg_path = value.to_string(),
+            _ if header.len() == 5 && header.starts_with("WAV") => {
+                if let Some(index) = parse_base36(&header[3..]) {
+                    soundbank.add_sound_sample_with_index(index, value.to_string());
+                }
+            },
+            _ if header.len() == 8 && (header.starts_with("VOLUME") || header.starts_with("WAVVOL")) => {
+                if let Some(index) = parse_base36(&header[6..]) {
+                    volumes.insert(index, value.or_default_empty_as(100.0_f32).clamp(0.0, 100.0) as u8);
+                }
+            },
+            _ if header.len() == 5 && header.starts_with("BPM") => {
+                if let Some(index) = parse_base36(&header[3..]) {
+                    bpm_table.insert(index, value.or_default_empty_as(initial_bpm));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    // the level shown is the one for the instrument the layout starts with
+    let first_lane = layout.split([',', '+']).next().unwrap_or("").trim();
+    let level_header = if GUITAR_LANES.contains(&first_lane) {
+        "GLEVEL"
+    } else if BASS_LANES.contains(&first_lane) {
+        "BLEVEL"
+    } else {
+        "DLEVEL"
+    };
+    chartinfo.difficulty_name = levels.get(level_header)
+        .filter(|level| !level.is_empty())
+        .map_or(ChartDefaults::DIFFICULTY_NAME.to_string(), |level| format!("Level {level}"));
+    chartinfo.key_count = key_count as u8;
+
+    let (channel_objects, measure_lengths, measure_beats) = process_channel_objects(channel_lines, &measure_length_changes);
+
+    let bpms_and_stops = process_timing(&channel_objects, &bpm_table, &HashMap::new(), initial_bpm);
+    let timing_points = process_timing_points(&bpms_and_stops, &measure_time_signatures(&measure_lengths, &measure_beats), 0);
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms_and_durations, change_types));
+
+    let mut objects: Vec<BeatNote> = Vec::new();
+    for (channel, channel_objects) in &channel_objects {
+        let lanes = channel_lanes(channel);
+        let columns: Vec<usize> = lanes.iter().filter_map(|lane| lane_columns.get(lane).copied()).collect();
+        let is_sound_effect = is_sound_effect_channel(channel) || !lanes.is_empty() && columns.is_empty();
+        if columns.is_empty() && !is_sound_effect {
+            continue;
+        }
+
+        for object in channel_objects {
+            let sample = parse_base36(object.value).filter(|index| soundbank.get_sound_sample(*index).is_some());
+            let volume = sample.and_then(|index| volumes.get(&index).copied()).unwrap_or(100);
+
+            if is_sound_effect {
+                if let Some(index) = sample {
+                    soundbank.add_sound_effect(SoundEffect::new(time_at(object.beat), volume, index));
+                }
+                continue;
+            }
+
+            let key_sound = sample.map(|index| KeySound::with_custom(volume, index, None));
+            for column in &columns {
+                objects.push((object.beat, *column, Key::normal(), key_sound));
+            }
+        }
+    }
+    soundbank.sound_effects.sort_by_key(|sound_effect| sound_effect.time);
+
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    let mut chart = Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank));
+    if has_random {
+        chart.warnings.push(RANDOM_WARNING.to_string());
+    }
+
+    Ok(chart)
+}
+
+/// parses DTXMania drum charts into DTXMania's own 10 lane layout
+pub(crate) fn from_dtx(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    from_dtx_with_layout(raw_chart, DEFAULT_LAYOUT)
+}
\ No newline at end of file
@@ -0,0 +1,120 @@
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    TimingChangeType,
+    GameMode,
+    Key,
+};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// each side has 4 lanes, the side that has to hit a section's notes has 0 to 3
+const LANE_COUNT: usize = 4;
+/// sections are 16 steps unless they say otherwise
+const DEFAULT_SECTION_BEATS: f32 = 4.0;
+/// the instrumental every song loads next to its chart
+const SONG_PATH: &str = "Inst.ogg";
+
+/// the beats a section lasts, `sectionBeats` in Psych Engine charts and `lengthInSteps` everywhere else
+#[inline]
+fn section_beats(section: &JsonValue) -> f32 {
+    section.get("sectionBeats")
+        .and_then(JsonValue::as_f32)
+        .or_else(|| section.get("lengthInSteps").and_then(JsonValue::as_f32).map(|steps| steps / 4.0))
+        .filter(|beats| *beats > 0.0)
+        .unwrap_or(DEFAULT_SECTION_BEATS)
+}
+
+/// parses Friday Night Funkin' charts, the player's side as 4K or both sides as 8K with the opponent's on the left
+fn parse_fnf(raw_chart: &str, both_sides: bool) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{
+        metadata::Metadata,
+        chartinfo::ChartInfo,
+        timing_points::TimingPoints,
+        timeline::{HitObjectTimeline, TimelineHitObject, TimingPointTimeline, TimelineTimingPoint},
+        hitobjects::HitObjects,
+        chart::Chart,
+    };
+
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    let fnf = JsonValue::parse(raw_chart)
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid Friday Night Funkin' chart: {e}")))?;
+    // the chart is usually inside of "song"
+    let song = fnf.get("song").filter(|song| song.get("notes").is_some()).unwrap_or(&fnf);
+    let sections = song.get("notes")
+        .and_then(JsonValue::as_array)
+        .ok_or_else(|| errors::ParseError::<GameMode>::InvalidChart("Missing notes in Friday Night Funkin' chart".to_string()))?;
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    metadata.title = song.get("song").and_then(JsonValue::as_str).unwrap_or("").or_default_empty(ChartDefaults::TITLE);
+    chartinfo.song_path = SONG_PATH.to_string();
+    let key_count = if both_sides { LANE_COUNT * 2 } else { LANE_COUNT };
+    chartinfo.key_count = key_count as u8;
+
+    let mut bpm = song.get("bpm").and_then(JsonValue::as_f32).filter(|bpm| *bpm > 0.0).unwrap_or(120.0);
+    let mut timing_timeline = TimingPointTimeline::with_capacity(sections.len());
+    timing_timeline.add_sorted(TimelineTimingPoint { time: 0, value: bpm, change_type: TimingChangeType::Bpm });
+
+    let mut timeline = HitObjectTimeline::with_capacity(sections.len() * 8);
+    let mut section_time: f32 = 0.0;
+    for section in sections {
+        let changes_bpm = section.get("changeBPM").and_then(JsonValue::as_bool).unwrap_or(false);
+        if let Some(section_bpm) = section.get("bpm").and_then(JsonValue::as_f32).filter(|section_bpm| changes_bpm && *section_bpm > 0.0 && *section_bpm != bpm) {
+            bpm = section_bpm;
+            timing_timeline.add_sorted(TimelineTimingPoint { time: section_time.round() as i32, value: bpm, change_type: TimingChangeType::Bpm });
+        }
+        section_time += section_beats(section) * 60000.0 / bpm;
+
+        let must_hit = section.get("mustHitSection").and_then(JsonValue::as_bool).unwrap_or(true);
+        for note in section.get("sectionNotes").into_iter().flat_map(JsonValue::iter_array) {
+            let Some(fields) = note.as_array() else {
+                continue;
+            };
+            let (Some(time), Some(lane)) = (fields.first().and_then(JsonValue::as_f64), fields.get(1).and_then(JsonValue::as_i64)) else {
+                continue;
+            };
+            // events are on lane -1 and extra keys past 7 aren't part of either side
+            if !(0..LANE_COUNT as i64 * 2).contains(&lane) {
+                continue;
+            }
+            let lane = lane as usize;
+            let is_player = (lane < LANE_COUNT) == must_hit;
+            let column = match (is_player, both_sides) {
+                (true, true) => LANE_COUNT + lane % LANE_COUNT,
+                (true, false) | (false, true) => lane % LANE_COUNT,
+                (false, false) => continue,
+            };
+
+            let time = time.round() as i32;
+            let sustain = fields.get(2).and_then(JsonValue::as_f64).unwrap_or(0.0).round() as i32;
+            if sustain > 0 {
+                timeline.add_sorted(TimelineHitObject { time, column, key: Key::slider_start(Some(time + sustain)), keysound: None });
+                timeline.add_sorted(TimelineHitObject { time: time + sustain, column, key: Key::slider_end(), keysound: None });
+            } else {
+                timeline.add_sorted(TimelineHitObject { time, column, key: Key::normal(), keysound: None });
+            }
+        }
+    }
+
+    let mut timing_points = TimingPoints::with_capacity(timing_timeline.len());
+    timing_timeline.to_timing_points(&mut timing_points, 0);
+
+    let mut hitobjects = HitObjects::with_capacity(timeline.len());
+    timeline.to_hitobjects(&mut hitobjects, 0, key_count, &timing_points.times, &timing_points.bpms());
+
+    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, None))
+}
+
+/// parses the player's side of a Friday Night Funkin' chart as 4K
+pub(crate) fn from_fnf(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    parse_fnf(raw_chart, false)
+}
+
+/// parses both sides of a Friday Night Funkin' chart as 8K, the opponent's lanes are on the left
+pub(crate) fn from_fnf_both_sides(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    parse_fnf(raw_chart, true)
+}
\ No newline at end of file
@@ -0,0 +1,206 @@
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::utils::time::{process_bpms_and_stops, process_timing_points};
+use crate::utils::string::{
+    StrDefaultExtension,
+    StrNumericDefaultExtension,
+};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+/// FX L, BT A, BT B, BT C, BT D and FX R, the FX lanes go on the outside of the BT lanes
+const KEY_COUNT: usize = 6;
+const BT_COLUMNS: [usize; 4] = [1, 2, 3, 4];
+const FX_COLUMNS: [usize; 2] = [0, 5];
+/// stops are in 192nds of a 4/4 measure
+const STOP_UNITS_PER_BEAT: f32 = 48.0;
+
+/// what each lane of a note line like `0120|02|--` is doing
+#[derive(Clone, Copy, PartialEq)]
+enum LaneState {
+    Empty,
+    Chip,
+    Long,
+}
+
+#[inline]
+fn bt_state(c: char) -> LaneState {
+    match c {
+        '1' => LaneState::Chip,
+        '2' => LaneState::Long,
+        _ => LaneState::Empty,
+    }
+}
+
+/// FX long notes can be any letter, each one being a different audio effect
+#[inline]
+fn fx_state(c: char) -> LaneState {
+    match c {
+        '0' => LaneState::Empty,
+        '2' => LaneState::Chip,
+        _ => LaneState::Long,
+    }
+}
+
+/// the bpm of a header like `t=120` or `t=120-240`, ranges are only shown on song select
+#[inline]
+fn parse_header_bpm(value: &str) -> Option<f32> {
+    value.split('-').next()?.trim().parse().ok()
+}
+
+/// `beat=n/d` as the length of a measure in beats
+#[inline]
+fn parse_measure_length(value: &str) -> Option<f32> {
+    let (numerator, denominator) = value.split_once('/')?;
+    let numerator: f32 = numerator.trim().parse().ok()?;
+    let denominator: f32 = denominator.trim().parse().ok()?;
+    (numerator > 0.0 && denominator > 0.0).then_some(numerator * 4.0 / denominator)
+}
+
+/// parses K-Shoot Mania (.ksh) charts as 6K, lasers have no column to go in so they get dropped
+pub(crate) fn from_ksh(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, chart::Chart};
+
+    let raw_chart = raw_chart.trim_start_matches('\u{feff}');
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let lines: Vec<&str> = raw_chart.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect();
+    let body_start = lines.iter()
+        .position(|line| *line == "--")
+        .ok_or_else(|| errors::ParseError::<GameMode>::InvalidChart("Missing measures in K-Shoot Mania chart".to_string()))?;
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+
+    let mut initial_bpm = 120.0;
+    let mut difficulty = "";
+    let mut level = "";
+    for (key, value) in lines[..body_start].iter().filter_map(|line| line.split_once('=')) {
+        match key {
+            "title" => metadata.title = value.or_default_empty(ChartDefaults::TITLE),
+            "artist" => metadata.artist = value.or_default_empty(ChartDefaults::ARTIST),
+            "effect" => metadata.creator = value.or_default_empty(ChartDefaults::CREATOR),
+            "jacket" => chartinfo.bg_path = value.to_string(),
+            // the second song is the one with the FX effects baked in
+            "m" => chartinfo.song_path = value.split(';').next().unwrap_or("").to_string(),
+            "o" => chartinfo.audio_offset = value.or_default_empty_as(0),
+            "po" => chartinfo.preview_time = value.or_default_empty_as(*ChartDefaults::PREVIEW_TIME),
+            "t" => initial_bpm = parse_header_bpm(value).unwrap_or(initial_bpm),
+            "difficulty" => difficulty = value,
+            "level" => level = value,
+            _ => {},
+        }
+    }
+
+    let difficulty = match difficulty {
+        "light" => "Light",
+        "challenge" => "Challenge",
+        "extended" => "Extended",
+        "infinite" => "Infinite",
+        _ => "",
+    };
+    chartinfo.difficulty_name = format!("{difficulty} {level}").trim().or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+    chartinfo.key_count = KEY_COUNT as u8;
+
+    let mut bpm_changes: Vec<(f32, f32)> = Vec::new();
+    let mut stops: Vec<(f32, f32)> = Vec::new();
+    let mut measure_lengths: Vec<(f32, f32)> = Vec::new();
+    // beat and lane states of every note line, hold ends need times so notes get made once timing is known
+    let mut note_lines: Vec<(f32, [LaneState; KEY_COUNT])> = Vec::new();
+    let mut has_lasers = false;
+
+    let mut measure_beat = 0.0;
+    let mut measure_length = 4.0;
+    for measure in lines[body_start + 1..].split(|line| *line == "--") {
+        // whatever is after the last measure isn't one
+        let note_line_count = measure.iter().filter(|line| line.contains('|')).count();
+        if note_line_count == 0 {
+            continue;
+        }
+
+        // options apply at the note line after them, beat= is only allowed at the start of a measure
+        let mut line_beat = measure_beat;
+        for line in measure.iter().take_while(|line| !line.contains('|')) {
+            if let Some(length) = line.strip_prefix("beat=").and_then(parse_measure_length) {
+                if length != measure_length {
+                    measure_lengths.push((measure_beat, length));
+                }
+                measure_length = length;
+            }
+        }
+        let beats_per_line = measure_length / note_line_count as f32;
+
+        for line in measure {
+            if let Some((key, value)) = line.split_once('=') {
+                match key {
+                    "t" => if let Ok(bpm) = value.parse() { bpm_changes.push((line_beat, bpm)) },
+                    "stop" => if let Ok(units) = value.parse::<f32>() { stops.push((line_beat, units / STOP_UNITS_PER_BEAT)) },
+                    _ => {},
+                }
+                continue;
+            }
+
+            let mut parts = line.split('|');
+            let (Some(bt), Some(fx)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            has_lasers |= parts.next().is_some_and(|lasers| lasers.chars().take(2).any(|laser| laser != '-'));
+
+            let mut states = [LaneState::Empty; KEY_COUNT];
+            for (column, c) in BT_COLUMNS.iter().zip(bt.chars()) {
+                states[*column] = bt_state(c);
+            }
+            for (column, c) in FX_COLUMNS.iter().zip(fx.chars()) {
+                states[*column] = fx_state(c);
+            }
+            note_lines.push((line_beat, states));
+            line_beat += beats_per_line;
+        }
+
+        measure_beat += measure_length;
+    }
+
+    let bpms_and_stops = process_bpms_and_stops(bpm_changes, stops, initial_bpm);
+    let start_time = chartinfo.audio_offset;
+    let timing_points = process_timing_points(&bpms_and_stops, &measure_lengths, start_time);
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, start_time, (beats, bpms_and_durations, change_types));
+
+    let mut objects: Vec<BeatNote> = Vec::new();
+    // a long note runs from its first line to the first line it's not on, the empty line at the end closes the rest
+    let mut hold_starts: [Option<f32>; KEY_COUNT] = [None; KEY_COUNT];
+    let end_line = (measure_beat, [LaneState::Empty; KEY_COUNT]);
+    for (beat, states) in note_lines.iter().chain(std::iter::once(&end_line)) {
+        for (column, state) in states.iter().enumerate() {
+            if *state != LaneState::Long {
+                if let Some(start_beat) = hold_starts[column].take() {
+                    objects.push((start_beat, column, Key::slider_start(Some(time_at(*beat))), None));
+                    objects.push((*beat, column, Key::slider_end(), None));
+                }
+            }
+            match state {
+                LaneState::Chip => objects.push((*beat, column, Key::normal(), None)),
+                LaneState::Long if hold_starts[column].is_none() => hold_starts[column] = Some(*beat),
+                _ => {},
+            }
+        }
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(objects, KEY_COUNT, time_at);
+
+    let mut chart = Chart::new(metadata, chartinfo, timing_points, hitobjects, None);
+    if has_lasers {
+        chart.warnings.push("K-Shoot Mania lasers can't be placed in columns and were dropped".to_string());
+    }
+
+    Ok(chart)
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    TimingChangeType,
+    GameMode,
+    Key,
+};
+use crate::models::sound::{KeySound, SoundEffect};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::time::{BpmsAndStops, merge_bpm_and_stops, process_timing_changes};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// Malody's key mode, the only one that maps onto columns
+const KEY_MODE: i64 = 0;
+/// what the song note's "type" is, every other sound note is a keysound or a sample playing on its own
+const SONG_NOTE_TYPE: i64 = 1;
+
+/// beats are [beat, numerator, denominator]
+#[inline]
+fn parse_beat(value: Option<&JsonValue>) -> Option<f32> {
+    let parts = value?.as_array()?;
+    let beat = parts.first()?.as_f64()?;
+    let numerator = parts.get(1).and_then(JsonValue::as_f64).unwrap_or(0.0);
+    let denominator = parts.get(2).and_then(JsonValue::as_f64).filter(|denominator| *denominator != 0.0).unwrap_or(1.0);
+    // huge numbers in the json can make a beat that isn't one
+    Some((beat + numerator / denominator) as f32).filter(|beat| beat.is_finite())
+}
+
+fn process_bpms(mc: &JsonValue) -> BpmsAndStops {
+    let mut bpm_changes: Vec<(f32, f32)> = mc.get("time").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(|change| Some((parse_beat(change.get("beat"))?, change.get("bpm")?.as_f32()?)))
+        .filter(|(_, bpm)| *bpm > 0.0)
+        .collect();
+    bpm_changes.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting bpm changes"));
+
+    // the first bpm is used from the start of the chart even if it's placed later
+    match bpm_changes.first_mut() {
+        Some(first) => first.0 = first.0.min(0.0),
+        None => bpm_changes.push((0.0, 120.0)),
+    }
+
+    let (bpm_beats, bpms): (Vec<f32>, Vec<f32>) = bpm_changes.into_iter().unzip();
+    merge_bpm_and_stops(bpm_beats, bpms, Vec::new(), Vec::new())
+}
+
+/// scroll changes are the chart's sv
+fn process_timing_points(mc: &JsonValue, bpms_and_stops: &BpmsAndStops, start_time: i32) -> models::timing_points::TimingPoints {
+    let scroll_changes = mc.get("effect").into_iter()
+        .flat_map(JsonValue::iter_array)
+        .filter_map(|effect| Some((parse_beat(effect.get("beat"))?, effect.get("scroll")?.as_f32()?, TimingChangeType::Sv)))
+        .collect();
+    process_timing_changes(bpms_and_stops, scroll_changes, start_time)
+}
+
+/// parses Malody key mode (.mc) charts, the song is a note with a sound and the offset of its first beat
+pub(crate) fn from_mc(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, sound::SoundBank, chart::Chart};
+
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    let mc = JsonValue::parse(raw_chart)
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid Malody chart: {e}")))?;
+    let meta = mc.get("meta")
+        .ok_or_else(|| errors::ParseError::<GameMode>::InvalidChart("Missing meta in Malody chart".to_string()))?;
+
+    let mode = meta.get("mode").and_then(JsonValue::as_i64).unwrap_or(KEY_MODE);
+    if mode != KEY_MODE {
+        return Err(Box::new(errors::ParseError::InvalidMode(mode.to_string(), GameMode::Mania)));
+    }
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut soundbank = SoundBank::new();
+
+    let meta_str = |value: &JsonValue, key: &str| value.get(key).and_then(JsonValue::as_str).unwrap_or("").to_string();
+    let song = meta.get("song").unwrap_or(&JsonValue::Null);
+    metadata.title = meta_str(song, "title").or_default_empty(ChartDefaults::TITLE);
+    metadata.alt_title = meta_str(song, "titleorg").or_default_empty(&metadata.title);
+    metadata.artist = meta_str(song, "artist").or_default_empty(ChartDefaults::ARTIST);
+    metadata.alt_artist = meta_str(song, "artistorg").or_default_empty(&metadata.artist);
+    metadata.creator = meta_str(meta, "creator").or_default_empty(ChartDefaults::CREATOR);
+    chartinfo.difficulty_name = meta_str(meta, "version").or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+    chartinfo.bg_path = meta_str(meta, "background");
+    chartinfo.preview_time = meta.get("preview").and_then(JsonValue::as_i64).unwrap_or(*ChartDefaults::PREVIEW_TIME as i64) as i32;
+
+    let notes: Vec<&JsonValue> = mc.get("note").into_iter().flat_map(JsonValue::iter_array).collect();
+    let key_count = meta.get("mode_ext")
+        .and_then(|mode_ext| mode_ext.get("column"))
+        .and_then(JsonValue::as_i64)
+        .filter(|column_count| *column_count > 0)
+        .or_else(|| notes.iter().filter_map(|note| note.get("column")?.as_i64()).max().map(|column| column + 1))
+        .unwrap_or(*ChartDefaults::KEY_COUNT as i64) as usize;
+    chartinfo.key_count = key_count as u8;
+
+    let bpms_and_stops = process_bpms(&mc);
+    let (beats, bpms, change_types) = &bpms_and_stops;
+    let time_from_first_beat = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms, change_types));
+
+    // the song plays from its note's beat, and "offset" is how far into the song the first beat is
+    if let Some(song_note) = notes.iter().find(|note| note.get("type").and_then(JsonValue::as_i64) == Some(SONG_NOTE_TYPE)) {
+        chartinfo.song_path = meta_str(song_note, "sound");
+        let song_beat = parse_beat(song_note.get("beat")).unwrap_or(0.0);
+        let offset = song_note.get("offset").and_then(JsonValue::as_i64).unwrap_or(0) as i32;
+        chartinfo.audio_offset = offset - time_from_first_beat(song_beat);
+    }
+    let start_time = chartinfo.audio_offset;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, start_time, (beats, bpms, change_types));
+
+    let timing_points = process_timing_points(&mc, &bpms_and_stops, start_time);
+
+    let mut objects: Vec<BeatNote> = Vec::with_capacity(notes.len());
+    for note in notes {
+        if note.get("type").and_then(JsonValue::as_i64) == Some(SONG_NOTE_TYPE) {
+            continue;
+        }
+        let Some(beat) = parse_beat(note.get("beat")) else {
+            continue;
+        };
+        let volume = note.get("vol").and_then(JsonValue::as_i64).unwrap_or(100).clamp(0, 100) as u8;
+        let sample = note.get("sound")
+            .and_then(JsonValue::as_str)
+            .filter(|path| !path.is_empty())
+            .map(|path| soundbank.add_sound_sample(path.to_string()));
+
+        let column = note.get("column")
+            .and_then(JsonValue::as_i64)
+            .filter(|column| (0..key_count as i64).contains(column));
+        let Some(column) = column else {
+            if let Some(index) = sample {
+                soundbank.add_sound_effect(SoundEffect::new(time_at(beat), volume, index));
+            }
+            continue;
+        };
+
+        let key_sound = sample.map(|index| KeySound::with_custom(volume, index, None));
+        match parse_beat(note.get("endbeat")).filter(|end_beat| *end_beat > beat) {
+            Some(end_beat) => {
+                objects.push((beat, column as usize, Key::slider_start(Some(time_at(end_beat))), key_sound));
+                objects.push((end_beat, column as usize, Key::slider_end(), None));
+            },
+            None => objects.push((beat, column as usize, Key::normal(), key_sound)),
+        }
+    }
+    soundbank.sound_effects.sort_by_key(|sound_effect| sound_effect.time);
+
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank)))
+}
\ No newline at end of file
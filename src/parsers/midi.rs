@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::models::midi::MidiOptions;
+use crate::utils::time::{BpmsAndStops, process_bpms_and_stops, process_timing_points};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+const HEADER_CHUNK: &[u8] = b"MThd";
+const TRACK_CHUNK: &[u8] = b"MTrk";
+const MICROSECONDS_PER_MINUTE: f32 = 60_000_000.0;
+/// bpm until the first tempo event
+const DEFAULT_BPM: f32 = 120.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MidiEventKind {
+    NoteOn { channel: u8, key: u8 },
+    NoteOff { channel: u8, key: u8 },
+    /// microseconds per beat
+    Tempo(u32),
+    /// numerator and the power of 2 the denominator is
+    TimeSignature(u8, u8),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MidiEvent {
+    pub(crate) tick: u32,
+    pub(crate) kind: MidiEventKind,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MidiTrack {
+    pub(crate) name: String,
+    pub(crate) events: Vec<MidiEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct MidiFile {
+    /// ticks per beat
+    pub(crate) resolution: u16,
+    pub(crate) tracks: Vec<MidiTrack>,
+}
+
+/// a note from its note on to its note off
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MidiNote {
+    pub(crate) tick: u32,
+    pub(crate) end_tick: u32,
+    pub(crate) key: u8,
+}
+
+/// reads big endian numbers and variable length quantities
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    #[inline]
+    fn is_at_end(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    #[inline]
+    fn take(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.position..self.position.checked_add(length)?)?;
+        self.position += length;
+        Some(bytes)
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    #[inline]
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    #[inline]
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    #[inline]
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// 7 bits a byte, the top bit is set on every byte but the last
+    fn var_len(&mut self) -> Option<u32> {
+        let mut value: u32 = 0;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+/// events of a track with their deltas added up into ticks, the first track name event names the track
+fn read_track(data: &[u8]) -> Option<MidiTrack> {
+    let mut reader = Reader::new(data);
+    let mut track = MidiTrack::default();
+    let mut tick: u32 = 0;
+    // channel messages can leave out their status byte if it's the same as the last one
+    let mut running_status: Option<u8> = None;
+
+    while !reader.is_at_end() {
+        tick = tick.saturating_add(reader.var_len()?);
+
+        let status = match reader.peek()? {
+            byte if byte & 0x80 != 0 => {
+                reader.u8()?;
+                byte
+            },
+            _ => running_status?,
+        };
+
+        match status {
+            0xFF => {
+                let meta_type = reader.u8()?;
+                let length = reader.var_len()? as usize;
+                let data = reader.take(length)?;
+                let kind = match meta_type {
+                    0x03 if track.name.is_empty() => {
+                        track.name = String::from_utf8_lossy(data).trim().to_string();
+                        None
+                    },
+                    0x51 if length == 3 => Some(MidiEventKind::Tempo(u32::from_be_bytes([0, data[0], data[1], data[2]]))),
+                    0x58 if length >= 2 => Some(MidiEventKind::TimeSignature(data[0], data[1])),
+                    0x2F => break,
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    track.events.push(MidiEvent { tick, kind });
+                }
+            },
+            0xF0 | 0xF7 => {
+                let length = reader.var_len()? as usize;
+                reader.take(length)?;
+            },
+            0x80..=0xEF => {
+                running_status = Some(status);
+                let channel = status & 0x0F;
+                let data = match status & 0xF0 {
+                    0xC0 | 0xD0 => reader.take(1)?,
+                    _ => reader.take(2)?,
+                };
+                let kind = match (status & 0xF0, data) {
+                    (0x90, [key, velocity]) if *velocity > 0 => Some(MidiEventKind::NoteOn { channel, key: *key }),
+                    // a note on with no velocity is a note off
+                    (0x80 | 0x90, [key, _]) => Some(MidiEventKind::NoteOff { channel, key: *key }),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    track.events.push(MidiEvent { tick, kind });
+                }
+            },
+            _ => return None,
+        }
+    }
+
+    Some(track)
+}
+
+/// reads the tracks of a standard MIDI file, SMPTE timed files have no beats so they aren't supported
+pub(crate) fn read_midi(raw_midi: &[u8]) -> Result<MidiFile, errors::ParseError<GameMode>> {
+    let invalid = |message: &str| errors::ParseError::<GameMode>::InvalidChart(message.to_string());
+
+    if raw_midi.is_empty() {
+        return Err(errors::ParseError::EmptyChartData);
+    }
+
+    let mut reader = Reader::new(raw_midi);
+    if reader.take(4) != Some(HEADER_CHUNK) {
+        return Err(invalid("Missing MThd header in MIDI file"));
+    }
+    let header_length = reader.u32().ok_or_else(|| invalid("Truncated MIDI header"))? as usize;
+    let mut header = Reader::new(reader.take(header_length).ok_or_else(|| invalid("Truncated MIDI header"))?);
+    let (_format, _track_count, division) = (header.u16(), header.u16(), header.u16());
+    let division = division.ok_or_else(|| invalid("Truncated MIDI header"))?;
+    if division & 0x8000 != 0 || division == 0 {
+        return Err(errors::ParseError::UnsupportedFormat);
+    }
+
+    let mut tracks = Vec::new();
+    while !reader.is_at_end() {
+        let (Some(chunk), Some(length)) = (reader.take(4), reader.u32()) else {
+            return Err(invalid("Truncated MIDI chunk"));
+        };
+        let data = reader.take(length as usize).ok_or_else(|| invalid("Truncated MIDI chunk"))?;
+        // other chunks are allowed and get skipped
+        if chunk == TRACK_CHUNK {
+            tracks.push(read_track(data).ok_or_else(|| invalid("Invalid MIDI track data"))?);
+        }
+    }
+
+    Ok(MidiFile { resolution: division, tracks })
+}
+
+impl MidiTrack {
+    /// note ons paired with their note offs, a note on for a key that's already down ends the one before it
+    pub(crate) fn notes(&self) -> Vec<MidiNote> {
+        let mut held: HashMap<(u8, u8), u32> = HashMap::new();
+        let mut notes = Vec::new();
+
+        for event in &self.events {
+            match event.kind {
+                MidiEventKind::NoteOn { channel, key } => {
+                    if let Some(tick) = held.insert((channel, key), event.tick) {
+                        notes.push(MidiNote { tick, end_tick: event.tick, key });
+                    }
+                },
+                MidiEventKind::NoteOff { channel, key } => {
+                    if let Some(tick) = held.remove(&(channel, key)) {
+                        notes.push(MidiNote { tick, end_tick: event.tick, key });
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        // notes that never get let go end with the track
+        let last_tick = self.events.last().map_or(0, |event| event.tick);
+        notes.extend(held.into_iter().map(|((_, key), tick)| MidiNote { tick, end_tick: last_tick, key }));
+        notes.sort_by_key(|note| (note.tick, note.key));
+
+        notes
+    }
+}
+
+impl MidiFile {
+    #[inline]
+    pub(crate) fn beat_at(&self, tick: u32) -> f32 {
+        tick as f32 / self.resolution as f32
+    }
+
+    /// tempo events of every track as bpm changes
+    pub(crate) fn bpms(&self) -> BpmsAndStops {
+        let bpm_changes = self.tracks.iter()
+            .flat_map(|track| &track.events)
+            .filter_map(|event| match event.kind {
+                MidiEventKind::Tempo(microseconds) if microseconds > 0 => Some((self.beat_at(event.tick), MICROSECONDS_PER_MINUTE / microseconds as f32)),
+                _ => None,
+            })
+            .collect();
+
+        process_bpms_and_stops(bpm_changes, Vec::new(), DEFAULT_BPM)
+    }
+
+    /// time signature changes as (beat, beats in a measure)
+    pub(crate) fn time_signatures(&self) -> Vec<(f32, f32)> {
+        let mut time_signatures: Vec<(f32, f32)> = self.tracks.iter()
+            .flat_map(|track| &track.events)
+            .filter_map(|event| match event.kind {
+                MidiEventKind::TimeSignature(numerator, denominator_power) if numerator > 0 => {
+                    Some((self.beat_at(event.tick), numerator as f32 * 4.0 / 2f32.powi(denominator_power as i32)))
+                },
+                _ => None,
+            })
+            .collect();
+        time_signatures.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting time signatures"));
+
+        // only changes are kept, 4/4 is what it is before the first one
+        let mut previous_length = 4.0;
+        time_signatures.retain(|(_, length)| {
+            let is_change = *length != previous_length;
+            previous_length = *length;
+            is_change
+        });
+
+        time_signatures
+    }
+}
+
+/// turns the notes on the chosen tracks and pitches of a standard MIDI file into columns, tempo events become bpm changes
+pub(crate) fn from_midi(raw_midi: &[u8], options: &MidiOptions) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, chart::Chart};
+
+    let midi = read_midi(raw_midi)?;
+
+    let notes: Vec<MidiNote> = midi.tracks.iter()
+        .enumerate()
+        .filter(|(i, _)| options.tracks.is_empty() || options.tracks.contains(&(*i as u32)))
+        .flat_map(|(_, track)| track.notes())
+        .collect();
+
+    let pitches: Vec<u8> = if options.pitches.is_empty() {
+        let mut pitches: Vec<u8> = notes.iter().map(|note| note.key).collect();
+        pitches.sort_unstable();
+        pitches.dedup();
+        pitches
+    } else {
+        options.pitches.clone()
+    };
+    let key_count = pitches.len();
+    if key_count == 0 || key_count > u8::MAX as usize {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(format!("{key_count} pitches can't be used as columns"))));
+    }
+
+    let mut columns: Vec<(f32, usize, f32)> = notes.iter()
+        .filter_map(|note| {
+            let column = pitches.iter().position(|pitch| *pitch == note.key)?;
+            Some((midi.beat_at(note.tick), column, midi.beat_at(note.end_tick.saturating_sub(note.tick))))
+        })
+        .collect();
+    if columns.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No notes on the chosen MIDI tracks and pitches".to_string())));
+    }
+    columns.sort_by(|a, b| (a.1, a.0).partial_cmp(&(b.1, b.0)).expect("Comparison between NaN values when sorting notes"));
+    columns.dedup_by(|later, earlier| later.1 == earlier.1 && later.0 == earlier.0);
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    // the first track's name is usually the song's
+    metadata.title = midi.tracks.first()
+        .map_or("", |track| track.name.as_str())
+        .or_default_empty(ChartDefaults::TITLE);
+    chartinfo.key_count = key_count as u8;
+
+    let bpms_and_stops = midi.bpms();
+    let timing_points = process_timing_points(&bpms_and_stops, &midi.time_signatures(), 0);
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms_and_durations, change_types));
+
+    // long notes running into the next note of their column can't be held
+    let mut objects: Vec<BeatNote> = Vec::with_capacity(columns.len());
+    for (i, (beat, column, length)) in columns.iter().enumerate() {
+        let next_beat = columns.get(i + 1).filter(|next| next.1 == *column).map(|next| next.0);
+        let end_beat = beat + length;
+        let is_hold = options.hold_threshold > 0.0 && *length >= options.hold_threshold;
+        if is_hold && next_beat.is_none_or(|next_beat| end_beat < next_beat) {
+            objects.push((*beat, *column, Key::slider_start(Some(time_at(end_beat))), None));
+            objects.push((end_beat, *column, Key::slider_end(), None));
+        } else {
+            objects.push((*beat, *column, Key::normal(), None));
+        }
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(objects, key_count, time_at);
+
+    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, None))
+}
\ No newline at end of file
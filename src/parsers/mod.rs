@@ -1,3 +1,17 @@
 pub mod osu;
+mod osu_convert;
 pub mod stepmania;
-pub mod quaver;
\ No newline at end of file
+pub mod quaver;
+pub mod bms;
+pub mod malody;
+pub mod o2jam;
+pub mod dtx;
+pub mod ksh;
+pub mod midi;
+pub mod clone_hero;
+pub mod fnf;
+pub mod beat_saber;
+pub mod tja;
+pub mod osz;
+pub mod qp;
+pub mod sm_folder;
\ No newline at end of file
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    GameMode,
+    Key,
+};
+use crate::models::sound::{KeySound, SoundEffect};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::time::{
+    merge_bpm_and_stops,
+    process_timing_points,
+    measure_time_signatures,
+};
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+const OJN_SIGNATURE: &[u8] = b"ojn\0";
+const OJN_HEADER_SIZE: usize = 300;
+/// measure, channel and event count
+const PACKAGE_HEADER_SIZE: usize = 8;
+const EVENT_SIZE: usize = 4;
+/// packages past this measure are corrupt, no song is anywhere near this long
+const MAX_MEASURE: usize = 4096;
+
+const DIFFICULTY_NAMES: [&str; 3] = ["Easy", "Normal", "Hard"];
+const GENRES: [&str; 11] = ["Ballad", "Rock", "Dance", "Techno", "Hip-hop", "Soul/R&B", "Jazz", "Funk", "Classical", "Traditional", "Etc"];
+
+const MEASURE_LENGTH_CHANNEL: u16 = 0;
+const BPM_CHANNEL: u16 = 1;
+/// channels 2 to 8 are the 7 lanes, 9 to 22 play samples on their own
+const FIRST_LANE_CHANNEL: u16 = 2;
+const KEY_COUNT: usize = 7;
+const LAST_AUTOPLAY_CHANNEL: u16 = 22;
+
+/// samples that are oggs instead of wavs are referenced from 1000 up in both files
+const OGG_SAMPLE_ID_OFFSET: usize = 1000;
+
+#[inline]
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    bytes.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+#[inline]
+fn read_i32(bytes: &[u8], offset: usize) -> Option<i32> {
+    bytes.get(offset..offset + 4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+#[inline]
+fn read_f32(bytes: &[u8], offset: usize) -> Option<f32> {
+    bytes.get(offset..offset + 4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// strings are fixed size and end at the first null, they're usually EUC-KR so anything that isn't UTF-8 gets replaced
+#[inline]
+fn read_string(bytes: &[u8], offset: usize, length: usize) -> String {
+    let raw = bytes.get(offset..offset + length).unwrap_or(&[]);
+    let end = raw.iter().position(|byte| *byte == 0).unwrap_or(raw.len());
+    String::from_utf8_lossy(&raw[..end]).trim().to_string()
+}
+
+/// sample names by the id notes use, from the three kinds of .ojm files
+fn parse_ojm_sample_names(raw_ojm: &[u8]) -> HashMap<usize, String> {
+    let mut names = HashMap::new();
+
+    match raw_ojm.get(0..4) {
+        // every sample has its own header with the id in it
+        Some(b"M30\0") => {
+            let sample_count = read_i32(raw_ojm, 12).unwrap_or(0).max(0);
+            let mut offset = read_i32(raw_ojm, 16).unwrap_or(28).max(0) as usize;
+            for _ in 0..sample_count {
+                let (Some(size), Some(codec)) = (read_i32(raw_ojm, offset + 32), read_u16(raw_ojm, offset + 36)) else {
+                    break;
+                };
+                let reference = read_u16(raw_ojm, offset + 44).unwrap_or(0) as usize;
+                let id = match codec {
+                    0 => Some(OGG_SAMPLE_ID_OFFSET + reference),
+                    5 => Some(reference),
+                    _ => None,
+                };
+                if let Some(id) = id {
+                    names.insert(id, read_string(raw_ojm, offset, 32));
+                }
+                offset += 52 + size.max(0) as usize;
+            }
+        },
+        // wavs then oggs, ids count up in the order they're in, empty ones included
+        Some(b"OMC\0" | b"OJM\0") => {
+            let ogg_start = read_i32(raw_ojm, 12).unwrap_or(0).max(0) as usize;
+            let file_size = (read_i32(raw_ojm, 16).unwrap_or(0).max(0) as usize).min(raw_ojm.len());
+
+            let mut offset = read_i32(raw_ojm, 8).unwrap_or(20).max(0) as usize;
+            let mut id = 0;
+            while offset + 56 <= ogg_start.min(file_size) {
+                let size = read_i32(raw_ojm, offset + 52).unwrap_or(0).max(0) as usize;
+                if size > 0 {
+                    names.insert(id, read_string(raw_ojm, offset, 32));
+                }
+                offset += 56 + size;
+                id += 1;
+            }
+
+            let mut offset = ogg_start;
+            let mut id = OGG_SAMPLE_ID_OFFSET;
+            while offset + 36 <= file_size {
+                let size = read_i32(raw_ojm, offset + 32).unwrap_or(0).max(0) as usize;
+                if size > 0 {
+                    names.insert(id, read_string(raw_ojm, offset, 32));
+                }
+                offset += 36 + size;
+                id += 1;
+            }
+        },
+        _ => {},
+    }
+
+    names.retain(|_, name| !name.is_empty());
+    names
+}
+
+/// an event in a package, `beat` is where it is in the whole chart
+#[derive(Debug, Clone, Copy)]
+struct OjnEvent {
+    beat: f32,
+    channel: u16,
+    raw: [u8; 4],
+}
+
+/// reads every package of a difficulty, measures are placed using the lengths from channel 0
+fn process_packages(raw_notes: &[u8], package_count: usize) -> (Vec<OjnEvent>, Vec<f32>, Vec<f32>) {
+    // (measure, channel, events)
+    let mut packages: Vec<(usize, u16, &[u8])> = Vec::with_capacity(package_count);
+    let mut offset = 0;
+    for _ in 0..package_count {
+        let (Some(measure), Some(channel), Some(event_count)) = (
+            read_i32(raw_notes, offset),
+            read_u16(raw_notes, offset + 4),
+            read_u16(raw_notes, offset + 6),
+        ) else {
+            break;
+        };
+        let events_start = offset + PACKAGE_HEADER_SIZE;
+        let events_end = events_start + event_count as usize * EVENT_SIZE;
+        let Some(events) = raw_notes.get(events_start..events_end) else {
+            break;
+        };
+        offset = events_end;
+        let measure = measure.max(0) as usize;
+        if measure > MAX_MEASURE {
+            continue;
+        }
+        packages.push((measure, channel, events));
+    }
+
+    let measure_count = packages.iter().map(|(measure, _, _)| measure + 1).max().unwrap_or(0);
+    let mut measure_lengths = vec![1.0; measure_count];
+    for (measure, channel, events) in &packages {
+        if *channel == MEASURE_LENGTH_CHANNEL {
+            if let Some(length) = read_f32(events, 0).filter(|length| *length > 0.0) {
+                measure_lengths[*measure] = length;
+            }
+        }
+    }
+    let measure_beats: Vec<f32> = measure_lengths.iter()
+        .scan(0.0, |beat, length| {
+            let measure_beat = *beat;
+            *beat += length * 4.0;
+            Some(measure_beat)
+        })
+        .collect();
+
+    let mut events = Vec::new();
+    for (measure, channel, raw_events) in packages {
+        if channel == MEASURE_LENGTH_CHANNEL {
+            continue;
+        }
+        let event_count = raw_events.len() / EVENT_SIZE;
+        for (i, raw) in raw_events.chunks_exact(EVENT_SIZE).enumerate() {
+            let beat = measure_beats[measure] + i as f32 / event_count as f32 * measure_lengths[measure] * 4.0;
+            events.push(OjnEvent { beat, channel, raw: [raw[0], raw[1], raw[2], raw[3]] });
+        }
+    }
+    events.sort_by(|a, b| a.beat.partial_cmp(&b.beat).expect("Comparison between NaN values when sorting events"));
+
+    (events, measure_lengths, measure_beats)
+}
+
+fn build_chart(
+    raw_chart: &[u8],
+    difficulty: usize,
+    metadata: &models::metadata::Metadata,
+    sample_names: &HashMap<usize, String>,
+) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    use models::{chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, sound::SoundBank, chart::Chart};
+
+    let invalid_header = || errors::ParseError::<GameMode>::InvalidChart("OJN header is cut off".to_string());
+    let initial_bpm = read_f32(raw_chart, 16).filter(|bpm| *bpm > 0.0).unwrap_or(120.0);
+    let package_count = read_i32(raw_chart, 64 + difficulty * 4).ok_or_else(invalid_header)?.max(0) as usize;
+    let notes_start = read_i32(raw_chart, 284 + difficulty * 4).ok_or_else(invalid_header)?.max(0) as usize;
+    // each difficulty's notes go up to the next one's, and the cover comes after the last
+    let notes_end = read_i32(raw_chart, 288 + difficulty * 4).ok_or_else(invalid_header)?.max(0) as usize;
+    let raw_notes = raw_chart.get(notes_start..notes_end.clamp(notes_start, raw_chart.len())).unwrap_or(&[]);
+
+    let mut chartinfo = ChartInfo::empty();
+    chartinfo.difficulty_name = DIFFICULTY_NAMES[difficulty].to_string();
+    chartinfo.key_count = KEY_COUNT as u8;
+    let mut soundbank = SoundBank::new();
+
+    let (events, measure_lengths, measure_beats) = process_packages(raw_notes, package_count);
+
+    let bpm_changes: Vec<(f32, f32)> = events.iter()
+        .filter(|event| event.channel == BPM_CHANNEL)
+        .map(|event| (event.beat, f32::from_le_bytes(event.raw)))
+        .filter(|(_, bpm)| *bpm > 0.0)
+        .collect();
+    let (mut bpm_beats, mut bpms): (Vec<f32>, Vec<f32>) = bpm_changes.into_iter().unzip();
+    if bpm_beats.first() != Some(&0.0) {
+        bpm_beats.insert(0, 0.0);
+        bpms.insert(0, initial_bpm);
+    }
+    let bpms_and_stops = merge_bpm_and_stops(bpm_beats, bpms, Vec::new(), Vec::new());
+    let timing_points = process_timing_points(&bpms_and_stops, &measure_time_signatures(&measure_lengths, &measure_beats), 0);
+    let (beats, bpms, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, 0, (beats, bpms, change_types));
+
+    // sample ids are sparse, so only the ones that get used go into the soundbank
+    let mut sample_indices: HashMap<usize, usize> = HashMap::new();
+    let mut objects: Vec<BeatNote> = Vec::new();
+    // (beat, keysound) of the long note each lane is holding
+    let mut held_notes: Vec<Option<(f32, Option<KeySound>)>> = vec![None; KEY_COUNT];
+
+    for event in events {
+        if !(FIRST_LANE_CHANNEL..=LAST_AUTOPLAY_CHANNEL).contains(&event.channel) {
+            continue;
+        }
+        let value = u16::from_le_bytes([event.raw[0], event.raw[1]]) as usize;
+        if value == 0 {
+            continue;
+        }
+        let volume = match event.raw[2] >> 4 {
+            0 => 100,
+            volume => (volume as u32 * 100 / 16) as u8,
+        };
+        let note_type = event.raw[3];
+
+        let sample_id = if note_type % 8 > 3 { value - 1 + OGG_SAMPLE_ID_OFFSET } else { value - 1 };
+        let sample = sample_names.get(&sample_id).map(|name| *sample_indices.entry(sample_id)
+            .or_insert_with(|| soundbank.add_sound_sample(name.clone())));
+
+        let column = (event.channel - FIRST_LANE_CHANNEL) as usize;
+        if column >= KEY_COUNT {
+            if let Some(index) = sample {
+                soundbank.add_sound_effect(SoundEffect::new(time_at(event.beat), volume, index));
+            }
+            continue;
+        }
+
+        let key_sound = sample.map(|index| KeySound::with_custom(volume, index, None));
+        match note_type % 4 {
+            2 => held_notes[column] = Some((event.beat, key_sound)),
+            3 => {
+                if let Some((start_beat, key_sound)) = held_notes[column].take() {
+                    objects.push((start_beat, column, Key::slider_start(Some(time_at(event.beat))), key_sound));
+                    objects.push((event.beat, column, Key::slider_end(), None));
+                }
+            },
+            _ => objects.push((event.beat, column, Key::normal(), key_sound)),
+        }
+    }
+
+    // a long note that never ends is played as a normal note
+    for (column, held_note) in held_notes.into_iter().enumerate() {
+        if let Some((beat, key_sound)) = held_note {
+            objects.push((beat, column, Key::normal(), key_sound));
+        }
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(objects, KEY_COUNT, time_at);
+
+    Ok(Chart::new(metadata.clone(), chartinfo, timing_points, hitobjects, Some(soundbank)))
+}
+
+/// parses the Easy, Normal and Hard charts in an .ojn file, sample names come from its .ojm and keysounds are left out if it's empty
+pub(crate) fn from_ojn_all(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>> {
+    use models::metadata::Metadata;
+
+    if raw_chart.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    if raw_chart.get(4..8) != Some(OJN_SIGNATURE) || raw_chart.len() < OJN_HEADER_SIZE {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("Not an OJN file".to_string())));
+    }
+
+    let mut metadata = Metadata::empty();
+    metadata.title = read_string(raw_chart, 108, 64).or_default_empty(ChartDefaults::TITLE);
+    metadata.artist = read_string(raw_chart, 172, 32).or_default_empty(ChartDefaults::ARTIST);
+    metadata.creator = read_string(raw_chart, 204, 32).or_default_empty(ChartDefaults::CREATOR);
+    metadata.genre = read_i32(raw_chart, 12)
+        .and_then(|genre| GENRES.get(genre.max(0) as usize))
+        .unwrap_or(&GENRES[GENRES.len() - 1])
+        .to_string();
+
+    let sample_names = parse_ojm_sample_names(raw_ojm);
+
+    (0..DIFFICULTY_NAMES.len())
+        .map(|difficulty| build_chart(raw_chart, difficulty, &metadata, &sample_names))
+        .collect()
+}
+
+/// parses the Easy chart of an .ojn file
+pub(crate) fn from_ojn(raw_chart: &[u8], raw_ojm: &[u8]) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    let mut charts = from_ojn_all(raw_chart, raw_ojm)?;
+    Ok(charts.swap_remove(0))
+}
\ No newline at end of file
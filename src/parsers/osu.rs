@@ -19,6 +19,13 @@ use crate::utils::rhythm::{
     calculate_beat_from_time,
 };
 use crate::errors;
+use super::osu_convert::{
+    convert_to_mania,
+    ConvertDifficulty,
+    ConvertObject,
+    ConvertObjectKind,
+    ConvertTimingPoint,
+};
 
 #[derive(Debug, PartialEq, Eq)]
 enum OsuSection {
@@ -29,11 +36,11 @@ enum OsuSection {
     Difficulty,
     Events,
     TimingPoints,
+    Colours,
     HitObjects
 }
 
-#[allow(unused)]
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 enum OsuMode {
     Standard,
     Taiko,
@@ -55,9 +62,14 @@ impl Display for OsuMode {
     }
 }
 
+/// the most keys osu!mania supports
+const MAX_CONVERTED_KEY_COUNT: u8 = 18;
+
 type HitSample = (u8, u8, usize, u8, String);
-type TimingPoint = (i32, f32, f32, f32, i32, f32, bool, u32);
-type HitObject = (u32, u32, i32, u8, u8, i32, HitSample);
+type TimingPoint = (i32, f64, f32, f32, i32, f32, bool, u32);
+/// slides, pixel length and edge sounds of a slider
+type SliderParams = (u32, f64, Vec<u8>);
+type HitObject = (u32, u32, i32, u8, u8, i32, HitSample, Option<SliderParams>);
 
 
 #[derive(Debug, PartialEq, Eq)]
@@ -99,19 +111,38 @@ fn coords_to_column(coords: u32, key_count: u8) -> usize {
     (coords as f32 * key_count as f32 / 512.0) as usize
 }
 
-fn validate_mode_mania(mode: &str) -> Result<bool, Box<dyn std::error::Error>> {
-    use self::OsuMode::*;
+fn parse_mode(mode: &str) -> OsuMode {
+    match mode {
+        "0" => OsuMode::Standard,
+        "1" => OsuMode::Taiko,
+        "2" => OsuMode::Catch,
+        "3" => OsuMode::Mania,
+        _ => OsuMode::Unknown,
+    }
+}
 
-    if mode != "3" {
-        let current_mode = match mode {
-            "0" => Standard,
-            "1" => Taiko,
-            "2" => Catch,
-            _ => Unknown,
-        };
-        return Err( Box::new(errors::ParseError::InvalidMode(current_mode.to_string(), GameMode::Mania)) );
+/// game mode a chart of this mode gets parsed as, standard and catch only get converted when a key count is requested
+fn osu_mode_to_game_mode(mode: &OsuMode, convert_key_count: Option<u8>) -> Result<GameMode, Box<dyn std::error::Error>> {
+    match (mode, convert_key_count) {
+        (OsuMode::Mania, _) => Ok(GameMode::Mania),
+        (OsuMode::Taiko, _) => Ok(GameMode::Taiko),
+        (OsuMode::Standard | OsuMode::Catch, Some(_)) => Ok(GameMode::Mania),
+        _ => Err( Box::new(errors::ParseError::InvalidMode(mode.to_string(), GameMode::Mania)) ),
     }
-    Ok(true)
+}
+
+/// how long a slider lasts in ms, from the bpm and sv at its start time
+fn slider_duration(
+    time: i32,
+    (slides, length, _): &SliderParams,
+    slider_multiplier: f32,
+    timing_points: &models::timing_points::TimingPoints,
+) -> i32 {
+    let (bpm, multiplier) = timing_points.bpm_and_sv_at(time);
+    // osu! clamps inherited beat lengths to -1000..-10
+    let multiplier = multiplier.clamp(0.1, 10.0);
+    let beats = *length as f32 / (slider_multiplier * 100.0 * multiplier);
+    (beats * 60000.0 / bpm * *slides as f32).round() as i32
 }
 
 
@@ -149,6 +180,7 @@ where
             "[Difficulty]" => OsuSection::Difficulty,
             "[Events]" => OsuSection::Events,
             "[TimingPoints]" => OsuSection::TimingPoints,
+            "[Colours]" => OsuSection::Colours,
             "[HitObjects]" => OsuSection::HitObjects,
             _ => OsuSection::Unknown,
         };
@@ -220,7 +252,11 @@ fn parse_timing_point(raw: &str) -> Result<TimingPoint, Box<dyn std::error::Erro
 }
 
 fn parse_hitsample(raw: &str) -> Result<HitSample, Box<dyn std::error::Error>> {
-    
+    // older beatmaps leave the hit sample out entirely
+    if raw.trim().is_empty() {
+        return Ok((0, 0, 0, 0, String::new()));
+    }
+
     let mut components = raw.split(":");
 
     let normal_set = parse_next!("normalSet", components, raw);
@@ -241,18 +277,39 @@ fn parse_hitobject(raw: &str) -> Result<HitObject, Box<dyn std::error::Error>> {
     let key_type: u8 = parse_next!("note type", components, raw);
     let hit_sound = parse_next!("hit sound", components, raw);
 
-    if key_type == 128 {
+    if key_type & 128 != 0 {
         let joined = components.collect::<Vec<_>>().join("");
         let mut split_components = joined.splitn(2, ":");
         let object_params = parse_next!("object params", split_components, raw);
         let hit_sample_raw = get_next!("hit sample", split_components, raw);
         let hit_sample = parse_hitsample(&hit_sample_raw)?;
-        Ok((x, y, time, key_type, hit_sound, object_params, hit_sample))
+        Ok((x, y, time, key_type, hit_sound, object_params, hit_sample, None))
+    } else if key_type & 8 != 0 {
+        let end_time = parse_next!("end time", components, raw);
+        let hit_sample_raw = get_next!("hit sample", components, raw);
+        let hit_sample = parse_hitsample(&hit_sample_raw)?;
+        Ok((x, y, time, key_type, hit_sound, end_time, hit_sample, None))
+    } else if key_type & 2 != 0 {
+        #[allow(unused)]
+        let curve = get_next!("curve", components, raw);
+        let slides = parse_next!("slides", components, raw);
+        let length = parse_next!("length", components, raw);
+        let edge_sounds = get_next!("edge sounds", components, raw);
+        // osu! reads an edge sound it can't parse as no hitsound
+        let edge_sounds = edge_sounds.split('|')
+            .filter(|edge_sound| !edge_sound.is_empty())
+            .map(|edge_sound| edge_sound.parse().unwrap_or(0))
+            .collect();
+        #[allow(unused)]
+        let edge_sets = get_next!("edge sets", components, raw);
+        let hit_sample_raw = get_next!("hit sample", components, raw);
+        let hit_sample = parse_hitsample(&hit_sample_raw)?;
+        Ok((x, y, time, key_type, hit_sound, 0, hit_sample, Some((slides, length, edge_sounds))))
     } else {
         let hit_sample_raw = get_next!("hit sample", components, raw);
         
         let hit_sample = parse_hitsample(&hit_sample_raw)?;
-        Ok((x, y, time, key_type, hit_sound, 0, hit_sample))
+        Ok((x, y, time, key_type, hit_sound, 0, hit_sample, None))
     }
 }
 
@@ -301,7 +358,7 @@ fn parse_event(line: &str) -> Result<Event, Box<dyn std::error::Error>> {
                 volume
             })
         }
-        "2" => {
+        "2" | "Break" => {
             Ok(Event::Break {
                 start_time: parse_next!("start_time", components, line),
                 end_time: parse_next!("end_time", components, line),
@@ -311,9 +368,11 @@ fn parse_event(line: &str) -> Result<Event, Box<dyn std::error::Error>> {
     }
 }
 
+/// the timing points are also kept in `convert_timing_points` as they are for converting to mania
 fn process_timing_points(
     timing_points: &mut models::timing_points::TimingPoints,
     chartinfo: &mut models::chartinfo::ChartInfo,
+    convert_timing_points: &mut Vec<ConvertTimingPoint>,
     raw: &str
 ) -> Result<(), Box<dyn std::error::Error>>  {
     use models::timing_points::TimingChange;
@@ -321,19 +380,16 @@ fn process_timing_points(
     for line in raw.lines().map(str::trim) {
         #[allow(unused)]
         let (time, beat_length, meter, sample_set, sample_index, volume, uninherited, effects) = parse_timing_point(line)?;
-        
+        // the first effect bit is kiai
+        convert_timing_points.push((time, beat_length, uninherited, effects & 1 != 0));
+
+        let beat_length = beat_length as f32;
         if uninherited {
             let bpm = beatlength_to_bpm(&beat_length);
-            timing_points.add(time, 0.0, TimingChange {
-                change_type: TimingChangeType::Bpm,
-                value: bpm,
-            });
+            timing_points.add(time, 0.0, TimingChange::new(TimingChangeType::Bpm, bpm));
         } else {
             let multiplier = beatlength_to_multiplier(&beat_length);
-            timing_points.add(time, 0.0, TimingChange {
-                change_type: TimingChangeType::Sv,
-                value: multiplier,
-            });
+            timing_points.add(time, 0.0, TimingChange::new(TimingChangeType::Sv, multiplier));
         }
     }
 
@@ -430,15 +486,202 @@ fn process_hitobjects(
         }
     }
 
+    let bpm_times: Vec<i32> = timing_points.bpm_changes_zipped().map(|(time, _, _)| *time).collect();
     timeline.to_hitobjects(hitobjects,
         chartinfo.audio_offset, key_count as usize,
-        &timing_points.times, &timing_points.bpms());
+        &bpm_times, &timing_points.bpms());
 
     Ok(())
 }
 
-#[allow(clippy::single_match)]
+fn process_taiko_hitobjects(
+    hitobjects: &mut models::hitobjects::HitObjects,
+    timing_points: &mut models::timing_points::TimingPoints,
+    chartinfo: &mut models::chartinfo::ChartInfo,
+    soundbank: &mut models::sound::SoundBank,
+    slider_multiplier: f32,
+    raw: &str
+) -> Result<(), Box<dyn std::error::Error>> {
+    use models::timeline::{HitObjectTimeline, TimelineHitObject};
+
+    let lines: Vec<&str> = raw.lines().map(str::trim).filter(|s| !s.is_empty()).collect();
+    let mut timeline: HitObjectTimeline = HitObjectTimeline::with_capacity(lines.len());
+
+    for line in lines {
+        #[allow(unused)]
+        let (x, y, object_time, object_type, object_hitsound, end_time, object_keysound, slider_params) = parse_hitobject(line)?;
+
+        // whistles and claps make a kat, finishes make it big
+        let is_kat = object_hitsound & (2 | 8) != 0;
+        let is_big = object_hitsound & 4 != 0;
+
+        let key = if object_type & 8 != 0 {
+            Key::spinner(Some(end_time))
+        } else if let Some(slider_params) = slider_params {
+            let slider_end_time = object_time + slider_duration(object_time, &slider_params, slider_multiplier, timing_points);
+            if is_big {
+                Key::big_drumroll(Some(slider_end_time))
+            } else {
+                Key::drumroll(Some(slider_end_time))
+            }
+        } else if object_type & 1 != 0 {
+            match (is_kat, is_big) {
+                (false, false) => Key::don(),
+                (true, false) => Key::kat(),
+                (false, true) => Key::big_don(),
+                (true, true) => Key::big_kat(),
+            }
+        } else {
+            continue;
+        };
+
+        // the hitsound is already what the note is, so only custom samples are kept
+        let key_sound = if object_keysound.4.trim().is_empty() {
+            None
+        } else {
+            let idx = soundbank.get_index_sample(&object_keysound.4)
+                .unwrap_or_else(|| soundbank.add_sound_sample(object_keysound.4));
+            Some(KeySound::with_custom(object_keysound.3.clamp(0, 100), idx, None))
+        };
+
+        timeline.add_sorted(TimelineHitObject {
+            time: object_time,
+            column: 0,
+            key,
+            keysound: key_sound,
+        });
+    }
+
+    let bpm_times: Vec<i32> = timing_points.bpm_changes_zipped().map(|(time, _, _)| *time).collect();
+    timeline.to_hitobjects(hitobjects,
+        chartinfo.audio_offset, 1,
+        &bpm_times, &timing_points.bpms());
+
+    Ok(())
+}
+
+/// converts standard and catch objects into mania notes the way osu!stable does with a key mod on.
+/// osu! can put a note in a column that's still held, those notes are left out and how many there were is returned
+#[allow(clippy::too_many_arguments)]
+fn process_converted_mania_hitobjects(
+    hitobjects: &mut models::hitobjects::HitObjects,
+    timing_points: &mut models::timing_points::TimingPoints,
+    chartinfo: &mut models::chartinfo::ChartInfo,
+    soundbank: &mut models::sound::SoundBank,
+    convert_timing_points: &[ConvertTimingPoint],
+    total_break_time: i32,
+    difficulty: &ConvertDifficulty,
+    raw: &str
+) -> Result<usize, Box<dyn std::error::Error>> {
+    use models::timeline::{HitObjectTimeline, TimelineHitObject};
+    use models::sound::HitSoundType;
+
+    let key_count = chartinfo.key_count as usize;
+
+    let lines: Vec<&str> = raw.lines().map(str::trim).filter(|s| !s.is_empty()).collect();
+    let mut objects: Vec<(ConvertObject, HitSample)> = Vec::with_capacity(lines.len());
+    for line in lines {
+        let (x, y, object_time, object_type, object_hitsound, end_time, object_keysound, slider_params) = parse_hitobject(line)?;
+
+        let kind = if object_type & (8 | 128) != 0 {
+            ConvertObjectKind::Spinner { end_time }
+        } else if let Some((slides, length, edge_sounds)) = slider_params {
+            let node_hitsounds = (0..=slides.max(1) as usize)
+                .map(|node| edge_sounds.get(node).copied().unwrap_or(object_hitsound))
+                .collect();
+            ConvertObjectKind::Slider { spans: slides as i32, length, node_hitsounds }
+        } else if object_type & 1 != 0 {
+            ConvertObjectKind::Circle
+        } else {
+            continue;
+        };
+        objects.push((ConvertObject { time: object_time, x: x as f32, y: y as f32, hitsound: object_hitsound, kind }, object_keysound));
+    }
+    // osu! sorts the objects of hand edited beatmaps, keeping the order of ones on the same time
+    objects.sort_by_key(|(object, _)| object.time);
+    let (objects, samples): (Vec<ConvertObject>, Vec<HitSample>) = objects.into_iter().unzip();
+
+    let mut notes = convert_to_mania(&objects, convert_timing_points, total_break_time, difficulty, chartinfo.key_count);
+    notes.sort_by_key(|note| note.time);
+
+    let mut timeline: HitObjectTimeline = HitObjectTimeline::with_capacity(notes.len() * 2);
+    // when what was last put in each column ends
+    let mut held_until: Vec<Option<i32>> = vec![None; key_count];
+    let mut left_out = 0;
+    for note in notes {
+        let column = note.column as usize;
+        if held_until[column].is_some_and(|held_until| note.time <= held_until) {
+            left_out += 1;
+            continue;
+        }
+        held_until[column] = Some(note.end_time.unwrap_or(note.time));
+
+        let (_, _, _, volume, filename) = &samples[note.object];
+        let volume = (*volume).clamp(0, 100);
+        let keysound = if !filename.trim().is_empty() {
+            let idx = soundbank.get_index_sample(filename)
+                .unwrap_or_else(|| soundbank.add_sound_sample(filename.clone()));
+            Some(KeySound::with_custom(volume, idx, None))
+        } else if note.hitsound & 4 != 0 {
+            Some(KeySound::of_type(volume, HitSoundType::Finish))
+        } else if note.hitsound & 8 != 0 {
+            Some(KeySound::of_type(volume, HitSoundType::Clap))
+        } else if note.hitsound & 2 != 0 {
+            Some(KeySound::of_type(volume, HitSoundType::Whistle))
+        } else {
+            None
+        };
+
+        match note.end_time {
+            Some(end_time) => {
+                timeline.add_sorted(TimelineHitObject {
+                    time: note.time,
+                    column,
+                    key: Key::slider_start(Some(end_time)),
+                    keysound,
+                });
+                timeline.add_sorted(TimelineHitObject {
+                    time: end_time,
+                    column,
+                    key: Key::slider_end(),
+                    keysound: None,
+                });
+            },
+            None => {
+                timeline.add_sorted(TimelineHitObject {
+                    time: note.time,
+                    column,
+                    key: Key::normal(),
+                    keysound,
+                });
+            },
+        }
+    }
+
+    let bpm_times: Vec<i32> = timing_points.bpm_changes_zipped().map(|(time, _, _)| *time).collect();
+    timeline.to_hitobjects(hitobjects,
+        chartinfo.audio_offset, key_count,
+        &bpm_times, &timing_points.bpms());
+
+    Ok(left_out)
+}
+
 pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    parse_osu(raw_chart, None)
+}
+
+/// parses standard and catch beatmaps as `key_count` key mania charts, converted like osu!stable does
+/// with the matching key mod on. mania and taiko beatmaps are parsed like `from_osu` does
+pub(crate) fn from_osu_as_mania(raw_chart: &str, key_count: u8) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    if !(1..=MAX_CONVERTED_KEY_COUNT).contains(&key_count) {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(
+            format!("Can't convert into {key_count}k, the key count has to be between 1 and {MAX_CONVERTED_KEY_COUNT}"))));
+    }
+    parse_osu(raw_chart, Some(key_count))
+}
+
+#[allow(clippy::single_match)]
+fn parse_osu(raw_chart: &str, convert_key_count: Option<u8>) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
     use self::OsuSection;
     use models::{metadata::Metadata, chartinfo::ChartInfo, timing_points::TimingPoints, hitobjects::HitObjects, sound, chart::Chart};
 
@@ -454,6 +697,15 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn
     let mut soundbank = sound::SoundBank::new();
 
     let mut key_count = 0;
+    let mut slider_multiplier = 1.4;
+    // what converting to mania is seeded with, the approach rate is the overall difficulty unless it's set
+    let mut difficulty = ConvertDifficulty { hp: 5.0, cs: 5.0, od: 5.0, ar: 5.0, slider_multiplier: 1.4 };
+    let mut approach_rate: Option<f32> = None;
+    let mut convert_timing_points: Vec<ConvertTimingPoint> = Vec::new();
+    let mut total_break_time = 0;
+    let mut left_out = 0;
+    // beatmaps without a mode are treated as mania
+    let mut osu_mode = OsuMode::Mania;
 
     let mut temp_row: Row = Vec::new();
     let mut temp_hitsounds: Vec<u8> = Vec::new();
@@ -469,7 +721,16 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn
                         },
                         "AudioLeadIn" => {}, // TODO: recheck later
                         "PreviewTime" => chartinfo.preview_time = value.or_default_empty_as(*ChartDefaults::PREVIEW_TIME),
-                        "Mode" => { validate_mode_mania(value)?; }, // TODO: modify this when adding taiko support later
+                        "Mode" => {
+                            osu_mode = parse_mode(value);
+                            chartinfo.game_mode = osu_mode_to_game_mode(&osu_mode, convert_key_count)?;
+                            match osu_mode {
+                                // taiko is played on a single drum lane
+                                OsuMode::Taiko => chartinfo.key_count = 1,
+                                OsuMode::Standard | OsuMode::Catch => chartinfo.key_count = convert_key_count.unwrap_or(chartinfo.key_count),
+                                _ => {},
+                            }
+                        },
                         _ => {},
                     }
                     Ok(())
@@ -508,7 +769,7 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn
                             }
                         },
                         Event::Video { start_time, filename } => {},
-                        Event::Break { start_time, end_time } => {},
+                        Event::Break { start_time, end_time } => total_break_time += (end_time - start_time).max(0),
                         Event::Sample { start_time, sample_path, volume } => {
                             let idx = soundbank.add_sound_sample(sample_path);
                             soundbank.add_sound_effect(SoundEffect::new(start_time, volume.clamp(0, 100), idx));
@@ -522,24 +783,42 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn
             OsuSection::Difficulty => {
                 process_key_value(content, |key, value| {
                     match key {
+                        "HPDrainRate" => difficulty.hp = value.or_default_empty_as::<f32>(5.0),
                         "CircleSize" => {
-                            key_count = value.or_default_empty_as::<f32>(*ChartDefaults::KEY_COUNT as f32) as u8;
-                            temp_row = vec![Key::empty(); key_count as usize];
-                            temp_hitsounds = vec![0; key_count as usize];
-                            chartinfo.key_count = key_count;
+                            difficulty.cs = value.or_default_empty_as::<f32>(5.0);
+                            if osu_mode == OsuMode::Mania {
+                                key_count = value.or_default_empty_as::<f32>(*ChartDefaults::KEY_COUNT as f32) as u8;
+                                temp_row = vec![Key::empty(); key_count as usize];
+                                temp_hitsounds = vec![0; key_count as usize];
+                                chartinfo.key_count = key_count;
+                            }
+                        },
+                        "OverallDifficulty" => difficulty.od = value.or_default_empty_as::<f32>(5.0),
+                        "ApproachRate" => approach_rate = Some(value.or_default_empty_as::<f32>(5.0)),
+                        "SliderMultiplier" => {
+                            difficulty.slider_multiplier = value.or_default_empty_as::<f64>(1.4);
+                            slider_multiplier = difficulty.slider_multiplier as f32;
                         },
                         _ => {},
                     }
                     Ok(())
                 })?;
+                difficulty.ar = approach_rate.unwrap_or(difficulty.od);
             },
 
             OsuSection::TimingPoints => {
-                process_timing_points(&mut timing_points, &mut chartinfo, content)?;
+                process_timing_points(&mut timing_points, &mut chartinfo, &mut convert_timing_points, content)?;
             },
             
             OsuSection::HitObjects => {
-                process_hitobjects(&mut hitobjects, &mut timing_points, &mut chartinfo, &mut soundbank, content)?
+                match osu_mode {
+                    OsuMode::Taiko => process_taiko_hitobjects(&mut hitobjects, &mut timing_points, &mut chartinfo, &mut soundbank, slider_multiplier, content)?,
+                    OsuMode::Standard | OsuMode::Catch => {
+                        left_out = process_converted_mania_hitobjects(&mut hitobjects, &mut timing_points, &mut chartinfo, &mut soundbank,
+                            &convert_timing_points, total_break_time, &difficulty, content)?;
+                    },
+                    _ => process_hitobjects(&mut hitobjects, &mut timing_points, &mut chartinfo, &mut soundbank, content)?,
+                }
             },
             _ => {},
             
@@ -547,5 +826,9 @@ pub(crate) fn from_osu(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn
         Ok(())
     })?;
     
-    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank)))
+    let mut chart = Chart::new(metadata, chartinfo, timing_points, hitobjects, Some(soundbank));
+    if left_out > 0 {
+        chart.warnings.push(format!("{left_out} converted notes landed in a column that was still held and were left out"));
+    }
+    Ok(chart)
 }
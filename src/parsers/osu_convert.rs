@@ -0,0 +1,915 @@
+// osu!stable's conversion of standard and catch beatmaps into mania. this follows osu!lazer's
+// legacy pattern generators, which reproduce stable's conversion including its random number generator
+
+use std::collections::VecDeque;
+
+const HITSOUND_WHISTLE: u8 = 2;
+const HITSOUND_FINISH: u8 = 4;
+const HITSOUND_CLAP: u8 = 8;
+
+const PATTERN_NONE: u16 = 0;
+const PATTERN_FORCE_STACK: u16 = 1;
+const PATTERN_FORCE_NOT_STACK: u16 = 1 << 1;
+const PATTERN_KEEP_SINGLE: u16 = 1 << 2;
+const PATTERN_LOW_PROBABILITY: u16 = 1 << 3;
+const PATTERN_GATHERED: u16 = 1 << 7;
+const PATTERN_MIRROR: u16 = 1 << 8;
+const PATTERN_REVERSE: u16 = 1 << 9;
+const PATTERN_CYCLE: u16 = 1 << 10;
+const PATTERN_STAIR: u16 = 1 << 11;
+const PATTERN_REVERSE_STAIR: u16 = 1 << 12;
+
+/// how many of the latest note times the density is taken from
+const DENSITY_NOTE_COUNT: usize = 7;
+/// beat length of a beatmap without uninherited timing points
+const DEFAULT_BEAT_LENGTH: f64 = 1000.0;
+
+pub(super) enum ConvertObjectKind {
+    Circle,
+    /// spans is how many times the slider is travelled, node_hitsounds has the hitsound of each of its ends
+    Slider {
+        spans: i32,
+        length: f64,
+        node_hitsounds: Vec<u8>,
+    },
+    /// spinners, bananas and mania holds
+    Spinner {
+        end_time: i32,
+    },
+}
+
+/// an object of the beatmap that's being converted
+pub(super) struct ConvertObject {
+    pub time: i32,
+    pub x: f32,
+    pub y: f32,
+    pub hitsound: u8,
+    pub kind: ConvertObjectKind,
+}
+
+/// time, beat length, uninherited and kiai of a timing point
+pub(super) type ConvertTimingPoint = (i32, f64, bool, bool);
+
+/// the difficulty settings the conversion is seeded with
+pub(super) struct ConvertDifficulty {
+    pub hp: f32,
+    pub cs: f32,
+    pub od: f32,
+    pub ar: f32,
+    pub slider_multiplier: f64,
+}
+
+/// a mania note, held until end_time if it has one.
+/// object is the index of the object it was converted from and hitsound the hitsound it plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ConvertedNote {
+    pub time: i32,
+    pub end_time: Option<i32>,
+    pub column: i32,
+    pub object: usize,
+    pub hitsound: u8,
+}
+
+/// the xorshift generator osu!stable converts with, so the same seed gives the same columns
+struct LegacyRandom {
+    x: u32,
+    y: u32,
+    z: u32,
+    w: u32,
+}
+
+impl LegacyRandom {
+    fn new(seed: i32) -> Self {
+        Self { x: seed as u32, y: 842502087, z: 3579807591, w: 273326509 }
+    }
+
+    fn next_uint(&mut self) -> u32 {
+        let t = self.x ^ (self.x << 11);
+        self.x = self.y;
+        self.y = self.z;
+        self.z = self.w;
+        self.w = self.w ^ (self.w >> 19) ^ t ^ (t >> 8);
+        self.w
+    }
+
+    fn next_double(&mut self) -> f64 {
+        (self.next_uint() & 0x7FFF_FFFF) as f64 * (1.0 / (i32::MAX as f64 + 1.0))
+    }
+
+    fn next_in(&mut self, lower_bound: i32, upper_bound: i32) -> i32 {
+        (lower_bound as f64 + self.next_double() * (upper_bound - lower_bound) as f64) as i32
+    }
+}
+
+/// notes generated together, with the columns they take up
+#[derive(Default)]
+struct Pattern {
+    notes: Vec<ConvertedNote>,
+    columns: Vec<i32>,
+}
+
+impl Pattern {
+    fn add(&mut self, note: ConvertedNote) {
+        if !self.columns.contains(&note.column) {
+            self.columns.push(note.column);
+        }
+        self.notes.push(note);
+    }
+
+    fn append(&mut self, other: &mut Pattern) {
+        for note in other.notes.drain(..) {
+            self.add(note);
+        }
+        other.columns.clear();
+    }
+
+    fn has_column(&self, column: i32) -> bool {
+        self.columns.contains(&column)
+    }
+
+    fn column_count(&self) -> i32 {
+        self.columns.len() as i32
+    }
+}
+
+/// the timing, sv and kiai at any time, with points on the same time resolved like osu! does:
+/// the first uninherited point sets the beat length and the last inherited one the sv and kiai
+struct ControlPoints {
+    beat_lengths: Vec<(i32, f64)>,
+    /// time, bpm multiplier and kiai
+    effects: Vec<(i32, f64, bool)>,
+}
+
+impl ControlPoints {
+    fn new(timing_points: &[ConvertTimingPoint]) -> Self {
+        let mut beat_lengths: Vec<(i32, f64)> = Vec::new();
+        let mut effects: Vec<(i32, f64, bool)> = Vec::new();
+
+        for group in timing_points.chunk_by(|a, b| a.0 == b.0) {
+            let time = group[0].0;
+            if let Some((_, beat_length, _, _)) = group.iter().find(|point| point.2) {
+                beat_lengths.push((time, beat_length.clamp(6.0, 60000.0)));
+            }
+            let effect = group.iter().rev().find(|point| !point.2)
+                .or_else(|| group.iter().find(|point| point.2));
+            if let Some((_, beat_length, _, kiai)) = effect {
+                let bpm_multiplier = if *beat_length < 0.0 {
+                    (-*beat_length as f32).clamp(10.0, 10000.0) as f64 / 100.0
+                } else {
+                    1.0
+                };
+                effects.push((time, bpm_multiplier, *kiai));
+            }
+        }
+
+        Self { beat_lengths, effects }
+    }
+
+    fn beat_length_at(&self, time: i32) -> f64 {
+        let i = self.beat_lengths.partition_point(|(point_time, _)| *point_time <= time);
+        self.beat_lengths.get(i.saturating_sub(1)).map_or(DEFAULT_BEAT_LENGTH, |(_, beat_length)| *beat_length)
+    }
+
+    /// bpm multiplier and kiai
+    fn effect_at(&self, time: i32) -> (f64, bool) {
+        let i = self.effects.partition_point(|(point_time, _, _)| *point_time <= time);
+        if i == 0 {
+            return (1.0, false);
+        }
+        let (_, bpm_multiplier, kiai) = self.effects[i - 1];
+        (bpm_multiplier, kiai)
+    }
+}
+
+/// what the pattern generators share, none of them can place a note if every column they could use is taken
+struct Generator<'a> {
+    random: &'a mut LegacyRandom,
+    object: &'a ConvertObject,
+    index: usize,
+    previous: &'a Pattern,
+    total_columns: i32,
+    random_start: i32,
+    conversion_difficulty: f64,
+}
+
+impl Generator<'_> {
+    fn has_hitsound(&self, hitsound: u8) -> bool {
+        self.object.hitsound & hitsound != 0
+    }
+
+    fn get_column(&self, position: f32, allow_special: bool) -> i32 {
+        if allow_special && self.total_columns == 8 {
+            return ((position / (512.0 / 7.0)).floor() as i32).clamp(0, 6) + 1;
+        }
+        ((position / (512.0 / self.total_columns as f32)).floor() as i32).clamp(0, self.total_columns - 1)
+    }
+
+    fn get_random_column(&mut self, lower_bound: Option<i32>, upper_bound: Option<i32>) -> i32 {
+        self.random.next_in(lower_bound.unwrap_or(self.random_start), upper_bound.unwrap_or(self.total_columns))
+    }
+
+    fn get_random_note_count(&mut self, p2: f64, p3: f64, p4: f64, p5: f64, p6: f64) -> i32 {
+        let value = self.random.next_double();
+        if value >= 1.0 - p6 {
+            6
+        } else if value >= 1.0 - p5 {
+            5
+        } else if value >= 1.0 - p4 {
+            4
+        } else if value >= 1.0 - p3 {
+            3
+        } else if value >= 1.0 - p2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// the initial column if it's free, otherwise random columns (or the next column when gathered)
+    /// until one is free. columns are taken when they're in one of the patterns or excluded
+    fn find_available_column(
+        &mut self,
+        initial_column: i32,
+        bounds: (Option<i32>, Option<i32>),
+        gathered: bool,
+        excluded: Option<i32>,
+        patterns: &[&Pattern],
+    ) -> Option<i32> {
+        let lower_bound = bounds.0.unwrap_or(self.random_start);
+        let upper_bound = bounds.1.unwrap_or(self.total_columns);
+        let is_valid = |column: i32| excluded != Some(column) && patterns.iter().all(|pattern| !pattern.has_column(column));
+
+        if is_valid(initial_column) {
+            return Some(initial_column);
+        }
+        if !(lower_bound..upper_bound).any(is_valid) {
+            return None;
+        }
+
+        let mut column = initial_column;
+        loop {
+            column = if gathered {
+                if column + 1 == self.total_columns { self.random_start } else { column + 1 }
+            } else {
+                self.get_random_column(Some(lower_bound), Some(upper_bound))
+            };
+            if is_valid(column) {
+                return Some(column);
+            }
+        }
+    }
+
+    fn note(&self, column: i32, time: i32, end_time: Option<i32>, hitsound: u8) -> ConvertedNote {
+        ConvertedNote { time, end_time, column, object: self.index, hitsound }
+    }
+
+    /// circles, the column comes from the x position unless the time and distance to the last note make a pattern
+    fn hit_object_pattern(
+        &mut self,
+        (previous_time, previous_position): (f64, (f32, f32)),
+        density: f64,
+        last_stair: u16,
+        control_points: &ControlPoints,
+    ) -> (Option<Pattern>, u16) {
+        let beat_length = control_points.beat_length_at(self.object.time);
+        let (_, kiai) = control_points.effect_at(self.object.time);
+
+        let position_separation = ((self.object.x - previous_position.0).powi(2) + (self.object.y - previous_position.1).powi(2)).sqrt();
+        let time_separation = self.object.time as f64 - previous_time;
+
+        let mut convert_type = if time_separation <= 80.0 {
+            PATTERN_FORCE_NOT_STACK | PATTERN_KEEP_SINGLE
+        } else if time_separation <= 95.0 {
+            PATTERN_FORCE_NOT_STACK | PATTERN_KEEP_SINGLE | last_stair
+        } else if time_separation <= 105.0 {
+            PATTERN_FORCE_NOT_STACK | PATTERN_LOW_PROBABILITY
+        } else if time_separation <= 125.0 {
+            PATTERN_FORCE_NOT_STACK
+        } else if time_separation <= 135.0 && position_separation < 20.0 {
+            PATTERN_CYCLE | PATTERN_KEEP_SINGLE
+        } else if time_separation <= 150.0 && position_separation < 20.0 {
+            PATTERN_FORCE_STACK | PATTERN_LOW_PROBABILITY
+        } else if position_separation < 20.0 && density >= beat_length / 2.5 {
+            PATTERN_REVERSE | PATTERN_LOW_PROBABILITY
+        } else if density < beat_length / 2.5 || kiai {
+            PATTERN_NONE
+        } else {
+            PATTERN_LOW_PROBABILITY
+        };
+
+        if convert_type & PATTERN_KEEP_SINGLE == 0 {
+            if self.has_hitsound(HITSOUND_FINISH) && self.total_columns != 8 {
+                convert_type |= PATTERN_MIRROR;
+            } else if self.has_hitsound(HITSOUND_CLAP) {
+                convert_type |= PATTERN_GATHERED;
+            }
+        }
+
+        let pattern = self.hit_object_pattern_core(convert_type);
+
+        let mut stair_type = last_stair;
+        for note in pattern.iter().flat_map(|pattern| &pattern.notes) {
+            if convert_type & PATTERN_STAIR != 0 && note.column == self.total_columns - 1 {
+                stair_type = PATTERN_REVERSE_STAIR;
+            }
+            if convert_type & PATTERN_REVERSE_STAIR != 0 && note.column == self.random_start {
+                stair_type = PATTERN_STAIR;
+            }
+        }
+        (pattern, stair_type)
+    }
+
+    fn hit_object_pattern_core(&mut self, convert_type: u16) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let single = |generator: &Self, column: i32| {
+            let mut pattern = Pattern::default();
+            pattern.add(generator.note(column, generator.object.time, None, generator.object.hitsound));
+            Some(pattern)
+        };
+
+        if self.total_columns == 1 {
+            return single(self, 0);
+        }
+
+        let last_column = self.previous.notes.first().map_or(0, |note| note.column);
+
+        if convert_type & PATTERN_REVERSE != 0 && !self.previous.notes.is_empty() {
+            // the columns of the last pattern, mirrored
+            for i in self.random_start..self.total_columns {
+                if self.previous.has_column(i) {
+                    pattern.add(self.note(self.random_start + self.total_columns - i - 1, self.object.time, None, self.object.hitsound));
+                }
+            }
+            return Some(pattern);
+        }
+
+        if convert_type & PATTERN_CYCLE != 0 && self.previous.notes.len() == 1
+            // the special key of 7k+1 and the middle column aren't cycled out of
+            && (self.total_columns != 8 || last_column != 0)
+            && (self.total_columns % 2 == 0 || last_column != self.total_columns / 2)
+        {
+            return single(self, self.random_start + self.total_columns - last_column - 1);
+        }
+
+        if convert_type & PATTERN_FORCE_STACK != 0 && !self.previous.notes.is_empty() {
+            // on the columns of the last pattern
+            for i in self.random_start..self.total_columns {
+                if self.previous.has_column(i) {
+                    pattern.add(self.note(i, self.object.time, None, self.object.hitsound));
+                }
+            }
+            return Some(pattern);
+        }
+
+        if self.previous.notes.len() == 1 {
+            if convert_type & PATTERN_STAIR != 0 {
+                let column = if last_column + 1 == self.total_columns { self.random_start } else { last_column + 1 };
+                return single(self, column);
+            }
+            if convert_type & PATTERN_REVERSE_STAIR != 0 {
+                let column = if last_column - 1 == self.random_start - 1 { self.total_columns - 1 } else { last_column - 1 };
+                return single(self, column);
+            }
+        }
+
+        if convert_type & PATTERN_KEEP_SINGLE != 0 {
+            return self.random_notes(convert_type, 1);
+        }
+
+        if convert_type & PATTERN_MIRROR != 0 {
+            return if self.conversion_difficulty > 6.5 {
+                self.random_pattern_with_mirrored(convert_type, 0.12, 0.38, 0.12)
+            } else if self.conversion_difficulty > 4.0 {
+                self.random_pattern_with_mirrored(convert_type, 0.12, 0.17, 0.0)
+            } else {
+                self.random_pattern_with_mirrored(convert_type, 0.12, 0.0, 0.0)
+            };
+        }
+
+        let low_probability = convert_type & PATTERN_LOW_PROBABILITY != 0;
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.random_pattern(convert_type, 0.78, 0.42, 0.0, 0.0)
+            } else {
+                self.random_pattern(convert_type, 1.0, 0.62, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.random_pattern(convert_type, 0.35, 0.08, 0.0, 0.0)
+            } else {
+                self.random_pattern(convert_type, 0.52, 0.15, 0.0, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.0 {
+            if low_probability {
+                self.random_pattern(convert_type, 0.18, 0.0, 0.0, 0.0)
+            } else {
+                self.random_pattern(convert_type, 0.45, 0.0, 0.0, 0.0)
+            }
+        } else {
+            self.random_pattern(convert_type, 0.0, 0.0, 0.0, 0.0)
+        }
+    }
+
+    fn random_notes(&mut self, convert_type: u16, note_count: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+        let gathered = convert_type & PATTERN_GATHERED != 0;
+        let allow_stacking = convert_type & PATTERN_FORCE_NOT_STACK == 0;
+
+        let note_count = if allow_stacking {
+            note_count
+        } else {
+            note_count.min(self.total_columns - self.random_start - previous.column_count())
+        };
+
+        let mut column = self.get_column(self.object.x, true);
+        for _ in 0..note_count {
+            column = if allow_stacking {
+                self.find_available_column(column, (None, None), gathered, None, &[&pattern])?
+            } else {
+                self.find_available_column(column, (None, None), gathered, None, &[&pattern, previous])?
+            };
+            pattern.add(self.note(column, self.object.time, None, self.object.hitsound));
+        }
+        Some(pattern)
+    }
+
+    fn random_pattern(&mut self, convert_type: u16, p2: f64, p3: f64, p4: f64, p5: f64) -> Option<Pattern> {
+        let note_count = self.random_note_count(p2, p3, p4, p5);
+        self.random_notes(convert_type, note_count)
+    }
+
+    fn random_pattern_with_mirrored(&mut self, convert_type: u16, centre_probability: f64, p2: f64, p3: f64) -> Option<Pattern> {
+        if convert_type & PATTERN_FORCE_NOT_STACK != 0 {
+            return self.random_pattern(convert_type, 0.5 + p2 / 2.0, p2, (p2 + p3) / 2.0, p3);
+        }
+
+        let mut pattern = Pattern::default();
+        let (note_count, add_to_centre) = self.random_note_count_mirrored(centre_probability, p2, p3);
+
+        let column_limit = (if self.total_columns % 2 == 0 { self.total_columns } else { self.total_columns - 1 }) / 2;
+        let mut column = self.get_random_column(None, Some(column_limit));
+        for _ in 0..note_count {
+            column = self.find_available_column(column, (None, Some(column_limit)), false, None, &[&pattern])?;
+            pattern.add(self.note(column, self.object.time, None, self.object.hitsound));
+            pattern.add(self.note(self.random_start + self.total_columns - column - 1, self.object.time, None, self.object.hitsound));
+        }
+
+        if add_to_centre {
+            pattern.add(self.note(self.total_columns / 2, self.object.time, None, self.object.hitsound));
+        }
+        if self.random_start > 0 && self.has_hitsound(HITSOUND_CLAP) && self.has_hitsound(HITSOUND_FINISH) {
+            pattern.add(self.note(0, self.object.time, None, self.object.hitsound));
+        }
+        Some(pattern)
+    }
+
+    fn random_note_count(&mut self, mut p2: f64, mut p3: f64, mut p4: f64, mut p5: f64) -> i32 {
+        match self.total_columns {
+            2 => (p2, p3, p4, p5) = (0.0, 0.0, 0.0, 0.0),
+            3 => (p2, p3, p4, p5) = (p2.min(0.1), 0.0, 0.0, 0.0),
+            4 => (p2, p3, p4, p5) = (p2.min(0.23), p3.min(0.04), 0.0, 0.0),
+            5 => (p3, p4, p5) = (p3.min(0.15), p4.min(0.03), 0.0),
+            _ => {},
+        }
+        if self.has_hitsound(HITSOUND_CLAP) {
+            p2 = 1.0;
+        }
+        self.get_random_note_count(p2, p3, p4, p5, 0.0)
+    }
+
+    /// how many notes go on each side and whether there's one in the middle
+    fn random_note_count_mirrored(&mut self, mut centre_probability: f64, mut p2: f64, mut p3: f64) -> (i32, bool) {
+        // stable's probabilities are doubled as "1 - p", which can take them past 1
+        match self.total_columns {
+            2 => (centre_probability, p2, p3) = (0.0, 0.0, 0.0),
+            3 => (centre_probability, p2, p3) = (centre_probability.min(0.03), 0.0, 0.0),
+            4 => (centre_probability, p2, p3) = (0.0, 1.0 - ((1.0 - p2) * 2.0).max(0.8), 0.0),
+            5 => (centre_probability, p3) = (centre_probability.min(0.03), 0.0),
+            6 => (centre_probability, p2, p3) = (0.0, 1.0 - ((1.0 - p2) * 2.0).max(0.5), 1.0 - ((1.0 - p3) * 2.0).max(0.85)),
+            _ => {},
+        }
+        let (p2, p3) = (p2.clamp(0.0, 1.0), p3.clamp(0.0, 1.0));
+
+        let centre_value = self.random.next_double();
+        let note_count = self.get_random_note_count(p2, p3, 0.0, 0.0, 0.0);
+        let add_to_centre = self.total_columns % 2 != 0 && note_count != 3 && centre_value > 1.0 - centre_probability;
+        (note_count, add_to_centre)
+    }
+
+    /// spinners, a single note or hold in a random column
+    fn end_time_object_pattern(&mut self, end_time: i32) -> Option<Pattern> {
+        let start_time = self.object.time;
+        let hold_end_time = Some(end_time).filter(|end_time| end_time - start_time >= 100);
+        let previous = self.previous;
+
+        let column = match self.total_columns {
+            8 if self.has_hitsound(HITSOUND_FINISH) && end_time - start_time < 1000 => 0,
+            8 => {
+                let column = self.get_random_column(None, None);
+                self.find_available_column(column, (None, None), false, None, &[previous])?
+            },
+            _ => self.get_random_column(Some(0), None),
+        };
+
+        let mut pattern = Pattern::default();
+        pattern.add(self.note(column, start_time, hold_end_time, self.object.hitsound));
+        Some(pattern)
+    }
+}
+
+/// the part of a slider the notes for it are generated from
+struct PathObject<'a> {
+    start_time: i32,
+    end_time: i32,
+    segment_duration: i32,
+    spans: i32,
+    node_hitsounds: &'a [u8],
+    convert_type: u16,
+}
+
+impl PathObject<'_> {
+    fn hitsound_at(&self, time: i32, object_hitsound: u8) -> u8 {
+        let index = if self.segment_duration == 0 { 0 } else { (time - self.start_time) / self.segment_duration };
+        usize::try_from(index).ok()
+            .and_then(|index| self.node_hitsounds.get(index))
+            .copied()
+            .unwrap_or(object_hitsound)
+    }
+}
+
+impl Generator<'_> {
+    fn add_path_note(&self, path: &PathObject, pattern: &mut Pattern, column: i32, start_time: i32, end_time: i32) {
+        if start_time == end_time {
+            pattern.add(self.note(column, start_time, None, path.hitsound_at(start_time, self.object.hitsound)));
+        } else {
+            pattern.add(self.note(column, start_time, Some(end_time), self.object.hitsound));
+        }
+    }
+
+    /// sliders, short ones become notes on every end, long ones holds mixed with notes
+    fn path_object_pattern(&mut self, path: &mut PathObject) -> Option<Pattern> {
+        let start_time = path.start_time;
+
+        if self.total_columns == 1 {
+            let mut pattern = Pattern::default();
+            self.add_path_note(path, &mut pattern, 0, start_time, path.end_time);
+            return Some(pattern);
+        }
+
+        if path.spans > 1 {
+            if path.segment_duration <= 90 {
+                return self.random_hold_notes(path, start_time, 1);
+            }
+            if path.segment_duration <= 120 {
+                path.convert_type |= PATTERN_FORCE_NOT_STACK;
+                return self.path_random_notes(path, start_time, path.spans + 1);
+            }
+            if path.segment_duration <= 160 {
+                return self.stair(path, start_time);
+            }
+            if path.segment_duration <= 200 && self.conversion_difficulty > 3.0 {
+                return self.random_multiple_notes(path, start_time);
+            }
+            if path.end_time - start_time >= 4000 {
+                return self.n_random_notes(path, start_time, 0.23, 0.0, 0.0);
+            }
+            if path.segment_duration > 400 && path.spans < self.total_columns - 1 - self.random_start {
+                return self.tiled_hold_notes(path, start_time);
+            }
+            return self.hold_and_normal_notes(path, start_time);
+        }
+
+        if path.segment_duration <= 110 {
+            if self.previous.column_count() < self.total_columns {
+                path.convert_type |= PATTERN_FORCE_NOT_STACK;
+            } else {
+                path.convert_type &= !PATTERN_FORCE_NOT_STACK;
+            }
+            return self.path_random_notes(path, start_time, if path.segment_duration < 80 { 1 } else { 2 });
+        }
+
+        let low_probability = path.convert_type & PATTERN_LOW_PROBABILITY != 0;
+        if self.conversion_difficulty > 6.5 {
+            if low_probability {
+                self.n_random_notes(path, start_time, 0.78, 0.3, 0.0)
+            } else {
+                self.n_random_notes(path, start_time, 0.85, 0.36, 0.03)
+            }
+        } else if self.conversion_difficulty > 4.0 {
+            if low_probability {
+                self.n_random_notes(path, start_time, 0.43, 0.08, 0.0)
+            } else {
+                self.n_random_notes(path, start_time, 0.56, 0.18, 0.0)
+            }
+        } else if self.conversion_difficulty > 2.5 {
+            if low_probability {
+                self.n_random_notes(path, start_time, 0.3, 0.0, 0.0)
+            } else {
+                self.n_random_notes(path, start_time, 0.37, 0.08, 0.0)
+            }
+        } else if low_probability {
+            self.n_random_notes(path, start_time, 0.17, 0.0, 0.0)
+        } else {
+            self.n_random_notes(path, start_time, 0.27, 0.0, 0.0)
+        }
+    }
+
+    fn random_hold_notes(&mut self, path: &PathObject, start_time: i32, note_count: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let usable_columns = self.total_columns - self.random_start - previous.column_count();
+        let mut column = self.get_random_column(None, None);
+        for _ in 0..usable_columns.min(note_count) {
+            column = self.find_available_column(column, (None, None), false, None, &[&pattern, previous])?;
+            self.add_path_note(path, &mut pattern, column, start_time, path.end_time);
+        }
+        // kept apart from the loop above so the random numbers are drawn in the same order
+        for _ in 0..note_count - usable_columns {
+            column = self.find_available_column(column, (None, None), false, None, &[&pattern])?;
+            self.add_path_note(path, &mut pattern, column, start_time, path.end_time);
+        }
+        Some(pattern)
+    }
+
+    fn path_random_notes(&mut self, path: &PathObject, mut start_time: i32, note_count: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let mut column = self.get_column(self.object.x, true);
+        if path.convert_type & PATTERN_FORCE_NOT_STACK != 0 && previous.column_count() < self.total_columns {
+            column = self.find_available_column(column, (None, None), false, None, &[previous])?;
+        }
+
+        let mut last_column = column;
+        for _ in 0..note_count {
+            self.add_path_note(path, &mut pattern, column, start_time, start_time);
+            column = self.find_available_column(column, (None, None), false, Some(last_column), &[])?;
+            last_column = column;
+            start_time += path.segment_duration;
+        }
+        Some(pattern)
+    }
+
+    fn stair(&mut self, path: &PathObject, mut start_time: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+
+        let mut column = self.get_column(self.object.x, true);
+        let mut increasing = self.random.next_double() > 0.5;
+        for _ in 0..=path.spans {
+            self.add_path_note(path, &mut pattern, column, start_time, start_time);
+            start_time += path.segment_duration;
+
+            // turn around at the edges of the stage
+            if increasing {
+                if column >= self.total_columns - 1 {
+                    increasing = false;
+                    column -= 1;
+                } else {
+                    column += 1;
+                }
+            } else if column <= self.random_start {
+                increasing = true;
+                column += 1;
+            } else {
+                column -= 1;
+            }
+        }
+        Some(pattern)
+    }
+
+    fn random_multiple_notes(&mut self, path: &PathObject, mut start_time: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+
+        let legacy = (4..=8).contains(&self.total_columns);
+        let interval = self.random.next_in(1, self.total_columns - legacy as i32);
+
+        let mut column = self.get_column(self.object.x, true);
+        for _ in 0..=path.spans {
+            self.add_path_note(path, &mut pattern, column, start_time, start_time);
+
+            column += interval;
+            if column >= self.total_columns - self.random_start {
+                column = column - self.total_columns - self.random_start + legacy as i32;
+            }
+            column += self.random_start;
+
+            // 2k doesn't get a double on every end
+            if self.total_columns > 2 {
+                self.add_path_note(path, &mut pattern, column, start_time, start_time);
+            }
+
+            column = self.get_random_column(None, None);
+            start_time += path.segment_duration;
+        }
+        Some(pattern)
+    }
+
+    fn n_random_notes(&mut self, path: &PathObject, start_time: i32, mut p2: f64, mut p3: f64, mut p4: f64) -> Option<Pattern> {
+        match self.total_columns {
+            2 => (p2, p3, p4) = (0.0, 0.0, 0.0),
+            3 => (p2, p3, p4) = (p2.min(0.1), 0.0, 0.0),
+            4 => (p2, p3, p4) = (p2.min(0.3), p3.min(0.04), 0.0),
+            5 => (p2, p3, p4) = (p2.min(0.34), p3.min(0.1), p4.min(0.03)),
+            _ => {},
+        }
+
+        let is_double = |hitsound: u8| hitsound & (HITSOUND_CLAP | HITSOUND_FINISH) != 0;
+        if path.convert_type & PATTERN_LOW_PROBABILITY == 0
+            && (is_double(self.object.hitsound) || is_double(path.hitsound_at(path.start_time, self.object.hitsound)))
+        {
+            p2 = 1.0;
+        }
+
+        let note_count = self.get_random_note_count(p2, p3, p4, 0.0, 0.0);
+        self.random_hold_notes(path, start_time, note_count)
+    }
+
+    fn tiled_hold_notes(&mut self, path: &PathObject, mut start_time: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let column_repeat = path.spans.min(self.total_columns);
+        // not always the slider's end time because of the rounding
+        let end_time = start_time + path.segment_duration * path.spans;
+
+        let mut column = self.get_column(self.object.x, true);
+        if path.convert_type & PATTERN_FORCE_NOT_STACK != 0 && previous.column_count() < self.total_columns {
+            column = self.find_available_column(column, (None, None), false, None, &[previous])?;
+        }
+
+        for _ in 0..column_repeat {
+            column = self.find_available_column(column, (None, None), false, None, &[&pattern])?;
+            self.add_path_note(path, &mut pattern, column, start_time, end_time);
+            start_time += path.segment_duration;
+        }
+        Some(pattern)
+    }
+
+    fn hold_and_normal_notes(&mut self, path: &PathObject, mut start_time: i32) -> Option<Pattern> {
+        let mut pattern = Pattern::default();
+        let previous = self.previous;
+
+        let mut hold_column = self.get_column(self.object.x, true);
+        if path.convert_type & PATTERN_FORCE_NOT_STACK != 0 && previous.column_count() < self.total_columns {
+            hold_column = self.find_available_column(hold_column, (None, None), false, None, &[previous])?;
+        }
+        self.add_path_note(path, &mut pattern, hold_column, start_time, path.end_time);
+
+        let mut column = self.get_random_column(None, None);
+        let note_count = if self.conversion_difficulty > 6.5 {
+            self.get_random_note_count(0.63, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 4.0 {
+            self.get_random_note_count(if self.total_columns < 6 { 0.12 } else { 0.45 }, 0.0, 0.0, 0.0, 0.0)
+        } else if self.conversion_difficulty > 2.5 {
+            self.get_random_note_count(if self.total_columns < 6 { 0.0 } else { 0.24 }, 0.0, 0.0, 0.0, 0.0)
+        } else {
+            0
+        };
+        let note_count = note_count.min(self.total_columns - 1);
+
+        let ignore_head = path.hitsound_at(start_time, self.object.hitsound) & (HITSOUND_WHISTLE | HITSOUND_FINISH | HITSOUND_CLAP) == 0;
+
+        let mut row_pattern = Pattern::default();
+        for _ in 0..=path.spans {
+            if !(ignore_head && start_time == path.start_time) {
+                for _ in 0..note_count {
+                    column = self.find_available_column(column, (None, None), false, Some(hold_column), &[&row_pattern])?;
+                    self.add_path_note(path, &mut row_pattern, column, start_time, start_time);
+                }
+            }
+            pattern.append(&mut row_pattern);
+            start_time += path.segment_duration;
+        }
+        Some(pattern)
+    }
+}
+
+/// converts the objects of a standard or catch beatmap, sorted by time, into `key_count` key mania notes
+/// the way osu!stable does when a key mod is on. notes are returned in the order they're generated
+pub(super) fn convert_to_mania(
+    objects: &[ConvertObject],
+    timing_points: &[ConvertTimingPoint],
+    total_break_time: i32,
+    difficulty: &ConvertDifficulty,
+    key_count: u8,
+) -> Vec<ConvertedNote> {
+    let control_points = ControlPoints::new(timing_points);
+    let total_columns = key_count as i32;
+    // 7k+1 keeps its first column for special notes
+    let random_start = if total_columns == 8 { 1 } else { 0 };
+
+    let seed = (difficulty.hp + difficulty.cs).round_ties_even() as i32 * 20
+        + (difficulty.od as f64 * 41.2) as i32
+        + difficulty.ar.round_ties_even() as i32;
+    let mut random = LegacyRandom::new(seed);
+
+    let first_time = objects.first().map_or(0, |object| object.time);
+    let last_time = objects.last().map_or(0, |object| object.time);
+    let drain_time = match ((last_time as f64 - first_time as f64 - total_break_time as f64) / 1000.0) as i32 {
+        0 => 10000,
+        drain_time => drain_time,
+    };
+    let conversion_difficulty = (((difficulty.hp + difficulty.ar.clamp(4.0, 7.0)) as f64 / 1.5
+        + objects.len() as f64 / drain_time as f64 * 9.0) / 38.0 * 5.0 / 1.15)
+        .min(12.0);
+
+    let mut notes: Vec<ConvertedNote> = Vec::with_capacity(objects.len());
+    let mut last_pattern = Pattern::default();
+    let mut last_time = 0.0;
+    let mut last_position = (0.0, 0.0);
+    let mut last_stair = PATTERN_STAIR;
+    let mut note_times: VecDeque<f64> = VecDeque::with_capacity(DENSITY_NOTE_COUNT);
+    let mut density = i32::MAX as f64;
+
+    let mut compute_density = |time: f64, density: &mut f64| {
+        if note_times.len() == DENSITY_NOTE_COUNT {
+            note_times.pop_front();
+        }
+        note_times.push_back(time);
+        if note_times.len() >= 2 {
+            *density = (note_times[note_times.len() - 1] - note_times[0]) / note_times.len() as f64;
+        }
+    };
+
+    for (index, object) in objects.iter().enumerate() {
+        let mut generator = Generator {
+            random: &mut random,
+            object,
+            index,
+            previous: &last_pattern,
+            total_columns,
+            random_start,
+            conversion_difficulty,
+        };
+
+        let patterns = match &object.kind {
+            ConvertObjectKind::Slider { spans, length, node_hitsounds } => {
+                let spans = (*spans).max(1);
+                let (bpm_multiplier, kiai) = control_points.effect_at(object.time);
+                let beat_length = control_points.beat_length_at(object.time) * bpm_multiplier;
+                let end_time = (object.time as f64 + length * beat_length * spans as f64 * 0.01 / difficulty.slider_multiplier).floor() as i32;
+                let mut path = PathObject {
+                    start_time: object.time,
+                    end_time,
+                    segment_duration: (end_time - object.time) / spans,
+                    spans,
+                    node_hitsounds,
+                    convert_type: if kiai { PATTERN_NONE } else { PATTERN_LOW_PROBABILITY },
+                };
+
+                for span in 0..=spans {
+                    let time = object.time as f64 + (path.segment_duration * span) as f64;
+                    last_time = time;
+                    last_position = (object.x, object.y);
+                    compute_density(time, &mut density);
+                }
+
+                // the notes ending with the slider are what the next object is generated around
+                generator.path_object_pattern(&mut path).map(|pattern| {
+                    if pattern.notes.len() == 1 {
+                        return vec![pattern];
+                    }
+                    let (mut intermediate, mut ending) = (Pattern::default(), Pattern::default());
+                    for note in pattern.notes {
+                        if note.end_time.unwrap_or(note.time) == path.end_time {
+                            ending.add(note);
+                        } else {
+                            intermediate.add(note);
+                        }
+                    }
+                    vec![intermediate, ending]
+                })
+            },
+            ConvertObjectKind::Spinner { end_time } => {
+                let end_time = (*end_time).max(object.time);
+                let pattern = generator.end_time_object_pattern(end_time);
+                last_time = end_time as f64;
+                last_position = (256.0, 192.0);
+                compute_density(end_time as f64, &mut density);
+
+                // spinners don't change what the next object is generated around
+                notes.extend(pattern.into_iter().flat_map(|pattern| pattern.notes));
+                continue;
+            },
+            ConvertObjectKind::Circle => {
+                compute_density(object.time as f64, &mut density);
+                let (pattern, stair_type) = generator.hit_object_pattern((last_time, last_position), density, last_stair, &control_points);
+                last_stair = stair_type;
+                last_time = object.time as f64;
+                last_position = (object.x, object.y);
+                pattern.map(|pattern| vec![pattern])
+            },
+        };
+
+        for pattern in patterns.into_iter().flatten() {
+            notes.extend_from_slice(&pattern.notes);
+            last_pattern = pattern;
+        }
+    }
+
+    notes.retain(|note| (0..total_columns).contains(&note.column));
+    notes
+}
+
@@ -0,0 +1,75 @@
+use crate::models;
+use crate::models::common::{ChartDefaults, GameMode};
+use crate::models::mapset::{Asset, Mapset};
+use crate::parsers::osu::from_osu;
+use crate::utils::zip::read_zip;
+use crate::errors;
+
+/// paths in charts can use either slash and have `./` in front
+#[inline]
+pub(crate) fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+/// the song, background and samples of a chart
+fn referenced_paths(chart: &models::chart::Chart) -> Vec<String> {
+    let mut paths = vec![chart.chartinfo.song_path.clone(), chart.chartinfo.bg_path.clone()];
+    if let Some(soundbank) = &chart.soundbank {
+        paths.extend(soundbank.audio_tracks.iter().cloned());
+        paths.extend(soundbank.get_sample_paths());
+    }
+    paths.into_iter()
+        .map(|path| normalize_path(&path))
+        // parsers fill in missing paths with placeholders that aren't files
+        .filter(|path| !path.is_empty() && path != ChartDefaults::SONG_PATH && path != ChartDefaults::BG_PATH)
+        .collect()
+}
+
+/// every file the charts reference once, `exists` tells if it's next to them
+pub(crate) fn list_assets(charts: &[models::chart::Chart], exists: impl Fn(&str) -> bool) -> Vec<Asset> {
+    let mut assets: Vec<Asset> = Vec::new();
+    for path in charts.iter().flat_map(referenced_paths) {
+        if assets.iter().any(|asset| asset.path.eq_ignore_ascii_case(&path)) {
+            continue;
+        }
+        let is_missing = !exists(&path);
+        assets.push(Asset { path, is_missing });
+    }
+    assets
+}
+
+/// parses every chart in a mapset archive whose name ends with `extension` and lists the files they reference,
+/// files are matched without case since that's how Windows finds them
+pub(crate) fn parse_mapset(
+    raw_archive: &[u8],
+    extension: &str,
+    archive_name: &str,
+    parse: impl Fn(&str) -> Result<models::chart::Chart, Box<dyn std::error::Error>>,
+) -> Result<Mapset, Box<dyn std::error::Error>> {
+    if raw_archive.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+    let entries = read_zip(raw_archive)
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid {archive_name} archive: {e}")))?;
+
+    let mut charts = Vec::new();
+    for entry in entries.iter().filter(|entry| entry.name.to_lowercase().ends_with(extension)) {
+        let raw_chart = entry.contents()
+            .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("Invalid {archive_name} archive: {e}")))?;
+        let chart = parse(&String::from_utf8_lossy(&raw_chart))
+            .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("{}: {e}", entry.name)))?;
+        charts.push(chart);
+    }
+    if charts.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart(format!("No {extension} files in {archive_name} archive"))));
+    }
+
+    let assets = list_assets(&charts, |path| entries.iter().any(|entry| entry.name.eq_ignore_ascii_case(path)));
+    Ok(Mapset { charts, assets })
+}
+
+/// parses every difficulty of an osu! mapset (.osz) and lists the files they reference
+pub(crate) fn from_osz(raw_osz: &[u8]) -> Result<Mapset, Box<dyn std::error::Error>> {
+    parse_mapset(raw_osz, ".osu", ".osz", from_osu)
+}
@@ -0,0 +1,8 @@
+use crate::models::mapset::Mapset;
+use crate::parsers::osz::parse_mapset;
+use crate::parsers::quaver::from_qua;
+
+/// parses every difficulty of a Quaver mapset (.qp) and lists the files they reference
+pub(crate) fn from_qp(raw_qp: &[u8]) -> Result<Mapset, Box<dyn std::error::Error>> {
+    parse_mapset(raw_qp, ".qua", ".qp", from_qua)
+}
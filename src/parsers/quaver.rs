@@ -157,6 +157,7 @@ fn parse_sv(raw: &str) -> Result<TimingPoint, Box<dyn std::error::Error>> {
     Ok((time, sv_value, TimingChangeType::Sv))
 }
 
+#[allow(clippy::redundant_field_names)]
 fn parse_soundeffect(raw: &str) -> Result<SoundEffect, Box<dyn std::error::Error>> {
     let mut time = 0f32;
     let mut sample_index = 1;
@@ -191,9 +192,11 @@ fn parse_soundeffect(raw: &str) -> Result<SoundEffect, Box<dyn std::error::Error
         }
     }
 
-    Ok(SoundEffect { time: time as i32, volume: volume, sample: sample_index })
+    // samples are counted from 1 in .qua files
+    Ok(SoundEffect { time: time as i32, volume: volume, sample: sample_index.saturating_sub(1) })
 }
 
+#[allow(clippy::manual_strip)]
 fn parse_keysound(raw: &str) -> Result<HitSample, Box<dyn std::error::Error>> {
     let mut sample_index = 1;
     let mut volume = 100;
@@ -225,9 +228,11 @@ fn parse_keysound(raw: &str) -> Result<HitSample, Box<dyn std::error::Error>> {
             _ => {},
         }
     }
-    Ok((sample_index, volume))
+    // samples are counted from 1 in .qua files
+    Ok((sample_index.saturating_sub(1), volume))
 }
 
+#[allow(clippy::unnecessary_unwrap)]
 fn parse_hitobject(raw: &str) -> Result<HitObject, Box<dyn std::error::Error>> {
     let mut time = 0f32;
     let mut lane: usize = 1;
@@ -366,6 +371,7 @@ fn process_soundeffects(raw: &str, soundbank: &mut SoundBank) -> Result<(), Box<
     Ok(())
 }
 
+#[allow(clippy::manual_ignore_case_cmp)]
 fn process_samples(raw: &str, soundbank: &mut SoundBank) -> Result<(), Box<dyn std::error::Error>> {
     let trimmed_raw = raw.trim();
 
@@ -383,6 +389,7 @@ fn process_samples(raw: &str, soundbank: &mut SoundBank) -> Result<(), Box<dyn s
     Ok(())
 }
 
+#[allow(clippy::ptr_arg, clippy::unnecessary_cast)]
 fn process_notes(hitobjects: &mut models::hitobjects::HitObjects,
     chartinfo: &mut models::chartinfo::ChartInfo,
     bpms_times: &Vec<i32>,
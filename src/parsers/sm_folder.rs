@@ -0,0 +1,173 @@
+use crate::models::common::GameMode;
+use crate::models::mapset::{AssetFile, FailedSong, Mapset, Pack};
+use crate::parsers::osz::{list_assets, normalize_path};
+use crate::parsers::stepmania::{from_sm_all, from_ssc_all};
+use crate::errors;
+
+/// the chart files StepMania loads a song from, the first one it finds is used
+const CHART_EXTENSIONS: [&str; 2] = [".ssc", ".sm"];
+const AUDIO_EXTENSIONS: [&str; 4] = [".ogg", ".mp3", ".wav", ".flac"];
+const IMAGE_EXTENSIONS: [&str; 5] = [".png", ".jpg", ".jpeg", ".bmp", ".gif"];
+
+#[inline]
+fn has_extension(path: &str, extensions: &[&str]) -> bool {
+    let path = path.to_lowercase();
+    extensions.iter().any(|extension| path.ends_with(extension))
+}
+
+/// the actual name of a file, files are matched without case since that's how Windows finds them
+#[inline]
+fn find_file<'a>(files: &'a [String], path: &str) -> Option<&'a String> {
+    let path = normalize_path(path);
+    files.iter().find(|file| file.eq_ignore_ascii_case(&path))
+}
+
+/// the chart StepMania would load from a song folder, only files right in the folder count
+fn find_chart(files: &[String]) -> Option<(&String, &'static str)> {
+    CHART_EXTENSIONS.iter().find_map(|extension| {
+        files.iter()
+            .filter(|file| !file.contains('/') && has_extension(file, &[extension]))
+            .min()
+            .map(|file| (file, *extension))
+    })
+}
+
+/// the file a path points to, or the first file `fallback` accepts when it doesn't point to one
+fn resolve_path(files: &[String], path: &str, fallback: impl Fn(&str) -> bool) -> Option<String> {
+    find_file(files, path)
+        .or_else(|| {
+            let mut candidates: Vec<&String> = files.iter().filter(|file| fallback(file)).collect();
+            candidates.sort();
+            candidates.first().copied()
+        })
+        .cloned()
+}
+
+/// like StepMania, a background that can't be found is looked for as an image ending in `bg` or `background`
+#[inline]
+fn is_background(path: &str) -> bool {
+    if !has_extension(path, &IMAGE_EXTENSIONS) {
+        return false;
+    }
+    let stem = path.rsplit_once('.').map_or(path, |(stem, _)| stem).to_lowercase();
+    stem.ends_with("bg") || stem.ends_with("background")
+}
+
+/// parses the chart of a song folder, `files` are every path in it and `read` gets the contents of one
+fn parse_song(
+    files: &[String],
+    read: impl Fn(&str) -> Result<Vec<u8>, Box<dyn std::error::Error>>,
+) -> Result<Mapset, Box<dyn std::error::Error>> {
+    let Some((chart_path, extension)) = find_chart(files) else {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No .ssc or .sm file in song folder".to_string())));
+    };
+    let raw_chart = read(chart_path)?;
+    let raw_chart = String::from_utf8_lossy(&raw_chart);
+    let mut charts = if extension == ".ssc" { from_ssc_all(&raw_chart) } else { from_sm_all(&raw_chart) }
+        .map_err(|e| errors::ParseError::<GameMode>::InvalidChart(format!("{chart_path}: {e}")))?;
+
+    // fallbacks are only looked for right in the song folder
+    for chart in charts.iter_mut() {
+        let chartinfo = &mut chart.chartinfo;
+        if let Some(song_path) = resolve_path(files, &chartinfo.song_path, |file| !file.contains('/') && has_extension(file, &AUDIO_EXTENSIONS)) {
+            chartinfo.song_path = song_path;
+        }
+        if let Some(bg_path) = resolve_path(files, &chartinfo.bg_path, |file| !file.contains('/') && is_background(file)) {
+            chartinfo.bg_path = bg_path;
+        }
+    }
+
+    let assets = list_assets(&charts, |path| find_file(files, path).is_some());
+    Ok(Mapset { charts, assets })
+}
+
+/// parses every song folder of a pack, folders without an .ssc or .sm file are skipped
+/// and songs that can't be parsed are listed with why instead of stopping the rest
+fn parse_pack(
+    files: &[String],
+    read: impl Fn(&str) -> Result<Vec<u8>, Box<dyn std::error::Error>>,
+) -> Result<Pack, Box<dyn std::error::Error>> {
+    let mut folders: Vec<&str> = files.iter().filter_map(|file| file.split_once('/').map(|(folder, _)| folder)).collect();
+    folders.sort();
+    folders.dedup();
+
+    let mut mapsets = Vec::new();
+    let mut failed_songs = Vec::new();
+    for folder in folders {
+        let prefix = format!("{folder}/");
+        let song_files: Vec<String> = files.iter().filter_map(|file| file.strip_prefix(&prefix)).map(str::to_string).collect();
+        if find_chart(&song_files).is_none() {
+            continue;
+        }
+        match parse_song(&song_files, |path| read(&format!("{prefix}{path}"))) {
+            Ok(mapset) => mapsets.push(mapset),
+            Err(e) => failed_songs.push(FailedSong { folder: folder.to_string(), error: e.to_string() }),
+        }
+    }
+    if mapsets.is_empty() && failed_songs.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No song folders with .ssc or .sm files in pack".to_string())));
+    }
+
+    Ok(Pack { mapsets, failed_songs })
+}
+
+/// reads a file that was handed over with the rest of the folder
+fn read_asset_file(files: &[AssetFile], path: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    files.iter()
+        .find(|file| normalize_path(&file.path) == path)
+        .map(|file| file.data.clone())
+        .ok_or_else(|| format!("{path} not found").into())
+}
+
+/// parses the files of a StepMania song folder, paths are relative to the folder
+pub(crate) fn from_sm_folder_files(files: &[AssetFile]) -> Result<Mapset, Box<dyn std::error::Error>> {
+    let paths: Vec<String> = files.iter().map(|file| normalize_path(&file.path)).collect();
+    parse_song(&paths, |path| read_asset_file(files, path))
+}
+
+/// parses the files of a StepMania pack, paths start with the song folder they're in
+pub(crate) fn from_sm_pack_files(files: &[AssetFile]) -> Result<Pack, Box<dyn std::error::Error>> {
+    let paths: Vec<String> = files.iter().map(|file| normalize_path(&file.path)).collect();
+    parse_pack(&paths, |path| read_asset_file(files, path))
+}
+
+/// every file under a folder, relative to it and with `/` between folders,
+/// symlinked folders aren't gone into so a link back up can't loop forever
+#[cfg(not(target_arch = "wasm32"))]
+fn list_files(root: &std::path::Path) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        for entry in std::fs::read_dir(&folder)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                folders.push(path);
+            } else if file_type.is_symlink() && path.is_dir() {
+                continue;
+            } else if let Ok(relative_path) = path.strip_prefix(root) {
+                let components: Vec<String> = relative_path.components()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .collect();
+                files.push(components.join("/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// parses a StepMania song folder on disk, only the chart gets read
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn from_sm_folder(path: &std::path::Path) -> Result<Mapset, Box<dyn std::error::Error>> {
+    let files = list_files(path)?;
+    parse_song(&files, |file| Ok(std::fs::read(path.join(file))?))
+}
+
+/// parses every song folder of a StepMania pack on disk
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn from_sm_pack(path: &std::path::Path) -> Result<Pack, Box<dyn std::error::Error>> {
+    let files = list_files(path)?;
+    parse_pack(&files, |file| Ok(std::fs::read(path.join(file))?))
+}
@@ -4,9 +4,10 @@ use crate::models::common::{
     TimingChangeType,
     GameMode,
     Key,
-    KeyType
+    KeyType,
 };
 use crate::models::sound::KeySoundRow;
+use crate::models::timing_points::{TimeSignature, TimingChange};
 use crate::utils::string::{
     remove_comments,
     StrDefaultExtension,
@@ -14,21 +15,28 @@ use crate::utils::string::{
     trim_split_iter,
 };
 use crate::utils::time::{
+    BpmsAndStops,
     to_millis,
     merge_bpm_and_stops,
 };
-use crate::utils::rhythm::{
-    calculate_time_from_beat,
-    calculate_beat_from_time,
-};
+use crate::utils::rhythm::calculate_time_from_beat;
 use crate::errors;
 
-type BpmsAndStops = (Vec<f32>, Vec<f32>, Vec<TimingChangeType>);
+const DEFAULT_ROWS_PER_BEAT: f32 = 48.0;
+
+/// .sma files can also position things in rows instead of beats, those have an 'r' suffix
+#[inline]
+fn parse_beat(raw: &str, rows_per_beat: f32) -> Option<f32> {
+    let raw = raw.trim();
+    match raw.strip_suffix(['r', 'R']) {
+        Some(rows) => rows.trim().parse::<f32>().ok().map(|rows| rows / rows_per_beat),
+        None => raw.parse::<f32>().ok(),
+    }
+}
 
-pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
-    match raw {
-        ChartDefaults::RAW_BPMS => return (vec![0.0], vec![*ChartDefaults::BPM]),
-        _ => {}
+pub fn parse_bpms(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<f32>) {
+    if raw == ChartDefaults::RAW_BPMS {
+        return (vec![0.0], vec![*ChartDefaults::BPM]);
     }
 
     let mut beats = Vec::new();
@@ -39,7 +47,7 @@ pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
             let mut beat_bpm = beat_bpm_str.trim().split('=');
 
             if let (Some(beat_str), Some(bpm_str)) = (beat_bpm.next(), beat_bpm.next()) {
-                if let (Ok(beat), Ok(bpm)) = (beat_str.parse::<f32>(), bpm_str.parse::<f32>()) {
+                if let (Some(beat), Ok(bpm)) = (parse_beat(beat_str, rows_per_beat), bpm_str.parse::<f32>()) {
                     debug_assert_ne!(bpm, 0.0);
                     return Some((beat, bpm));
                 }
@@ -54,10 +62,9 @@ pub fn parse_bpms(raw: &str) -> (Vec<f32>, Vec<f32>) {
     (beats, bpms)
 }
 
-pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
-    match raw {
-        ChartDefaults::RAW_STOPS => return (vec![], vec![]),
-        _ => {}
+pub fn parse_stops(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<f32>) {
+    if raw == ChartDefaults::RAW_STOPS {
+        return (vec![], vec![]);
     }
 
     let mut beats = Vec::new();
@@ -68,7 +75,7 @@ pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
             let mut beat_bpm = beat_bpm_str.trim().split('=');
 
             if let (Some(beat_str), Some(duration_str)) = (beat_bpm.next(), beat_bpm.next()) {
-                if let (Ok(beat), Ok(duration)) = (beat_str.parse::<f32>(), duration_str.parse::<f32>()) {
+                if let (Some(beat), Ok(duration)) = (parse_beat(beat_str, rows_per_beat), duration_str.parse::<f32>()) {
                     return Some((beat, to_millis(duration)));
                 }
             }
@@ -82,6 +89,116 @@ pub fn parse_stops(raw: &str) -> (Vec<f32>, Vec<f32>) {
     (beats, durations)
 }
 
+/// .sma #SPEED, beat=ratio=delay where the delay can be in beats or seconds (with an 's' suffix),
+/// .ssc #SPEEDS, beat=ratio=duration=unit and #SCROLLS, beat=ratio.
+/// only the ratio is kept
+pub fn parse_speeds(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<f32>) {
+    let mut beats = Vec::new();
+    let mut ratios = Vec::new();
+
+    raw.split(',')
+        .filter_map(|speed_str| {
+            let mut speed = speed_str.trim().split('=');
+
+            if let (Some(beat_str), Some(ratio_str)) = (speed.next(), speed.next()) {
+                if let (Some(beat), Ok(ratio)) = (parse_beat(beat_str, rows_per_beat), ratio_str.trim().parse::<f32>()) {
+                    return Some((beat, ratio));
+                }
+            }
+            None
+        })
+        .for_each(|(beat, ratio)| {
+            beats.push(beat);
+            ratios.push(ratio);
+        });
+
+    (beats, ratios)
+}
+
+/// #WARPS and #FAKES, beat=length where the length is in beats
+pub fn parse_warps(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<f32>) {
+    let mut beats = Vec::new();
+    let mut lengths = Vec::new();
+
+    raw.split(',')
+        .filter_map(|warp_str| {
+            let mut warp = warp_str.trim().split('=');
+
+            if let (Some(beat_str), Some(length_str)) = (warp.next(), warp.next()) {
+                if let (Some(beat), Ok(length)) = (parse_beat(beat_str, rows_per_beat), length_str.trim().parse::<f32>()) {
+                    if length > 0.0 {
+                        return Some((beat, length));
+                    }
+                }
+            }
+            None
+        })
+        .for_each(|(beat, length)| {
+            beats.push(beat);
+            lengths.push(length);
+        });
+
+    (beats, lengths)
+}
+
+/// .sma #BEATSPERMEASURE, beat=beats and .ssc #TIMESIGNATURES, beat=numerator=denominator
+pub fn parse_beats_per_measure(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<TimeSignature>) {
+    let mut beats = Vec::new();
+    let mut time_signatures = Vec::new();
+
+    raw.split(',')
+        .filter_map(|signature_str| {
+            let mut signature = signature_str.trim().split('=');
+
+            if let (Some(beat_str), Some(numerator_str)) = (signature.next(), signature.next()) {
+                let beat = parse_beat(beat_str, rows_per_beat)?;
+                let time_signature = match signature.next() {
+                    Some(denominator_str) => {
+                        let (Ok(numerator), Ok(denominator)) = (numerator_str.trim().parse::<u32>(), denominator_str.trim().parse::<u32>()) else {
+                            return None;
+                        };
+                        TimeSignature::new(numerator, denominator)
+                    },
+                    None => TimeSignature::from_beats(numerator_str.trim().parse::<f32>().ok()?),
+                };
+                if time_signature.numerator > 0 && time_signature.denominator > 0 {
+                    return Some((beat, time_signature));
+                }
+            }
+            None
+        })
+        .for_each(|(beat, time_signature)| {
+            beats.push(beat);
+            time_signatures.push(time_signature);
+        });
+
+    (beats, time_signatures)
+}
+
+/// #TICKCOUNTS, beat=ticks
+pub fn parse_tick_counts(raw: &str, rows_per_beat: f32) -> (Vec<f32>, Vec<f32>) {
+    let mut beats = Vec::new();
+    let mut tick_counts = Vec::new();
+
+    raw.split(',')
+        .filter_map(|tick_count_str| {
+            let mut tick_count = tick_count_str.trim().split('=');
+
+            if let (Some(beat_str), Some(ticks_str)) = (tick_count.next(), tick_count.next()) {
+                if let (Some(beat), Ok(ticks)) = (parse_beat(beat_str, rows_per_beat), ticks_str.trim().parse::<f32>()) {
+                    return Some((beat, ticks));
+                }
+            }
+            None
+        })
+        .for_each(|(beat, ticks)| {
+            beats.push(beat);
+            tick_counts.push(ticks);
+        });
+
+    (beats, tick_counts)
+}
+
 
 pub fn parse_keys_in_row(row: &str) -> Vec<Key> {
 
@@ -101,9 +218,10 @@ pub(crate) fn get_sm_note_type(note: char) -> Key {
         '1' => Key::normal(),
         '2' => Key::slider_start(None),
         '3' => Key::slider_end(),
-        '4' => Key::slider_start(None),
+        '4' => Key::roll(None),
         'M' => Key::mine(),
         'F' => Key::fake(),
+        'L' => Key::lift(),
         _ => Key::unknown(),
     }
 }
@@ -129,102 +247,101 @@ where
     }
 }
 
-fn process_timing_points(bpms_and_stops: &BpmsAndStops, bpms_only: (Vec<f32>, Vec<f32>), start_time: i32) -> models::timing_points::TimingPoints {
-    use models::timing_points::{TimingPoints, TimingChange};
-    let mut timing_points = TimingPoints::with_capacity(64);
+fn process_timing_points(timing: &SmTimingData, bpms_and_stops: &BpmsAndStops, start_time: i32) -> models::timing_points::TimingPoints {
+    use models::timing_points::TimingPoints;
 
     let (beats, bpms_and_durations, change_types) = bpms_and_stops;
-    let (_bpms_only_beats, bpms_only_values) = bpms_only;
 
+    let mut changes: Vec<(f32, TimingChange)> = Vec::with_capacity(beats.len());
     for i in 0..beats.len() {
-        let current_beat = beats[i];
-        let bpm_or_duration = bpms_and_durations[i];
+        changes.push((beats[i], TimingChange::new(change_types[i], bpms_and_durations[i])));
+    }
+    changes.extend(timing.other_changes());
+    changes.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .expect("Comparison between NaN values when sorting timing changes")
+    });
+
+    let mut timing_points = TimingPoints::with_capacity(changes.len());
 
-        let insert_time = calculate_time_from_beat(
+    for (current_beat, change) in changes {
+        let mut insert_time = calculate_time_from_beat(
             current_beat,
             start_time,
             (beats, bpms_and_durations, change_types)
         );
-        
-        match change_types[i] {
-            TimingChangeType::Bpm => {
-                timing_points.add(
-                    insert_time,
-                    current_beat,
-                    TimingChange {
-                        change_type: TimingChangeType::Bpm,
-                        value: bpm_or_duration,
-                    }
-                );
-            },
-            TimingChangeType::Stop => {
-                // TODO: add stop point as is and we unwrap it inside the writer..
-                timing_points.add(
-                    insert_time,
-                    current_beat,
-                    TimingChange {
-                        change_type: TimingChangeType::Sv,
-                        value: 0.0,
-                    }
-                );
-
-                let stop_end_time = insert_time as f32 + bpm_or_duration;
-
-                // TODO: really hacky and ugly way, this is a temp solution, use TimeingPointsTImeline later
-                let bpm_times_vec = timing_points.bpm_changes_zipped()
-                .map(|(t, _, _)| *t)
-                .collect::<Vec<i32>>();
-                let bpm_times: &[i32] = &bpm_times_vec;  // Now you have a slice
-                
-                let stop_end_beat = calculate_beat_from_time(
-                    stop_end_time as i32,
-                    start_time,
-                    (&bpm_times, &bpms_only_values)
-                );
-
-                let stop_time = insert_time + bpm_or_duration as i32;
-
-                timing_points.add(
-                    stop_time,
-                    stop_end_beat,
-                    TimingChange {
-                        change_type: TimingChangeType::Sv,
-                        value: 1.0,
-                    }
-                );
-            },
-            _ => {}
+        // the time of a beat already includes the delay on it, but the delay itself starts before that
+        if change.change_type == TimingChangeType::Delay {
+            insert_time -= change.value as i32;
         }
+
+        // stops and delays keep their duration (ms) as the value, writers unwrap them as needed
+        timing_points.add(insert_time, current_beat, change);
     }
 
     timing_points
 }
 
-fn process_notes(raw_note_data: &str, chartinfo: &mut models::chartinfo::ChartInfo, bpms_and_stops: &BpmsAndStops) -> models::hitobjects::HitObjects {
+#[inline]
+fn parse_sm_notes_fields(raw: &str) -> (models::chartinfo::StepsInfo, &str) {
+    use models::chartinfo::StepsInfo;
+
+    let separated_note_data: Vec<&str> = trim_split_iter(raw.splitn(6, ':'), false);
+    if separated_note_data.len() < 6 {
+        return (StepsInfo::empty(), separated_note_data.last().copied().unwrap_or(""));
+    }
+
+    let steps_info = StepsInfo::new(
+        separated_note_data[0].to_string(),
+        separated_note_data[1].to_string(),
+        separated_note_data[2].to_string(),
+        separated_note_data[3].or_default_empty_as::<u32>(1),
+        parse_radar_values(separated_note_data[4]),
+    );
+
+    (steps_info, separated_note_data[5])
+}
+
+#[inline]
+fn parse_radar_values(raw: &str) -> Vec<f32> {
+    raw.split(',')
+        .filter_map(|value| value.trim().parse::<f32>().ok())
+        .collect()
+}
+
+fn process_notes(raw_notes: &str,
+    chartinfo: &mut models::chartinfo::ChartInfo,
+    timing: &SmTimingData,
+    bpms_and_stops: &BpmsAndStops) -> models::hitobjects::HitObjects {
     use crate::models::hitobjects::HitObjects;
 
-    if raw_note_data.contains("No Note Data") { return HitObjects::with_capacity(2048) }
+    if raw_notes.trim().is_empty() || raw_notes.contains(ChartDefaults::RAW_NOTES) { return HitObjects::with_capacity(2048) }
 
     let mut hitobjects = HitObjects::with_capacity(2048);
     let (beats, bpms_and_durations, change_types) = bpms_and_stops;
     
     let start_time = chartinfo.audio_offset;
-    let separated_note_data: Vec<&str> = trim_split_iter(raw_note_data.split(":"), false);
 
-    let difficulty_name = separated_note_data[2];
-    chartinfo.difficulty_name = difficulty_name.or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+    // the steps type decides the key count, rows are only looked at for steps types we don't know
+    let steps_type_key_count = chartinfo.steps_info.as_ref()
+        .and_then(|steps_info| models::chartinfo::StepsInfo::steps_type_key_count(&steps_info.steps_type));
 
-    // TODO: make error for stepmania if converting from keys other than 4
-    let _key_count = 4; // TODO: change this later if gonna make this function generic to support Beatmania
+    if let Some(key_count) = steps_type_key_count {
+        chartinfo.key_count = key_count;
+    } else if let Some(first_row) = raw_notes.lines().map(|row| row.trim().trim_matches(',')).find(|row| !row.is_empty()) {
+        chartinfo.key_count = first_row.len() as u8;
+    }
+    let key_count = chartinfo.key_count as usize;
 
-    let raw_notes = separated_note_data.last().unwrap_or(&"Failed to get raw notes in notes section");
     let measures: Vec<&str> = raw_notes.split(",").collect();
 
     let mut measure_beat_count: f32 = 0.0;
+    // holds and rolls that start unjudged become a single fake, so their ends are dropped too
+    let mut faked_holds = vec![false; key_count];
 
     for measure in measures {
         let trimmed_measure = measure.trim();
-        let rows: Vec<_> = trimmed_measure.split('\n').collect();
+        let rows: Vec<_> = trimmed_measure.split('\n').map(str::trim).collect();
         let row_count = rows.len();
         let beat_time_per_row = 4.0 / row_count as f32;
     
@@ -237,9 +354,21 @@ fn process_notes(raw_note_data: &str, chartinfo: &mut models::chartinfo::ChartIn
                 (beats, bpms_and_durations, change_types)
             );
     
-            let keys = parse_keys_in_row(row);
-            for key in &keys {
-                if key.key_type != KeyType::Empty {
+            let mut keys = parse_keys_in_row(row);
+            keys.resize(key_count, Key::empty());
+            let is_unjudged = timing.is_unjudged(row_beat);
+            for (key, faked_hold) in keys.iter_mut().zip(faked_holds.iter_mut()) {
+                match key.key_type {
+                    KeyType::Normal | KeyType::Lift if is_unjudged => *key = Key::fake(),
+                    KeyType::SliderStart | KeyType::Roll => {
+                        *faked_hold = is_unjudged;
+                        if is_unjudged { *key = Key::fake() }
+                    },
+                    KeyType::SliderEnd if *faked_hold => {
+                        *faked_hold = false;
+                        *key = Key::empty();
+                    },
+                    _ => {},
                 }
             }
             
@@ -257,8 +386,192 @@ fn process_notes(raw_note_data: &str, chartinfo: &mut models::chartinfo::ChartIn
     hitobjects
 }
 
-pub(crate) fn from_sm(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>>  {
-    use models::{metadata::Metadata, chartinfo::ChartInfo, chart::Chart};
+/// timing data that can be overridden per chart in .ssc files
+#[derive(Debug, Clone)]
+struct SmTimingData {
+    offset: i32,
+    rows_per_beat: f32,
+    bpms: (Vec<f32>, Vec<f32>),
+    stops: (Vec<f32>, Vec<f32>),
+    delays: (Vec<f32>, Vec<f32>),
+    warps: (Vec<f32>, Vec<f32>),
+    fakes: (Vec<f32>, Vec<f32>),
+    speeds: (Vec<f32>, Vec<f32>),
+    scrolls: (Vec<f32>, Vec<f32>),
+    time_signatures: (Vec<f32>, Vec<TimeSignature>),
+    tick_counts: (Vec<f32>, Vec<f32>),
+}
+
+impl SmTimingData {
+    /// every tag process_tag takes
+    const TAGS: [&'static str; 14] = [
+        "#OFFSET", "#BPMS", "#STOPS", "#FREEZES", "#DELAYS", "#WARPS", "#FAKES",
+        "#SPEED", "#SPEEDS", "#SCROLLS", "#BEATSPERMEASURE", "#TIMESIGNATURES", "#TICKCOUNTS", "#ROWSPERBEAT",
+    ];
+
+    fn empty() -> Self {
+        Self {
+            offset: *ChartDefaults::AUDIO_OFFSET,
+            rows_per_beat: DEFAULT_ROWS_PER_BEAT,
+            bpms: (vec![0.0], vec![0.0]),
+            stops: (vec![], vec![]),
+            delays: (vec![], vec![]),
+            warps: (vec![], vec![]),
+            fakes: (vec![], vec![]),
+            speeds: (vec![], vec![]),
+            scrolls: (vec![], vec![]),
+            time_signatures: (vec![], vec![]),
+            tick_counts: (vec![], vec![]),
+        }
+    }
+
+    /// returns false if the header isn't a timing tag
+    fn process_tag(&mut self, header: &str, content: &str) -> bool {
+        match header {
+            "#OFFSET" => self.offset = -to_millis(content.or_default_empty_as(*ChartDefaults::AUDIO_OFFSET as f32)) as i32,
+            "#BPMS" => self.bpms = parse_bpms(&content.or_default_empty(ChartDefaults::RAW_BPMS), self.rows_per_beat),
+            "#STOPS" | "#FREEZES" => self.stops = parse_stops(&content.or_default_empty(ChartDefaults::RAW_STOPS), self.rows_per_beat),
+            "#DELAYS" => self.delays = parse_stops(&content.or_default_empty(ChartDefaults::RAW_STOPS), self.rows_per_beat),
+            "#WARPS" => self.warps = parse_warps(content, self.rows_per_beat),
+            "#FAKES" => self.fakes = parse_warps(content, self.rows_per_beat),
+            "#SPEED" | "#SPEEDS" => self.speeds = parse_speeds(content, self.rows_per_beat),
+            "#SCROLLS" => self.scrolls = parse_speeds(content, self.rows_per_beat),
+            "#BEATSPERMEASURE" | "#TIMESIGNATURES" => self.time_signatures = parse_beats_per_measure(content, self.rows_per_beat),
+            "#TICKCOUNTS" => self.tick_counts = parse_tick_counts(content, self.rows_per_beat),
+            "#ROWSPERBEAT" => self.rows_per_beat = content.or_default_empty_as(DEFAULT_ROWS_PER_BEAT),
+            _ => return false,
+        }
+        true
+    }
+
+    /// everything that affects the time of a beat: bpms, stops, delays and warps
+    fn bpms_and_stops(&self) -> BpmsAndStops {
+        let (beats, values, change_types) = merge_bpm_and_stops(self.bpms.0.clone(), self.bpms.1.clone(), self.stops.0.clone(), self.stops.1.clone());
+        if self.delays.0.is_empty() && self.warps.0.is_empty() {
+            return (beats, values, change_types);
+        }
+
+        let mut changes: Vec<(f32, f32, TimingChangeType)> = beats.into_iter()
+            .zip(values)
+            .zip(change_types)
+            .map(|((beat, value), change_type)| (beat, value, change_type))
+            .collect();
+        for (beat, duration) in self.delays.0.iter().zip(self.delays.1.iter()) {
+            changes.push((*beat, *duration, TimingChangeType::Delay));
+        }
+        for (beat, length) in self.warps.0.iter().zip(self.warps.1.iter()) {
+            changes.push((*beat, *length, TimingChangeType::Warp));
+        }
+        changes.sort_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .expect("Comparison between NaN values when sorting timing changes")
+        });
+
+        let mut merged: BpmsAndStops = (Vec::with_capacity(changes.len()), Vec::with_capacity(changes.len()), Vec::with_capacity(changes.len()));
+        for (beat, value, change_type) in changes {
+            merged.0.push(beat);
+            merged.1.push(value);
+            merged.2.push(change_type);
+        }
+        merged
+    }
+
+    /// notes in fakes and inside of warps (but not at their start) can't be hit
+    fn is_unjudged(&self, beat: f32) -> bool {
+        let in_fake = self.fakes.0.iter().zip(self.fakes.1.iter())
+            .any(|(start, length)| beat >= *start && beat < start + length);
+        let in_warp = self.warps.0.iter().zip(self.warps.1.iter())
+            .any(|(start, length)| beat > *start && beat < start + length);
+        in_fake || in_warp
+    }
+
+    /// speeds and scrolls multiply each other, so they become a single sv
+    fn sv_changes(&self) -> Vec<(f32, f32)> {
+        let mut changes: Vec<(f32, f32, bool)> = Vec::with_capacity(self.speeds.0.len() + self.scrolls.0.len());
+        for (beat, ratio) in self.speeds.0.iter().zip(self.speeds.1.iter()) {
+            changes.push((*beat, *ratio, true));
+        }
+        for (beat, ratio) in self.scrolls.0.iter().zip(self.scrolls.1.iter()) {
+            changes.push((*beat, *ratio, false));
+        }
+        changes.sort_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .expect("Comparison between NaN values when sorting sv changes")
+        });
+
+        let mut sv_changes: Vec<(f32, f32)> = Vec::with_capacity(changes.len());
+        let (mut speed, mut scroll) = (1.0, 1.0);
+        for (beat, ratio, is_speed) in changes {
+            if is_speed { speed = ratio } else { scroll = ratio }
+
+            match sv_changes.last_mut() {
+                Some(last) if last.0 == beat => last.1 = speed * scroll,
+                _ => sv_changes.push((beat, speed * scroll)),
+            }
+        }
+
+        sv_changes
+    }
+
+    /// beat and change of everything that doesn't affect the time of a beat
+    fn other_changes(&self) -> Vec<(f32, TimingChange)> {
+        let mut changes = Vec::with_capacity(self.speeds.0.len() + self.scrolls.0.len()
+            + self.time_signatures.0.len() + self.tick_counts.0.len());
+
+        for (beat, multiplier) in self.sv_changes() {
+            changes.push((beat, TimingChange::new(TimingChangeType::Sv, multiplier)));
+        }
+        for (beat, time_signature) in self.time_signatures.0.iter().zip(self.time_signatures.1.iter()) {
+            changes.push((*beat, TimingChange::time_signature(*time_signature)));
+        }
+        for (beat, ticks) in self.tick_counts.0.iter().zip(self.tick_counts.1.iter()) {
+            changes.push((*beat, TimingChange::new(TimingChangeType::TickCount, *ticks)));
+        }
+
+        changes
+    }
+}
+
+/// returns false if the header isn't a song tag
+fn process_song_tag(header: &str, content: &str,
+    metadata: &mut models::metadata::Metadata,
+    chartinfo: &mut models::chartinfo::ChartInfo) -> bool {
+    match header {
+        "#TITLE" => metadata.title = content.or_default_empty(ChartDefaults::TITLE),
+        "#ARTIST" => metadata.artist = content.or_default_empty(ChartDefaults::ARTIST),
+        "#SUBTITLE" => metadata.source = content.or_default_empty(ChartDefaults::SOURCE),
+        "#TITLETRANSLIT" => metadata.alt_title = content.or_default_empty(ChartDefaults::ALT_TITLE),
+        "#ARTISTTRANSLIT" => metadata.alt_artist = content.or_default_empty(ChartDefaults::ALT_ARTIST),
+        "#SUBTITLETRANSLIT" => {},
+        "#GENRE" => metadata.genre = content.or_default_empty(ChartDefaults::GENRE),
+        "#CREDIT" => metadata.creator = content.or_default_empty(ChartDefaults::CREATOR),
+        "#BACKGROUND"=> chartinfo.bg_path = content.or_default_empty(ChartDefaults::BG_PATH),
+        "#MUSIC" => chartinfo.song_path = content.or_default_empty(ChartDefaults::SONG_PATH),
+        "#SAMPLESTART" => chartinfo.preview_time = to_millis(content.or_default_empty_as(*ChartDefaults::PREVIEW_TIME as f32)) as i32,
+        _ => return false,
+    }
+    true
+}
+
+fn build_chart(metadata: models::metadata::Metadata,
+    mut chartinfo: models::chartinfo::ChartInfo,
+    timing: &SmTimingData,
+    raw_notes: &str) -> models::chart::Chart {
+    use models::chart::Chart;
+
+    chartinfo.audio_offset = timing.offset;
+
+    let bpms_and_stops = timing.bpms_and_stops();
+    let timing_points = process_timing_points(timing, &bpms_and_stops, chartinfo.audio_offset);
+    let hitobjects = process_notes(raw_notes, &mut chartinfo, timing, &bpms_and_stops);
+
+    Chart::new(metadata, chartinfo, timing_points, hitobjects, None)
+}
+
+/// parses every #NOTES block in a .sm file, the song's info and timing are shared between all of them
+/// .sma files are .sm files with a few extra timing tags (#BEATSPERMEASURE, #ROWSPERBEAT, #SPEED), so both go through here
+fn parse_sm_charts(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>>  {
+    use models::{metadata::Metadata, chartinfo::ChartInfo};
 
     let uncommented_chart = remove_comments(raw_chart, "//");
 
@@ -268,58 +581,134 @@ pub(crate) fn from_sm(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn s
 
     let mut metadata = Metadata::empty();
     let mut chartinfo = ChartInfo::empty();
+    let mut timing = SmTimingData::empty();
+    let mut raw_notes: Vec<String> = Vec::new();
 
-    let mut bpms: (std::vec::Vec<f32>, std::vec::Vec<f32>) = (vec![0.0], vec![0.0]);
-    let mut raw_bpms = ChartDefaults::RAW_BPMS.to_string();
-    let mut stops = (vec![], vec![]);
-    let mut raw_stops = ChartDefaults::RAW_STOPS.to_string();
-    let mut raw_notes = ChartDefaults::RAW_NOTES.to_string();
+    // rows in #BPMS, #STOPS, etc. depend on #ROWSPERBEAT which can come after them
+    process_sections(&uncommented_chart, |header, content| {
+        if header == "#ROWSPERBEAT" {
+            timing.process_tag(header, content);
+        }
+    });
 
     process_sections(&uncommented_chart, |header, content| {
-        match header {
-            "#TITLE" => metadata.title = content.or_default_empty(ChartDefaults::TITLE),
-            "#ARTIST" => metadata.artist = content.or_default_empty(ChartDefaults::ARTIST),
-            "#SUBTITLE" => metadata.source = content.or_default_empty(ChartDefaults::SOURCE),
-            "#TITLETRANSLIT" => metadata.alt_title = content.or_default_empty(ChartDefaults::ALT_TITLE),
-            "#ARTISTTRANSLIT" => metadata.alt_artist = content.or_default_empty(ChartDefaults::ALT_ARTIST),
-            "#SUBTITLETRANSLIT" => {},
-            "#GENRE" => metadata.genre = content.or_default_empty(ChartDefaults::GENRE),
-            "#CREDIT" => metadata.creator = content.or_default_empty(ChartDefaults::CREATOR),
-            "#BACKGROUND"=> chartinfo.bg_path = content.or_default_empty(ChartDefaults::BG_PATH),
-            "#MUSIC" => chartinfo.song_path = content.or_default_empty(ChartDefaults::SONG_PATH),
-            "#OFFSET" => chartinfo.audio_offset = -to_millis(content.or_default_empty_as(*ChartDefaults::AUDIO_OFFSET as f32)) as i32,
-            "#SAMPLESTART" => chartinfo.preview_time = to_millis(content.or_default_empty_as(*ChartDefaults::PREVIEW_TIME as f32)) as i32,
-            "#BPMS" => {
-                raw_bpms = content.or_default_empty(ChartDefaults::RAW_BPMS);
-                bpms = parse_bpms(&raw_bpms);
-            },
-            "#STOPS" => {
-                raw_stops = content.or_default_empty(ChartDefaults::RAW_STOPS);
-                stops = parse_stops(&raw_stops);
-            },
-            "#NOTES" => {
-                raw_notes = content.or_default_empty(ChartDefaults::RAW_NOTES)
-            },
-            _ => {},
+        if header == "#ROWSPERBEAT"
+            || process_song_tag(header, content, &mut metadata, &mut chartinfo)
+            || timing.process_tag(header, content) {
+            return;
+        }
+
+        if header == "#NOTES" {
+            raw_notes.push(content.to_string());
         }
     });
-    
-    let bpms_only = bpms.clone();
-    let bpms_and_stops = merge_bpm_and_stops(bpms.0, bpms.1, stops.0, stops.1);
 
-    let timing_points = process_timing_points(&bpms_and_stops, bpms_only, chartinfo.audio_offset);
+    if raw_notes.is_empty() {
+        raw_notes.push(ChartDefaults::RAW_NOTES.to_string());
+    }
 
-    let hitobjects = process_notes(&raw_notes, &mut chartinfo, &bpms_and_stops);
+    Ok(raw_notes.iter()
+        .map(|raw_notes| {
+            let mut chartinfo = chartinfo.clone();
+            let (steps_info, notes) = parse_sm_notes_fields(raw_notes);
+            chartinfo.difficulty_name = steps_info.difficulty.or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+            chartinfo.steps_info = Some(steps_info);
+            build_chart(metadata.clone(), chartinfo, &timing, notes)
+        })
+        .collect())
+}
+
+/// the last #NOTES block is the chart, use `from_sm_all` for every difficulty
+pub(crate) fn from_sm(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>>  {
+    let mut charts = parse_sm_charts(raw_chart)?;
+    Ok(charts.swap_remove(charts.len() - 1))
+}
 
-    Ok(Chart::new(metadata, chartinfo, timing_points, hitobjects, None))
+pub(crate) fn from_sm_all(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>>  {
+    parse_sm_charts(raw_chart)
 }
 
-#[allow(unused)]
+/// the last #NOTES block is the chart, like in `from_sm`
 pub(crate) fn from_sma(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>>  {
-    unimplemented!();
+    let mut charts = parse_sm_charts(raw_chart)?;
+    Ok(charts.swap_remove(charts.len() - 1))
 }
 
-#[allow(unused)]
 pub(crate) fn from_ssc(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>>  {
-    unimplemented!();
-}
\ No newline at end of file
+    let mut charts = parse_ssc_charts(raw_chart)?;
+    Ok(charts.swap_remove(0))
+}
+
+pub(crate) fn from_ssc_all(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>>  {
+    parse_ssc_charts(raw_chart)
+}
+
+/// parses every #NOTEDATA block in a .ssc file, the song's info and timing are used unless a chart overrides it
+fn parse_ssc_charts(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>>  {
+    use models::{metadata::Metadata, chartinfo::{ChartInfo, StepsInfo}};
+
+    let uncommented_chart = remove_comments(raw_chart, "//");
+
+    if uncommented_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    // (metadata, chartinfo, the chart's own timing, raw notes)
+    type SscChart = (Metadata, ChartInfo, Option<SmTimingData>, String);
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    let mut timing = SmTimingData::empty();
+    let mut ssc_charts: Vec<SscChart> = Vec::new();
+
+    process_sections(&uncommented_chart, |header, content| {
+        if header == "#NOTEDATA" {
+            let mut chart_chartinfo = chartinfo.clone();
+            chart_chartinfo.difficulty_name.clear();
+            chart_chartinfo.steps_info = Some(StepsInfo::empty());
+            ssc_charts.push((metadata.clone(), chart_chartinfo, None, String::new()));
+            return;
+        }
+
+        let Some((chart_metadata, chart_chartinfo, chart_timing, raw_notes)) = ssc_charts.last_mut() else {
+            if !process_song_tag(header, content, &mut metadata, &mut chartinfo) {
+                timing.process_tag(header, content);
+            }
+            return;
+        };
+
+        if SmTimingData::TAGS.contains(&header) {
+            // like in StepMania a chart with any timing of its own doesn't use the song's, only its offset
+            chart_timing.get_or_insert_with(|| SmTimingData { offset: timing.offset, ..SmTimingData::empty() })
+                .process_tag(header, content);
+            return;
+        }
+
+        let steps_info = chart_chartinfo.steps_info.get_or_insert_with(StepsInfo::empty);
+        match header {
+            "#STEPSTYPE" => steps_info.steps_type = content.to_string(),
+            "#DESCRIPTION" => steps_info.description = content.to_string(),
+            "#CHARTNAME" => chart_chartinfo.difficulty_name = content.to_string(),
+            "#DIFFICULTY" => steps_info.difficulty = content.to_string(),
+            "#METER" => steps_info.meter = content.or_default_empty_as::<u32>(1),
+            "#RADARVALUES" => steps_info.radar_values = parse_radar_values(content),
+            "#CREDIT" => chart_metadata.creator = content.or_default_empty(&metadata.creator),
+            "#NOTES" => *raw_notes = content.to_string(),
+            _ => {},
+        }
+    });
+
+    if ssc_charts.is_empty() {
+        return Ok(vec![build_chart(metadata, chartinfo, &timing, "")]);
+    }
+
+    Ok(ssc_charts.into_iter()
+        .map(|(metadata, mut chartinfo, chart_timing, raw_notes)| {
+            if chartinfo.difficulty_name.is_empty() {
+                let difficulty = chartinfo.steps_info.as_ref().map_or("", |steps_info| steps_info.difficulty.as_str());
+                chartinfo.difficulty_name = difficulty.or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+            }
+            build_chart(metadata, chartinfo, chart_timing.as_ref().unwrap_or(&timing), &raw_notes)
+        })
+        .collect())
+}
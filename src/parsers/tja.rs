@@ -0,0 +1,336 @@
+use crate::models;
+use crate::models::common::{
+    ChartDefaults,
+    TimingChangeType,
+    GameMode,
+    Key,
+};
+use crate::utils::time::{process_bpms_and_stops, process_timing_changes};
+use crate::utils::string::StrDefaultExtension;
+use crate::utils::rhythm::calculate_time_from_beat;
+use crate::errors;
+
+/// taiko is played on a single drum lane
+const KEY_COUNT: usize = 1;
+
+/// the courses a `COURSE:` can be, by name or by number
+const COURSES: [&str; 5] = ["Easy", "Normal", "Hard", "Oni", "Edit"];
+
+/// what a note digit is, rolls and balloons last until the next `8`
+#[derive(Clone, Copy, PartialEq)]
+enum TjaNote {
+    Don,
+    Kat,
+    BigDon,
+    BigKat,
+    Drumroll,
+    BigDrumroll,
+    Balloon,
+    RollEnd,
+}
+
+#[inline]
+fn parse_note(c: char) -> Option<TjaNote> {
+    match c {
+        '1' => Some(TjaNote::Don),
+        '2' => Some(TjaNote::Kat),
+        // A and B are big notes hit with a partner's hand
+        '3' | 'A' => Some(TjaNote::BigDon),
+        '4' | 'B' => Some(TjaNote::BigKat),
+        '5' => Some(TjaNote::Drumroll),
+        '6' => Some(TjaNote::BigDrumroll),
+        // 9 is a kusudama, a balloon both players pop together
+        '7' | '9' => Some(TjaNote::Balloon),
+        '8' => Some(TjaNote::RollEnd),
+        _ => None,
+    }
+}
+
+#[inline]
+fn parse_course(value: &str) -> String {
+    match value.trim().parse::<usize>() {
+        Ok(index) if index < COURSES.len() => COURSES[index].to_string(),
+        _ => COURSES.iter()
+            .find(|course| course.eq_ignore_ascii_case(value.trim()))
+            .map_or(value.trim().to_string(), |course| course.to_string()),
+    }
+}
+
+/// `#MEASURE n/d` as the length of a measure in beats
+#[inline]
+fn parse_measure_length(value: &str) -> Option<f32> {
+    let (numerator, denominator) = value.split_once('/')?;
+    let numerator: f32 = numerator.trim().parse().ok()?;
+    let denominator: f32 = denominator.trim().parse().ok()?;
+    (numerator > 0.0 && denominator > 0.0).then_some(numerator * 4.0 / denominator)
+}
+
+/// a note or a command inside of a measure, commands happen at the note that comes after them
+enum MeasureItem {
+    Note(char),
+    BpmChange(f32),
+    Scroll(f32),
+    /// in ms
+    Delay(f32),
+}
+
+/// branches are written one after another but only one gets played, so only the master one
+/// is kept, or the hardest one a `#BRANCHSTART` has if it doesn't have one
+fn kept_branch(lines: &[&str]) -> &'static str {
+    let branches: Vec<&str> = lines.iter()
+        .filter_map(|line| line.strip_prefix('#'))
+        .map(|command| command.split_once(char::is_whitespace).map_or(command, |(name, _)| name))
+        .take_while(|name| !name.eq_ignore_ascii_case("BRANCHEND") && !name.eq_ignore_ascii_case("BRANCHSTART"))
+        .collect();
+    ["M", "E", "N"].into_iter()
+        .find(|branch| branches.iter().any(|name| name.eq_ignore_ascii_case(branch)))
+        .unwrap_or("M")
+}
+
+/// headers that are shared by every course
+struct TjaHeader<'a> {
+    title: &'a str,
+    subtitle: &'a str,
+    creator: &'a str,
+    genre: &'a str,
+    song_path: &'a str,
+    bg_path: &'a str,
+    bpm: f32,
+    offset: f32,
+    preview: f32,
+}
+
+fn process_course(header: &TjaHeader, course: &str, level: &str, lines: &[&str]) -> models::chart::Chart {
+    use models::{metadata::Metadata, chartinfo::ChartInfo, hitobjects::{HitObjects, BeatNote}, chart::Chart};
+
+    let mut metadata = Metadata::empty();
+    let mut chartinfo = ChartInfo::empty();
+    metadata.title = header.title.or_default_empty(ChartDefaults::TITLE);
+    // the subtitle is usually the artist or the source, with -- or ++ in front to hide it or show it
+    let subtitle = header.subtitle.strip_prefix("--").or_else(|| header.subtitle.strip_prefix("++")).unwrap_or(header.subtitle);
+    metadata.artist = subtitle.trim().or_default_empty(ChartDefaults::ARTIST);
+    metadata.creator = header.creator.or_default_empty(ChartDefaults::CREATOR);
+    metadata.genre = header.genre.to_string();
+    chartinfo.song_path = header.song_path.to_string();
+    chartinfo.bg_path = header.bg_path.to_string();
+    // the offset is when the first measure starts, negated
+    chartinfo.audio_offset = (-header.offset * 1000.0).round() as i32;
+    chartinfo.preview_time = (header.preview * 1000.0).round() as i32;
+    chartinfo.difficulty_name = format!("{course} {level}").trim().or_default_empty(ChartDefaults::DIFFICULTY_NAME);
+    chartinfo.key_count = KEY_COUNT as u8;
+    chartinfo.game_mode = GameMode::Taiko;
+
+    let mut bpm_changes: Vec<(f32, f32)> = Vec::new();
+    let mut other_changes: Vec<(f32, f32, TimingChangeType)> = Vec::new();
+    let mut delays: Vec<(f32, f32)> = Vec::new();
+    let mut notes: Vec<(f32, TjaNote)> = Vec::new();
+
+    let mut measure_beat = 0.0;
+    let mut measure_length = 4.0;
+    let mut next_measure_length = measure_length;
+    let mut items: Vec<MeasureItem> = Vec::new();
+    let mut branch = "M";
+    let mut in_skipped_branch = false;
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(command) = line.strip_prefix('#') {
+            let (name, value) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+            let name = name.to_ascii_uppercase();
+            match name.as_str() {
+                "BRANCHSTART" => {
+                    branch = kept_branch(&lines[i + 1..]);
+                    continue;
+                },
+                "N" | "E" | "M" => {
+                    in_skipped_branch = name != branch;
+                    continue;
+                },
+                "BRANCHEND" => {
+                    in_skipped_branch = false;
+                    continue;
+                },
+                _ if in_skipped_branch => continue,
+                _ => {},
+            }
+            match name.as_str() {
+                "BPMCHANGE" => if let Ok(bpm) = value.trim().parse() { items.push(MeasureItem::BpmChange(bpm)) },
+                "SCROLL" => if let Ok(scroll) = value.trim().parse() { items.push(MeasureItem::Scroll(scroll)) },
+                // the delay is in seconds, negative ones would go back in time so they're left out
+                "DELAY" => if let Ok(delay) = value.trim().parse::<f32>() {
+                    if delay > 0.0 {
+                        items.push(MeasureItem::Delay(delay * 1000.0));
+                    }
+                },
+                // a measure's length can only change before its first note
+                "MEASURE" => if let Some(length) = parse_measure_length(value) {
+                    next_measure_length = length;
+                    if !items.iter().any(|item| matches!(item, MeasureItem::Note(_))) {
+                        measure_length = length;
+                    }
+                },
+                // gogo time and bar lines have nothing to go in
+                _ => {},
+            }
+            continue;
+        }
+        if in_skipped_branch {
+            continue;
+        }
+
+        for c in line.chars() {
+            if c != ',' {
+                if c.is_ascii_alphanumeric() {
+                    items.push(MeasureItem::Note(c));
+                }
+                continue;
+            }
+
+            let note_count = items.iter().filter(|item| matches!(item, MeasureItem::Note(_))).count();
+            let beats_per_note = if note_count == 0 { 0.0 } else { measure_length / note_count as f32 };
+            if other_changes.iter().rev().find(|(_, _, change_type)| *change_type == TimingChangeType::TimeSignature)
+                .map_or(measure_length != 4.0, |(_, length, _)| *length != measure_length)
+            {
+                other_changes.push((measure_beat, measure_length, TimingChangeType::TimeSignature));
+            }
+
+            let mut beat = measure_beat;
+            for item in items.drain(..) {
+                match item {
+                    MeasureItem::Note(c) => {
+                        if let Some(note) = parse_note(c) {
+                            notes.push((beat, note));
+                        }
+                        beat += beats_per_note;
+                    },
+                    MeasureItem::BpmChange(bpm) => bpm_changes.push((beat, bpm)),
+                    MeasureItem::Scroll(scroll) => other_changes.push((beat, scroll, TimingChangeType::Sv)),
+                    MeasureItem::Delay(duration) => delays.push((beat, duration)),
+                }
+            }
+
+            measure_beat += measure_length;
+            measure_length = next_measure_length;
+        }
+    }
+
+    let mut bpms_and_stops = process_bpms_and_stops(bpm_changes, Vec::new(), header.bpm);
+    // delays push back the note they're on, so they're delays and not stops
+    for (beat, duration) in delays {
+        let index = bpms_and_stops.0.partition_point(|change_beat| *change_beat <= beat);
+        bpms_and_stops.0.insert(index, beat);
+        bpms_and_stops.1.insert(index, duration);
+        bpms_and_stops.2.insert(index, TimingChangeType::Delay);
+    }
+    let start_time = chartinfo.audio_offset;
+    let timing_points = process_timing_changes(&bpms_and_stops, other_changes, start_time);
+    let (beats, bpms_and_durations, change_types) = &bpms_and_stops;
+    let time_at = |beat: f32| calculate_time_from_beat(beat, start_time, (beats, bpms_and_durations, change_types));
+
+    let mut objects: Vec<BeatNote> = Vec::with_capacity(notes.len());
+    let mut roll_start: Option<(f32, TjaNote)> = None;
+    for (beat, note) in notes {
+        let key = match note {
+            TjaNote::Don => Key::don(),
+            TjaNote::Kat => Key::kat(),
+            TjaNote::BigDon => Key::big_don(),
+            TjaNote::BigKat => Key::big_kat(),
+            TjaNote::Drumroll | TjaNote::BigDrumroll | TjaNote::Balloon => {
+                roll_start.get_or_insert((beat, note));
+                continue;
+            },
+            TjaNote::RollEnd => {
+                let Some((start_beat, roll)) = roll_start.take() else {
+                    continue;
+                };
+                let end_time = Some(time_at(beat));
+                let key = match roll {
+                    TjaNote::BigDrumroll => Key::big_drumroll(end_time),
+                    TjaNote::Balloon => Key::spinner(end_time),
+                    _ => Key::drumroll(end_time),
+                };
+                objects.push((start_beat, 0, key, None));
+                continue;
+            },
+        };
+        // notes can't be hit during a roll so they're left out
+        if roll_start.is_none() {
+            objects.push((beat, 0, key, None));
+        }
+    }
+
+    let hitobjects = HitObjects::from_beat_notes(objects, KEY_COUNT, time_at);
+
+    Chart::new(metadata, chartinfo, timing_points, hitobjects, None)
+}
+
+/// parses every course between a `#START` and an `#END` of a TJA (.tja) file as a taiko chart
+pub(crate) fn from_tja_all(raw_chart: &str) -> Result<Vec<models::chart::Chart>, Box<dyn std::error::Error>> {
+    let raw_chart = raw_chart.trim_start_matches('\u{feff}');
+    if raw_chart.trim().is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::EmptyChartData));
+    }
+
+    let lines: Vec<&str> = raw_chart.lines()
+        .map(|line| line.split("//").next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut header = TjaHeader {
+        title: "",
+        subtitle: "",
+        creator: "",
+        genre: "",
+        song_path: "",
+        bg_path: "",
+        bpm: 120.0,
+        offset: 0.0,
+        preview: 0.0,
+    };
+    let mut course = COURSES[3].to_string();
+    let mut level = "";
+
+    let mut charts = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        i += 1;
+
+        if line.get(..6).is_some_and(|command| command.eq_ignore_ascii_case("#START")) {
+            let length = lines[i..].iter()
+                .position(|line| line.get(..4).is_some_and(|command| command.eq_ignore_ascii_case("#END")))
+                .unwrap_or(lines.len() - i);
+            charts.push(process_course(&header, &course, level, &lines[i..i + length]));
+            i += length + 1;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_uppercase().as_str() {
+            "TITLE" => header.title = value,
+            "SUBTITLE" => header.subtitle = value,
+            "MAKER" => header.creator = value,
+            "GENRE" => header.genre = value,
+            "WAVE" => header.song_path = value,
+            "BGIMAGE" => header.bg_path = value,
+            "BPM" => header.bpm = value.parse().ok().filter(|bpm| *bpm > 0.0).unwrap_or(header.bpm),
+            "OFFSET" => header.offset = value.parse().unwrap_or(header.offset),
+            "DEMOSTART" => header.preview = value.parse().unwrap_or(header.preview),
+            "COURSE" => course = parse_course(value),
+            "LEVEL" => level = value,
+            _ => {},
+        }
+    }
+
+    if charts.is_empty() {
+        return Err(Box::new(errors::ParseError::<GameMode>::InvalidChart("No #START found in TJA chart".to_string())));
+    }
+
+    Ok(charts)
+}
+
+/// parses the first course of a TJA (.tja) file
+pub(crate) fn from_tja(raw_chart: &str) -> Result<models::chart::Chart, Box<dyn std::error::Error>> {
+    from_tja_all(raw_chart).map(|mut charts| charts.swap_remove(0))
+}
@@ -0,0 +1,358 @@
+use std::fmt;
+
+/// a json value, objects keep their keys in the order they were in
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim_start_matches('\u{feff}');
+        let mut parser = JsonParser { raw, pos: 0, depth: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos < raw.len() {
+            return Err(format!("Unexpected data after the end of the json at {}", parser.pos));
+        }
+        Ok(value)
+    }
+
+    pub fn object<K: Into<String>>(entries: Vec<(K, JsonValue)>) -> Self {
+        JsonValue::Object(entries.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    /// value of a key when this is an object
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|number| number as f32)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_f64().map(|number| number.round() as i64)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// items of an array, nothing when this isn't one
+    pub fn iter_array(&self) -> std::slice::Iter<'_, JsonValue> {
+        self.as_array().unwrap_or(&[]).iter()
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<f32> for JsonValue {
+    // going through the string keeps 0.1 from becoming 0.10000000149011612
+    fn from(value: f32) -> Self {
+        JsonValue::Number(value.to_string().parse().unwrap_or(value as f64))
+    }
+}
+
+impl From<i32> for JsonValue {
+    fn from(value: i32) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<u32> for JsonValue {
+    fn from(value: u32) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(values: Vec<JsonValue>) -> Self {
+        JsonValue::Array(values)
+    }
+}
+
+fn write_json_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?,
+        }
+    }
+    f.write_str("\"")
+}
+
+/// compact json with no whitespace
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            // json has no NaN or infinity
+            JsonValue::Number(number) if !number.is_finite() => f.write_str("null"),
+            JsonValue::Number(number) => write!(f, "{number}"),
+            JsonValue::String(value) => write_json_string(f, value),
+            JsonValue::Array(values) => {
+                f.write_str("[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            },
+            JsonValue::Object(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_json_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            },
+        }
+    }
+}
+
+/// how many arrays and objects can be inside each other, no chart goes near it
+/// and going deeper would run out of stack before it could return an error
+const MAX_DEPTH: usize = 128;
+
+struct JsonParser<'a> {
+    raw: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl JsonParser<'_> {
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.raw.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", byte as char, self.pos))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{' | b'[') if self.depth >= MAX_DEPTH => Err(format!("Json is nested more than {MAX_DEPTH} levels deep at {}", self.pos)),
+            Some(b'{') => self.nested(Self::parse_object),
+            Some(b'[') => self.nested(Self::parse_array),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b't') => self.parse_literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.parse_literal("false", JsonValue::Bool(false)),
+            Some(b'n') => self.parse_literal("null", JsonValue::Null),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(format!("Unexpected character at {}", self.pos)),
+            None => Err("Unexpected end of the json".to_string()),
+        }
+    }
+
+    #[inline]
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<JsonValue, String>) -> Result<JsonValue, String> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, String> {
+        if self.raw[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(format!("Unknown value at {}", self.pos))
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<JsonValue, String> {
+        let start = self.pos;
+        while self.peek().is_some_and(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        self.raw[start..self.pos].parse()
+            .map(JsonValue::Number)
+            .map_err(|_| format!("Invalid number at {start}"))
+    }
+
+    fn parse_hex_escape(&mut self) -> Result<u32, String> {
+        let hex = self.raw.get(self.pos..self.pos + 4).ok_or("Unexpected end of the json")?;
+        let code = u32::from_str_radix(hex, 16).map_err(|_| format!("Invalid unicode escape at {}", self.pos))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    fn parse_string(&mut self) -> Result<String, String> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            let rest = &self.raw[self.pos..];
+            let end = rest.find(['"', '\\']).ok_or("Unterminated string")?;
+            value.push_str(&rest[..end]);
+            self.pos += end + 1;
+            if rest.as_bytes()[end] == b'"' {
+                return Ok(value);
+            }
+
+            let escaped = self.peek().ok_or("Unterminated string")?;
+            self.pos += 1;
+            match escaped {
+                b'"' => value.push('"'),
+                b'\\' => value.push('\\'),
+                b'/' => value.push('/'),
+                b'b' => value.push('\u{8}'),
+                b'f' => value.push('\u{c}'),
+                b'n' => value.push('\n'),
+                b'r' => value.push('\r'),
+                b't' => value.push('\t'),
+                b'u' => {
+                    let mut code = self.parse_hex_escape()?;
+                    // characters outside the basic plane are written as two escapes
+                    if (0xD800..0xDC00).contains(&code) && self.raw[self.pos..].starts_with("\\u") {
+                        self.pos += 2;
+                        let low = self.parse_hex_escape()?;
+                        code = 0x10000 + ((code - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+                    }
+                    value.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                },
+                _ => return Err(format!("Invalid escape at {}", self.pos - 1)),
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<JsonValue, String> {
+        self.expect(b'[')?;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(values));
+                },
+                _ => return Err(format!("Expected ',' or ']' at {}", self.pos)),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<JsonValue, String> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.parse_string()?;
+            self.expect(b':')?;
+            entries.push((key, self.parse_value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(entries));
+                },
+                _ => return Err(format!("Expected ',' or '}}' at {}", self.pos)),
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,4 +1,6 @@
+pub mod json;
 pub mod math;
 pub mod rhythm;
 pub mod string;
-pub mod time;
\ No newline at end of file
+pub mod time;
+pub mod zip;
\ No newline at end of file
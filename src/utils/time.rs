@@ -1,4 +1,9 @@
 use crate::models::{common::{KeyType, Row, TimingChangeType}, sound::KeySoundRow};
+use crate::models::timing_points::{TimingPoints, TimingChange};
+use crate::utils::rhythm::calculate_time_from_beat;
+
+/// beats, bpms or durations in ms, and what each of them is, sorted by beat
+pub(crate) type BpmsAndStops = (Vec<f32>, Vec<f32>, Vec<TimingChangeType>);
 
 #[inline(always)]
 pub fn to_millis(number: f32) -> f32 {
@@ -66,6 +71,95 @@ pub fn merge_bpm_and_stops(
     (beats, values, types)
 }
 
+/// bpm at a beat, from changes sorted by beat
+#[inline]
+fn bpm_at(beat: f32, bpm_beats: &[f32], bpms: &[f32], initial_bpm: f32) -> f32 {
+    bpm_beats.iter()
+        .zip(bpms)
+        .rev()
+        .find(|(bpm_beat, _)| **bpm_beat <= beat)
+        .map_or(initial_bpm, |(_, bpm)| *bpm)
+}
+
+/// bpm changes and stops as (beat, bpm) and (beat, length in beats), a bpm change on beat 0 replaces the initial bpm
+pub(crate) fn process_bpms_and_stops(mut bpm_changes: Vec<(f32, f32)>, stops: Vec<(f32, f32)>, initial_bpm: f32) -> BpmsAndStops {
+    bpm_changes.insert(0, (0.0, initial_bpm));
+    bpm_changes.retain(|(_, bpm)| *bpm > 0.0);
+    bpm_changes.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting bpm changes"));
+    bpm_changes.dedup_by(|later, earlier| {
+        let is_same_beat = later.0 == earlier.0;
+        if is_same_beat {
+            earlier.1 = later.1;
+        }
+        is_same_beat
+    });
+
+    let (bpm_beats, bpms): (Vec<f32>, Vec<f32>) = bpm_changes.into_iter().unzip();
+
+    let mut stop_beats = Vec::with_capacity(stops.len());
+    let mut stop_durations = Vec::with_capacity(stops.len());
+    for (beat, length) in stops {
+        let bpm = bpm_at(beat, &bpm_beats, &bpms, initial_bpm);
+        stop_beats.push(beat);
+        stop_durations.push(length * 60000.0 / bpm);
+    }
+
+    merge_bpm_and_stops(bpm_beats, bpms, stop_beats, stop_durations)
+}
+
+/// timing points from bpm changes and stops along with changes that don't move notes, like measure lengths and sv
+pub(crate) fn process_timing_changes(
+    bpms_and_stops: &BpmsAndStops,
+    other_changes: Vec<(f32, f32, TimingChangeType)>,
+    start_time: i32,
+) -> TimingPoints {
+    let (beats, bpms_and_durations, change_types) = bpms_and_stops;
+
+    let mut changes: Vec<(f32, f32, TimingChangeType)> = beats.iter().copied()
+        .zip(bpms_and_durations.iter().copied())
+        .zip(change_types.iter().copied())
+        .map(|((beat, value), change_type)| (beat, value, change_type))
+        .collect();
+    changes.extend(other_changes);
+    changes.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .expect("Comparison between NaN values when sorting timing changes")
+    });
+
+    let mut timing_points = TimingPoints::with_capacity(changes.len());
+    for (beat, value, change_type) in changes {
+        let time = calculate_time_from_beat(beat, start_time, (beats, bpms_and_durations, change_types));
+        timing_points.add(time, beat, TimingChange::new(change_type, value));
+    }
+
+    timing_points
+}
+
+/// timing points from bpm changes and stops along with measure lengths as (beat, length in beats)
+pub(crate) fn process_timing_points(
+    bpms_and_stops: &BpmsAndStops,
+    measure_lengths: &[(f32, f32)],
+    start_time: i32,
+) -> TimingPoints {
+    let measure_lengths = measure_lengths.iter()
+        .map(|(beat, length)| (*beat, *length, TimingChangeType::TimeSignature))
+        .collect();
+    process_timing_changes(bpms_and_stops, measure_lengths, start_time)
+}
+
+/// measure lengths as fractions of a 4/4 measure, turned into (beat, length in beats) whenever they change
+pub(crate) fn measure_time_signatures(measure_lengths: &[f32], measure_beats: &[f32]) -> Vec<(f32, f32)> {
+    let mut changes = Vec::new();
+    let mut previous_length = 1.0;
+    for (length, beat) in measure_lengths.iter().zip(measure_beats) {
+        if *length != previous_length {
+            changes.push((*beat, length * 4.0));
+            previous_length = *length;
+        }
+    }
+    changes
+}
+
 #[inline(always)]
 pub fn find_sliderend_time(
     start_idx: usize,
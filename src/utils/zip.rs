@@ -0,0 +1,398 @@
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+const END_OF_CENTRAL_DIRECTORY_SIZE: usize = 22;
+/// version 2.0, the first one with deflate and folders
+const ZIP_VERSION: u16 = 20;
+/// names are UTF-8
+const UTF8_FLAG: u16 = 1 << 11;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+/// 1980-01-01, the earliest date a zip can have
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { 0xedb88320 ^ (crc >> 1) } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| CRC32_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8))
+}
+
+/// `offset + length`, offsets come from the zip itself so they can be anything
+#[inline]
+fn add_offset(offset: usize, length: usize) -> Result<usize, String> {
+    offset.checked_add(length).ok_or_else(|| "Offset out of range in zip".to_string())
+}
+
+#[inline]
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes.get(offset..add_offset(offset, 2)?)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Unexpected end of zip".to_string())
+}
+
+#[inline]
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes.get(offset..add_offset(offset, 4)?)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Unexpected end of zip".to_string())
+}
+
+/// a file in a zip, its contents only get decompressed when asked for
+pub struct ZipEntry<'a> {
+    pub name: String,
+    method: u16,
+    crc: u32,
+    size: usize,
+    data: &'a [u8],
+}
+
+impl ZipEntry<'_> {
+    pub fn contents(&self) -> Result<Vec<u8>, String> {
+        let contents = match self.method {
+            STORED => self.data.to_vec(),
+            DEFLATED => inflate(self.data, self.size)?,
+            method => return Err(format!("{} uses compression method {method} which isn't supported", self.name)),
+        };
+        if contents.len() != self.size || crc32(&contents) != self.crc {
+            return Err(format!("{} is corrupted", self.name));
+        }
+        Ok(contents)
+    }
+}
+
+/// every file in a zip, folders are left out
+pub fn read_zip(raw: &[u8]) -> Result<Vec<ZipEntry<'_>>, String> {
+    // the end of central directory is at the end of the file, before a comment of up to 65535 bytes
+    let last_start = raw.len().checked_sub(END_OF_CENTRAL_DIRECTORY_SIZE).ok_or("Too small to be a zip")?;
+    let end = (last_start.saturating_sub(u16::MAX as usize)..=last_start)
+        .rev()
+        .find(|offset| read_u32(raw, *offset) == Ok(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or("Missing end of central directory, this isn't a zip")?;
+    let entry_count = read_u16(raw, end + 10)? as usize;
+    let mut offset = read_u32(raw, end + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(entry_count);
+    for _ in 0..entry_count {
+        if read_u32(raw, offset)? != CENTRAL_HEADER_SIGNATURE {
+            return Err("Invalid central directory entry".to_string());
+        }
+        let method = read_u16(raw, add_offset(offset, 10)?)?;
+        let crc = read_u32(raw, add_offset(offset, 16)?)?;
+        let compressed_size = read_u32(raw, add_offset(offset, 20)?)? as usize;
+        let size = read_u32(raw, add_offset(offset, 24)?)? as usize;
+        let name_length = read_u16(raw, add_offset(offset, 28)?)? as usize;
+        let extra_length = read_u16(raw, add_offset(offset, 30)?)? as usize;
+        let comment_length = read_u16(raw, add_offset(offset, 32)?)? as usize;
+        let local_offset = read_u32(raw, add_offset(offset, 42)?)? as usize;
+        let name_start = add_offset(offset, 46)?;
+        let name = raw.get(name_start..add_offset(name_start, name_length)?).ok_or("Unexpected end of zip")?;
+        // names that aren't flagged as UTF-8 are usually still UTF-8 in practice
+        let name = String::from_utf8_lossy(name).replace('\\', "/");
+        offset = add_offset(name_start, name_length + extra_length + comment_length)?;
+
+        if compressed_size == u32::MAX as usize || size == u32::MAX as usize {
+            return Err(format!("{name} is too big, zip64 isn't supported"));
+        }
+        if name.ends_with('/') {
+            continue;
+        }
+
+        if read_u32(raw, local_offset)? != LOCAL_HEADER_SIGNATURE {
+            return Err(format!("Invalid local header for {name}"));
+        }
+        let local_name_length = read_u16(raw, add_offset(local_offset, 26)?)? as usize;
+        let local_extra_length = read_u16(raw, add_offset(local_offset, 28)?)? as usize;
+        let data_start = add_offset(local_offset, 30 + local_name_length + local_extra_length)?;
+        let data = raw.get(data_start..add_offset(data_start, compressed_size)?).ok_or("Unexpected end of zip")?;
+        entries.push(ZipEntry { name, method, crc, size, data });
+    }
+
+    Ok(entries)
+}
+
+/// a zip with every file stored as is, the files going in are usually compressed already.
+/// sizes and offsets past what a zip without zip64 can hold are an error instead of getting cut off
+pub fn write_zip(files: &[(&str, &[u8])]) -> Result<Vec<u8>, String> {
+    if files.len() > u16::MAX as usize {
+        return Err(format!("Can't zip {} files, a zip holds at most {}", files.len(), u16::MAX));
+    }
+    let mut zip = Vec::with_capacity(files.iter().map(|(name, data)| name.len() * 2 + data.len() + 76).sum::<usize>() + END_OF_CENTRAL_DIRECTORY_SIZE);
+    let mut central_directory = Vec::new();
+
+    for (name, data) in files {
+        if name.len() > u16::MAX as usize {
+            return Err(format!("Can't zip a file with a {} byte name, names can be at most {} bytes", name.len(), u16::MAX));
+        }
+        if data.len() > u32::MAX as usize {
+            return Err(format!("{name} is too big to zip, zip64 isn't supported"));
+        }
+        let local_offset = u32::try_from(zip.len()).map_err(|_| "The zip is too big, zip64 isn't supported".to_string())?;
+        let crc = crc32(data);
+
+        zip.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        zip.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        zip.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+        zip.extend_from_slice(&STORED.to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(&DOS_DATE.to_le_bytes());
+        zip.extend_from_slice(&crc.to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+
+        central_directory.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        central_directory.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        central_directory.extend_from_slice(&ZIP_VERSION.to_le_bytes());
+        central_directory.extend_from_slice(&UTF8_FLAG.to_le_bytes());
+        central_directory.extend_from_slice(&STORED.to_le_bytes());
+        central_directory.extend_from_slice(&0u16.to_le_bytes());
+        central_directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+        central_directory.extend_from_slice(&crc.to_le_bytes());
+        central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        // extra field, comment, disk, internal and external attributes
+        central_directory.extend_from_slice(&[0; 12]);
+        central_directory.extend_from_slice(&local_offset.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    let central_directory_offset = u32::try_from(zip.len()).map_err(|_| "The zip is too big, zip64 isn't supported".to_string())?;
+    if central_directory.len() > u32::MAX as usize {
+        return Err("The zip is too big, zip64 isn't supported".to_string());
+    }
+    zip.extend_from_slice(&central_directory);
+    zip.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    zip.extend_from_slice(&[0; 4]);
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    zip.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    zip.extend_from_slice(&central_directory_offset.to_le_bytes());
+    zip.extend_from_slice(&0u16.to_le_bytes());
+
+    Ok(zip)
+}
+
+const MAX_CODE_LENGTH: usize = 15;
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// the order code length code lengths are in, the ones that are used the least are last
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    bit_count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.bit_count < count {
+            let byte = *self.data.get(self.pos).ok_or("Unexpected end of deflate data")?;
+            self.buffer |= (byte as u32) << self.bit_count;
+            self.pos += 1;
+            self.bit_count += 8;
+        }
+        let value = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.bit_count -= count;
+        Ok(value)
+    }
+
+    /// stored blocks start on a byte
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.bit_count = 0;
+    }
+}
+
+/// a canonical huffman code, as the amount of codes of each length and the symbols ordered by code
+struct Huffman {
+    counts: [u16; MAX_CODE_LENGTH + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, String> {
+        let mut counts = [0u16; MAX_CODE_LENGTH + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut left: i32 = 1;
+        for count in &counts[1..] {
+            left = (left << 1) - *count as i32;
+            if left < 0 {
+                return Err("Invalid huffman code in deflate data".to_string());
+            }
+        }
+
+        let mut offsets = [0u16; MAX_CODE_LENGTH + 2];
+        for length in 1..=MAX_CODE_LENGTH {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0u16; offsets[MAX_CODE_LENGTH + 1] as usize];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize] as usize] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for count in &self.counts[1..] {
+            code |= reader.bits(1)? as i32;
+            let count = *count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("Invalid huffman code in deflate data".to_string())
+    }
+}
+
+fn fixed_huffman() -> Result<(Huffman, Huffman), String> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_huffman(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? as usize + 257;
+    let distance_count = reader.bits(5)? as usize + 1;
+    let code_length_count = reader.bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_length_lengths[*index] = reader.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_length_lengths)?;
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("Repeated code length with nothing before it")?, 3 + reader.bits(2)?),
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count {
+        return Err("Too many code lengths in deflate data".to_string());
+    }
+
+    Ok((Huffman::new(&lengths[..literal_count])?, Huffman::new(&lengths[literal_count..])?))
+}
+
+/// decompresses raw deflate data, the way zips store it,
+/// stops as soon as it's bigger than `max_size` so a tiny archive can't fill up memory
+pub fn inflate(data: &[u8], max_size: usize) -> Result<Vec<u8>, String> {
+    let too_big = || format!("Deflate data is bigger than {max_size} bytes");
+    let mut reader = BitReader { data, pos: 0, buffer: 0, bit_count: 0 };
+    let mut output: Vec<u8> = Vec::with_capacity(max_size.min(data.len().saturating_mul(4)));
+
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        let (literals, distances) = match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let length = read_u16(data, reader.pos)?;
+                let complement = read_u16(data, reader.pos + 2)?;
+                if length != !complement {
+                    return Err("Invalid stored block length in deflate data".to_string());
+                }
+                let start = reader.pos + 4;
+                let block = data.get(start..start + length as usize).ok_or("Unexpected end of deflate data")?;
+                if output.len() + block.len() > max_size {
+                    return Err(too_big());
+                }
+                output.extend_from_slice(block);
+                reader.pos = start + length as usize;
+                if is_last {
+                    break;
+                }
+                continue;
+            },
+            1 => fixed_huffman()?,
+            2 => dynamic_huffman(&mut reader)?,
+            _ => return Err("Invalid block type in deflate data".to_string()),
+        };
+
+        loop {
+            let symbol = literals.decode(&mut reader)? as usize;
+            if symbol < 256 {
+                if output.len() >= max_size {
+                    return Err(too_big());
+                }
+                output.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                break;
+            }
+
+            let index = symbol - 257;
+            let (Some(length_base), Some(length_extra_bits)) = (LENGTH_BASES.get(index), LENGTH_EXTRA_BITS.get(index)) else {
+                return Err("Invalid length in deflate data".to_string());
+            };
+            let length = *length_base as usize + reader.bits(*length_extra_bits as u32)? as usize;
+            let index = distances.decode(&mut reader)? as usize;
+            let (Some(distance_base), Some(distance_extra_bits)) = (DISTANCE_BASES.get(index), DISTANCE_EXTRA_BITS.get(index)) else {
+                return Err("Invalid distance in deflate data".to_string());
+            };
+            let distance = *distance_base as usize + reader.bits(*distance_extra_bits as u32)? as usize;
+            if distance > output.len() {
+                return Err("Distance too far back in deflate data".to_string());
+            }
+            if output.len() + length > max_size {
+                return Err(too_big());
+            }
+
+            // the copy can overlap with itself so it goes a byte at a time
+            let start = output.len() - distance;
+            for i in 0..length {
+                output.push(output[start + i]);
+            }
+        }
+
+        if is_last {
+            break;
+        }
+    }
+
+    Ok(output)
+}
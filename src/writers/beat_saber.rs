@@ -0,0 +1,161 @@
+use crate::models;
+use crate::models::common::{GameMode, KeyType};
+use crate::utils::json::JsonValue;
+use crate::utils::rhythm::calculate_beat_from_time;
+use crate::errors;
+
+/// notes go on a grid 4 lines wide and 3 layers tall
+const GRID_LINES: usize = 4;
+const GRID_LAYERS: usize = 3;
+const GRID_CELLS: usize = GRID_LINES * GRID_LAYERS;
+/// walls reach from their layer up to this height
+const WALL_TOP: usize = 5;
+/// notes can be cut in any direction
+const ANY_DIRECTION: u32 = 8;
+/// the one difficulty written to Info.dat
+const DIFFICULTY: &str = "ExpertPlus";
+const DIFFICULTY_RANK: u32 = 9;
+const DIFFICULTY_FILENAME: &str = "ExpertPlusStandard.dat";
+
+/// the first grid cell whose column is `column`, the parser puts cells on columns in order
+/// so going back to the grid this way keeps every column where it was
+#[inline]
+fn grid_cell(column: usize, key_count: usize) -> (usize, usize) {
+    let cell = (column * GRID_CELLS).div_ceil(key_count);
+    (cell / GRID_LAYERS, cell % GRID_LAYERS)
+}
+
+fn check_chart(chart: &models::chart::Chart) -> Result<(), Box<dyn std::error::Error>> {
+    let key_count = chart.chartinfo.key_count as usize;
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "Beat Saber".to_string())));
+    }
+    if !(1..=GRID_CELLS).contains(&key_count) {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidKeyCount(
+            key_count as u8, format!("1k to {GRID_CELLS}k"), "Beat Saber".to_string())));
+    }
+    Ok(())
+}
+
+/// bpm change times and bpms with beat 0 at the start of the audio, the first bpm also goes from there
+/// so the first bpm change lands on whatever (fractional) beat it's on
+fn timing(chart: &models::chart::Chart) -> (Vec<i32>, Vec<f32>) {
+    let (mut bpm_times, mut bpms): (Vec<i32>, Vec<f32>) = chart.timing_points.bpm_changes_zipped()
+        .filter(|(_, _, change)| change.value > 0.0)
+        .map(|(time, _, change)| (*time, change.value))
+        .unzip();
+    if bpms.is_empty() {
+        bpm_times.push(0);
+        bpms.push(120.0);
+    }
+    if bpm_times[0] > 0 {
+        bpm_times.insert(0, 0);
+        bpms.insert(0, bpms[0]);
+    }
+
+    (bpm_times, bpms)
+}
+
+fn grid_object(beat: f32, line: usize, layer: usize) -> Vec<(&'static str, JsonValue)> {
+    vec![("b", beat.into()), ("x", line.into()), ("y", layer.into())]
+}
+
+/// writes a Beat Saber v3 difficulty (.dat), each column gets a cell of the grid,
+/// long notes become one line wide walls and mines become bombs
+pub(crate) fn to_beat_saber(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    check_chart(chart)?;
+    let key_count = chart.chartinfo.key_count as usize;
+    let (bpm_times, bpms) = timing(chart);
+    let beat_at = |time: i32| calculate_beat_from_time(time, 0, (&bpm_times, &bpms));
+
+    let bpm_events: Vec<JsonValue> = bpm_times.iter()
+        .zip(bpms.iter())
+        .map(|(time, bpm)| JsonValue::object(vec![("b", beat_at(*time).into()), ("m", (*bpm).into())]))
+        .collect();
+
+    let mut color_notes = Vec::new();
+    let mut bomb_notes = Vec::new();
+    let mut obstacles = Vec::new();
+    for (time, _, _, row) in chart.hitobjects.iter_zipped() {
+        let beat = beat_at(*time);
+        for (column, key) in row.iter().enumerate() {
+            let (line, layer) = grid_cell(column, key_count);
+            let mut object = grid_object(beat, line, layer);
+            match key.key_type {
+                KeyType::Normal | KeyType::Lift => {
+                    // red notes on the left half and blue notes on the right one
+                    let color = if line < GRID_LINES / 2 { 0 } else { 1 };
+                    object.extend([("c", color.into()), ("d", ANY_DIRECTION.into()), ("a", 0.into())]);
+                    color_notes.push(JsonValue::object(object));
+                },
+                KeyType::SliderStart | KeyType::Roll => {
+                    let end_beat = key.slider_end_time().map_or(beat, beat_at);
+                    object.extend([("d", (end_beat - beat).into()), ("w", 1.into()), ("h", (WALL_TOP - layer).into())]);
+                    obstacles.push(JsonValue::object(object));
+                },
+                KeyType::Mine => bomb_notes.push(JsonValue::object(object)),
+                _ => {},
+            }
+        }
+    }
+
+    let empty = || JsonValue::from(Vec::new());
+    let difficulty = JsonValue::object(vec![
+        ("version", "3.3.0".into()),
+        ("bpmEvents", bpm_events.into()),
+        ("rotationEvents", empty()),
+        ("colorNotes", color_notes.into()),
+        ("bombNotes", bomb_notes.into()),
+        ("obstacles", obstacles.into()),
+        ("sliders", empty()),
+        ("burstSliders", empty()),
+        ("waypoints", empty()),
+        ("basicBeatmapEvents", empty()),
+        ("colorBoostBeatmapEvents", empty()),
+        ("lightColorEventBoxGroups", empty()),
+        ("lightRotationEventBoxGroups", empty()),
+        ("lightTranslationEventBoxGroups", empty()),
+        ("useNormalEventsAsCompatibleEvents", true.into()),
+    ]);
+
+    Ok(difficulty.to_string())
+}
+
+/// writes the v2 Info.dat that goes with `to_beat_saber`, with its difficulty saved as `ExpertPlusStandard.dat`
+pub(crate) fn to_beat_saber_info(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    check_chart(chart)?;
+    let (_, bpms) = timing(chart);
+
+    let difficulty = JsonValue::object(vec![
+        ("_difficulty", DIFFICULTY.into()),
+        ("_difficultyRank", DIFFICULTY_RANK.into()),
+        ("_beatmapFilename", DIFFICULTY_FILENAME.into()),
+        ("_noteJumpMovementSpeed", 16.into()),
+        ("_noteJumpStartBeatOffset", 0.into()),
+        ("_customData", JsonValue::object(vec![("_difficultyLabel", chart.chartinfo.difficulty_name.as_str().into())])),
+    ]);
+    let info = JsonValue::object(vec![
+        ("_version", "2.1.0".into()),
+        ("_songName", chart.metadata.title.as_str().into()),
+        ("_songSubName", chart.metadata.alt_title.as_str().into()),
+        ("_songAuthorName", chart.metadata.artist.as_str().into()),
+        ("_levelAuthorName", chart.metadata.creator.as_str().into()),
+        ("_beatsPerMinute", bpms[0].into()),
+        ("_shuffle", 0.into()),
+        ("_shufflePeriod", 0.5.into()),
+        ("_previewStartTime", (chart.chartinfo.preview_time.max(0) as f64 / 1000.0).into()),
+        ("_previewDuration", 10.into()),
+        ("_songFilename", chart.chartinfo.song_path.as_str().into()),
+        ("_coverImageFilename", chart.chartinfo.bg_path.as_str().into()),
+        ("_environmentName", "DefaultEnvironment".into()),
+        ("_allDirectionsEnvironmentName", "GlassDesertEnvironment".into()),
+        // beats already start with the audio, the offset is a legacy field that some editors drop
+        ("_songTimeOffset", 0.into()),
+        ("_difficultyBeatmapSets", vec![JsonValue::object(vec![
+            ("_beatmapCharacteristicName", JsonValue::from("Standard")),
+            ("_difficultyBeatmaps", vec![difficulty].into()),
+        ])].into()),
+    ]);
+
+    Ok(info.to_string())
+}
@@ -0,0 +1,507 @@
+use std::collections::BTreeMap;
+use crate::models;
+use crate::models::common::{GameMode, KeyType, TimingChangeType};
+use crate::utils::string::add_key_value_template;
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// the biggest two digit base 36 id, ZZ
+const MAX_ID: usize = 36 * 36 - 1;
+/// what mines are written with, their value is how much they hurt
+const MINE_ID: &str = "01";
+/// cells every measure can be split into, the smallest one everything fits on gets used
+const CELL_COUNTS: [usize; 14] = [1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 192];
+/// beats an object can be off from a cell and still be put on it
+const CELL_SNAP_MARGIN: f32 = 0.01;
+/// #STOPxx values are in 192nds of a 4/4 measure
+const STOP_UNITS_PER_BEAT: f32 = 48.0;
+/// pulses in a beat for bmson
+const BMSON_RESOLUTION: f32 = 240.0;
+
+/// visible channels for every key count, LN channels are these +40 and mines are D or E instead of 1 or 2
+fn key_count_lanes(key_count: u8) -> Option<&'static [&'static str]> {
+    Some(match key_count {
+        4 => &["11", "12", "13", "14"],
+        5 => &["11", "12", "13", "14", "15"],
+        6 => &["16", "11", "12", "13", "14", "15"],
+        7 => &["11", "12", "13", "14", "15", "18", "19"],
+        8 => &["16", "11", "12", "13", "14", "15", "18", "19"],
+        9 => &["11", "12", "13", "14", "15", "22", "23", "24", "25"],
+        10 => &["11", "12", "13", "14", "15", "21", "22", "23", "24", "25"],
+        12 => &["16", "11", "12", "13", "14", "15", "21", "22", "23", "24", "25", "26"],
+        14 => &["11", "12", "13", "14", "15", "18", "19", "21", "22", "23", "24", "25", "28", "29"],
+        16 => &["16", "11", "12", "13", "14", "15", "18", "19", "21", "22", "23", "24", "25", "28", "29", "26"],
+        _ => return None,
+    })
+}
+
+/// a bmson note as (x, y, l), x 0 is bgm and y and l are in pulses
+type BmsonNote = (u32, i64, i64);
+
+/// bmson mode_hint and lanes (x) for every key count, counts without a layout are "generic-nkeys" with lanes 1 to n
+fn key_count_bmson_lanes(key_count: u8) -> (String, Vec<u32>) {
+    let (mode_hint, lanes): (&str, &[u32]) = match key_count {
+        5 => ("popn-5k", &[1, 2, 3, 4, 5]),
+        6 => ("beat-5k", &[8, 1, 2, 3, 4, 5]),
+        8 => ("beat-7k", &[8, 1, 2, 3, 4, 5, 6, 7]),
+        9 => ("popn-9k", &[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        12 => ("beat-10k", &[8, 1, 2, 3, 4, 5, 9, 10, 11, 12, 13, 16]),
+        16 => ("beat-14k", &[8, 1, 2, 3, 4, 5, 6, 7, 9, 10, 11, 12, 13, 14, 15, 16]),
+        _ => return (format!("generic-{key_count}keys"), (1..=key_count as u32).collect()),
+    };
+    (mode_hint.to_string(), lanes.to_vec())
+}
+
+/// the same lane on another kind of channel, like 5x and 6x for LNs
+#[inline]
+fn lane_with_player(lane: &str, first_player: char, second_player: char) -> String {
+    let (player, key) = lane.split_at(1);
+    let player = if player == "1" { first_player } else { second_player };
+    format!("{player}{key}")
+}
+
+#[inline]
+fn to_base36(id: usize) -> String {
+    const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    let id = id.min(MAX_ID);
+    format!("{}{}", DIGITS[id / 36] as char, DIGITS[id % 36] as char)
+}
+
+/// beat of a time, stops and delays included, measured from the chart's offset
+fn beat_at_time(time: i32, chart: &models::chart::Chart) -> f32 {
+    let mut beat = 0.0;
+    let mut current_time = chart.chartinfo.audio_offset as f32;
+    let mut bpm = chart.timing_points.bpms().first().copied().unwrap_or(120.0);
+
+    for (change_time, _, change) in chart.timing_points.iter_zipped() {
+        let change_time = *change_time as f32;
+        if change_time > time as f32 {
+            break;
+        }
+        beat += (change_time - current_time) * bpm / 60000.0;
+        current_time = change_time;
+        match change.change_type {
+            TimingChangeType::Bpm => bpm = change.value,
+            TimingChangeType::Stop | TimingChangeType::Delay => {
+                if (time as f32) < change_time + change.value {
+                    return beat;
+                }
+                current_time += change.value;
+            },
+            _ => {},
+        }
+    }
+
+    beat + (time as f32 - current_time) * bpm / 60000.0
+}
+
+/// start beat and length in beats of every measure up to `last_beat`
+fn process_measure_layout(chart: &models::chart::Chart, lead_beats: f32, last_beat: f32) -> Vec<(f32, f32)> {
+    let time_signatures: Vec<(f32, f32)> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::TimeSignature && *view.value > 0.0)
+        .map(|view| (*view.beat + lead_beats, *view.value))
+        .collect();
+
+    let mut measures = Vec::new();
+    let mut beat = 0.0;
+    while beat <= last_beat || measures.is_empty() {
+        let length = time_signatures.iter()
+            .rev()
+            .find(|(time_signature_beat, _)| *time_signature_beat <= beat + CELL_SNAP_MARGIN)
+            .map_or(4.0, |(_, length)| *length);
+        measures.push((beat, length));
+        beat += length;
+    }
+    measures
+}
+
+/// lays objects in a channel out over a measure, objects landing on the same cell go on extra lines
+fn process_channel_lines(measure_length: f32, objects: &[(f32, String)]) -> Vec<String> {
+    let cell_count = CELL_COUNTS.iter()
+        .copied()
+        .find(|cell_count| objects.iter().all(|(beat, _)| {
+            let cell = beat / measure_length * *cell_count as f32;
+            (cell - cell.round()).abs() * measure_length / (*cell_count as f32) < CELL_SNAP_MARGIN
+        }))
+        .unwrap_or(192);
+
+    let mut lines: Vec<Vec<&str>> = Vec::new();
+    for (beat, id) in objects {
+        let cell = ((beat / measure_length * cell_count as f32).round() as usize).min(cell_count - 1);
+        match lines.iter_mut().find(|line| line[cell] == "00") {
+            Some(line) => line[cell] = id,
+            None => {
+                let mut line = vec!["00"; cell_count];
+                line[cell] = id;
+                lines.push(line);
+            },
+        }
+    }
+
+    lines.into_iter().map(|line| line.concat()).collect()
+}
+
+pub(crate) fn to_bms(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let mut template = String::new();
+    let key_count = chart.chartinfo.key_count;
+
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "BMS".to_string())));
+    }
+    let lanes = key_count_lanes(key_count).ok_or_else(|| errors::WriteError::<GameMode>::InvalidKeyCount(
+        key_count, "4k, 5k, 6k, 7k, 8k, 9k, 10k, 12k, 14k and 16k".to_string(), "BMS".to_string()))?;
+
+    // #WAV00 can't be used since 00 means nothing is there, so samples get shifted up if the chart uses index 0
+    let sample_paths = chart.soundbank.as_ref().map(|soundbank| soundbank.get_sample_paths()).unwrap_or_default();
+    let id_shift = if sample_paths.first().is_some_and(|path| !path.is_empty()) { 1 } else { 0 };
+    let sample_id = |sample: usize| Some(sample + id_shift).filter(|id| *id <= MAX_ID).map(to_base36);
+    let mut next_id = (sample_paths.len() + id_shift).max(1);
+
+    let song_id = (!chart.chartinfo.song_path.is_empty()).then(|| {
+        next_id += 1;
+        to_base36(next_id - 1)
+    });
+    // notes without a keysound still need an id, so they get one with no sample behind it
+    let silent_id = to_base36(next_id);
+
+    // the song has to start on or after the first measure, so measures get added in front if it starts before beat 0
+    let song_beat = beat_at_time(0, chart);
+    let lead_beats = if song_id.is_some() && song_beat < 0.0 {
+        (-song_beat / 4.0).ceil() * 4.0
+    } else {
+        0.0
+    };
+
+    // (beat, channel, id)
+    let mut objects: Vec<(f32, String, String)> = Vec::with_capacity(chart.hitobjects.rows.len());
+
+    if let Some(song_id) = &song_id {
+        objects.push((song_beat + lead_beats, "01".to_string(), song_id.clone()));
+    }
+    if let Some(soundbank) = &chart.soundbank {
+        for sound_effect in &soundbank.sound_effects {
+            if let Some(id) = sample_id(sound_effect.sample) {
+                objects.push((beat_at_time(sound_effect.time, chart) + lead_beats, "01".to_string(), id));
+            }
+        }
+    }
+
+    for (_, beat, keysounds, row) in chart.hitobjects.iter_zipped() {
+        let beat = beat + lead_beats;
+        for (column, key) in row.iter().enumerate() {
+            let lane = lanes[column];
+            let id = keysounds.get_sounds()
+                .get(column)
+                .filter(|keysound| !keysounds.is_empty && keysound.has_custom)
+                .and_then(|keysound| keysound.sample)
+                .and_then(sample_id)
+                .unwrap_or_else(|| silent_id.clone());
+
+            match key.key_type {
+                // lifts are hit like normal notes
+                KeyType::Normal | KeyType::Lift => objects.push((beat, lane.to_string(), id)),
+                // long notes are written with #LNTYPE 1, a start and an end object in the LN channel
+                KeyType::SliderStart | KeyType::Roll | KeyType::SliderEnd => {
+                    let ln_lane = lane_with_player(lane, '5', '6');
+                    let id = if key.key_type == KeyType::SliderEnd { silent_id.clone() } else { id };
+                    objects.push((beat, ln_lane, id));
+                },
+                KeyType::Mine => {
+                    let mine_lane = lane_with_player(lane, 'D', 'E');
+                    objects.push((beat, mine_lane, MINE_ID.to_string()));
+                },
+                _ => {},
+            }
+        }
+    }
+
+    // bpms and stops that aren't the first bpm get an id so they can have decimals
+    let mut bpm_table: Vec<f32> = Vec::new();
+    let mut stop_table: Vec<f32> = Vec::new();
+    let initial_bpm = chart.timing_points.bpms().first().copied().unwrap_or(120.0);
+    let mut current_bpm = initial_bpm;
+    let mut is_initial_bpm = true;
+    for (_, beat, change) in chart.timing_points.iter_zipped() {
+        let beat = beat + lead_beats;
+        match change.change_type {
+            TimingChangeType::Bpm => {
+                current_bpm = change.value;
+                // the first bpm is already #BPM
+                if is_initial_bpm {
+                    is_initial_bpm = false;
+                    continue;
+                }
+                let id = match bpm_table.iter().position(|bpm| *bpm == change.value) {
+                    Some(position) => position + 1,
+                    None => {
+                        bpm_table.push(change.value);
+                        bpm_table.len()
+                    },
+                };
+                objects.push((beat, "08".to_string(), to_base36(id)));
+            },
+            // there are no delays in BMS so they stop the chart the same way
+            TimingChangeType::Stop | TimingChangeType::Delay => {
+                // short stops would get lost if these were rounded to whole 192nds, so they keep a few decimals
+                let units = (change.value * current_bpm / 60000.0 * STOP_UNITS_PER_BEAT * 1000.0).round() / 1000.0;
+                if units <= 0.0 {
+                    continue;
+                }
+                let id = match stop_table.iter().position(|stop| *stop == units) {
+                    Some(position) => position + 1,
+                    None => {
+                        stop_table.push(units);
+                        stop_table.len()
+                    },
+                };
+                objects.push((beat, "09".to_string(), to_base36(id)));
+            },
+            _ => {},
+        }
+    }
+
+    // header
+    template.push_str("*---------------------- HEADER FIELD\n");
+    add_key_value_template(&mut template,
+        "#PLAYER", " ", if lanes.iter().any(|lane| lane.starts_with('2')) && key_count != 9 { "3" } else { "1" }, "\n");
+    add_key_value_template(&mut template,
+        "#GENRE", " ", &chart.metadata.genre, "\n");
+    add_key_value_template(&mut template,
+        "#TITLE", " ", &chart.metadata.title.replace("\n", ""), "\n");
+    add_key_value_template(&mut template,
+        "#SUBTITLE", " ", &chart.chartinfo.difficulty_name, "\n");
+    add_key_value_template(&mut template,
+        "#ARTIST", " ", &chart.metadata.artist, "\n");
+    add_key_value_template(&mut template,
+        "#SUBARTIST", " ", &chart.metadata.creator, "\n");
+    add_key_value_template(&mut template,
+        "#BPM", " ", &initial_bpm.to_string(), "\n");
+    add_key_value_template(&mut template,
+        "#RANK", " ", "2", "\n");
+    add_key_value_template(&mut template,
+        "#STAGEFILE", " ", &chart.chartinfo.bg_path, "\n");
+    add_key_value_template(&mut template,
+        "#LNTYPE", " ", "1", "\n");
+    template.push('\n');
+
+    for (index, path) in sample_paths.iter().enumerate() {
+        if path.is_empty() {
+            continue;
+        }
+        if let Some(id) = sample_id(index) {
+            add_key_value_template(&mut template, &format!("#WAV{id}"), " ", path, "\n");
+        }
+    }
+    if let Some(song_id) = &song_id {
+        add_key_value_template(&mut template, &format!("#WAV{song_id}"), " ", &chart.chartinfo.song_path, "\n");
+    }
+    for (index, bpm) in bpm_table.iter().enumerate() {
+        add_key_value_template(&mut template, &format!("#BPM{}", to_base36(index + 1)), " ", &bpm.to_string(), "\n");
+    }
+    for (index, units) in stop_table.iter().enumerate() {
+        add_key_value_template(&mut template, &format!("#STOP{}", to_base36(index + 1)), " ", &units.to_string(), "\n");
+    }
+
+    // measures
+    template.push_str("\n*---------------------- MAIN DATA FIELD\n");
+    let last_beat = objects.iter().map(|(beat, _, _)| *beat).fold(0.0, f32::max);
+    let measures = process_measure_layout(chart, lead_beats, last_beat);
+
+    // (measure, channel) -> (beat in measure, id)
+    let mut measure_channels: BTreeMap<(usize, String), Vec<(f32, String)>> = BTreeMap::new();
+    for (beat, channel, id) in objects {
+        let measure = measures.partition_point(|(start, _)| *start <= beat + CELL_SNAP_MARGIN).saturating_sub(1);
+        let beat_in_measure = (beat - measures[measure].0).max(0.0);
+        measure_channels.entry((measure, channel)).or_default().push((beat_in_measure, id));
+    }
+
+    for (measure, (_, length)) in measures.iter().enumerate() {
+        // measure lengths only last for their own measure
+        if *length != 4.0 {
+            template.push_str(&format!("#{:03}02:{}\n", measure, length / 4.0));
+        }
+
+        for ((_, channel), measure_objects) in measure_channels.range((measure, String::new())..(measure + 1, String::new())) {
+            for line in process_channel_lines(*length, measure_objects) {
+                template.push_str(&format!("#{:03}{}:{}\n", measure, channel, line));
+            }
+        }
+    }
+
+    Ok(template)
+}
+
+
+pub(crate) fn to_bmson(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let key_count = chart.chartinfo.key_count;
+
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "bmson".to_string())));
+    }
+    if key_count == 0 {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidKeyCount(
+            key_count, "1k and up".to_string(), "bmson".to_string())));
+    }
+    let (mode_hint, lanes) = key_count_bmson_lanes(key_count);
+
+    // every sample is a sound channel, notes without a keysound go in a channel with no sample
+    let sample_paths = chart.soundbank.as_ref().map(|soundbank| soundbank.get_sample_paths()).unwrap_or_default();
+    let mut channels: Vec<(String, Vec<BmsonNote>)> = sample_paths.iter()
+        .map(|path| (path.clone(), Vec::new()))
+        .collect();
+    let sample_channel = |sample: usize| Some(sample).filter(|sample| sample_paths.get(*sample).is_some_and(|path| !path.is_empty()));
+    let song_channel = (!chart.chartinfo.song_path.is_empty()).then(|| {
+        channels.push((chart.chartinfo.song_path.clone(), Vec::new()));
+        channels.len() - 1
+    });
+    let silent_channel = channels.len();
+    channels.push((String::new(), Vec::new()));
+
+    // notes can't be before pulse 0, so measures get added in front if the song starts before beat 0
+    let song_beat = beat_at_time(0, chart);
+    let lead_beats = if song_channel.is_some() && song_beat < 0.0 {
+        (-song_beat / 4.0).ceil() * 4.0
+    } else {
+        0.0
+    };
+    let to_pulses = |beat: f32| ((beat + lead_beats) * BMSON_RESOLUTION).round() as i64;
+
+    if let Some(song_channel) = song_channel {
+        channels[song_channel].1.push((0, to_pulses(song_beat), 0));
+    }
+    if let Some(soundbank) = &chart.soundbank {
+        for sound_effect in &soundbank.sound_effects {
+            if let Some(channel) = sample_channel(sound_effect.sample) {
+                channels[channel].1.push((0, to_pulses(beat_at_time(sound_effect.time, chart)), 0));
+            }
+        }
+    }
+
+    // (channel, note) of the long note each column is holding
+    let mut held_notes: Vec<Option<(usize, usize)>> = vec![None; key_count as usize];
+    for (_, beat, keysounds, row) in chart.hitobjects.iter_zipped() {
+        let y = to_pulses(*beat);
+        for (column, key) in row.iter().enumerate() {
+            let channel = keysounds.get_sounds()
+                .get(column)
+                .filter(|keysound| !keysounds.is_empty && keysound.has_custom)
+                .and_then(|keysound| keysound.sample)
+                .and_then(sample_channel)
+                .unwrap_or(silent_channel);
+
+            match key.key_type {
+                KeyType::Normal | KeyType::Lift => channels[channel].1.push((lanes[column], y, 0)),
+                KeyType::SliderStart | KeyType::Roll => {
+                    channels[channel].1.push((lanes[column], y, 0));
+                    held_notes[column] = Some((channel, channels[channel].1.len() - 1));
+                },
+                KeyType::SliderEnd => {
+                    if let Some((channel, note)) = held_notes[column].take() {
+                        let start = &mut channels[channel].1[note];
+                        start.2 = (y - start.1).max(0);
+                    }
+                },
+                // bmson has no mines
+                _ => {},
+            }
+        }
+    }
+
+    let initial_bpm = chart.timing_points.bpms().first().copied().unwrap_or(120.0);
+    let mut bpm_events = Vec::new();
+    let mut stop_events = Vec::new();
+    let mut current_bpm = initial_bpm;
+    let mut is_initial_bpm = true;
+    for (_, beat, change) in chart.timing_points.iter_zipped() {
+        match change.change_type {
+            TimingChangeType::Bpm => {
+                current_bpm = change.value;
+                // the first bpm is already init_bpm
+                if is_initial_bpm {
+                    is_initial_bpm = false;
+                    continue;
+                }
+                bpm_events.push(JsonValue::object(vec![
+                    ("y", to_pulses(*beat).into()),
+                    ("bpm", change.value.into()),
+                ]));
+            },
+            // stops are in pulses and there are no delays, so they stop the chart the same way
+            TimingChangeType::Stop | TimingChangeType::Delay => {
+                let duration = (change.value * current_bpm / 60000.0 * BMSON_RESOLUTION).round() as i64;
+                if duration <= 0 {
+                    continue;
+                }
+                stop_events.push(JsonValue::object(vec![
+                    ("y", to_pulses(*beat).into()),
+                    ("duration", duration.into()),
+                ]));
+            },
+            _ => {},
+        }
+    }
+
+    let last_beat = channels.iter()
+        .flat_map(|(_, notes)| notes)
+        .map(|(_, y, l)| (y + l) as f32 / BMSON_RESOLUTION)
+        .fold(0.0, f32::max);
+    let measures = process_measure_layout(chart, lead_beats, last_beat);
+    let lines: Vec<JsonValue> = measures.iter()
+        .map(|(beat, _)| *beat)
+        .chain(measures.last().map(|(beat, length)| beat + length))
+        .map(|beat| JsonValue::object(vec![("y", ((beat * BMSON_RESOLUTION).round() as i64).into())]))
+        .collect();
+
+    let sound_channels: Vec<JsonValue> = channels.into_iter()
+        .filter(|(_, notes)| !notes.is_empty())
+        .map(|(name, mut notes)| {
+            notes.sort_by_key(|(_, y, _)| *y);
+            let notes: Vec<JsonValue> = notes.into_iter()
+                .map(|(x, y, l)| JsonValue::object(vec![
+                    ("x", x.into()),
+                    ("y", y.into()),
+                    ("l", l.into()),
+                    ("c", false.into()),
+                ]))
+                .collect();
+            JsonValue::object(vec![("name", name.into()), ("notes", notes.into())])
+        })
+        .collect();
+
+    let level = chart.chartinfo.steps_info.as_ref().map_or(0, |steps_info| steps_info.meter);
+    let info = JsonValue::object(vec![
+        ("title", chart.metadata.title.as_str().into()),
+        ("subtitle", "".into()),
+        ("artist", chart.metadata.artist.as_str().into()),
+        ("subartists", vec![chart.metadata.creator.as_str().into()].into()),
+        ("genre", chart.metadata.genre.as_str().into()),
+        ("mode_hint", mode_hint.into()),
+        ("chart_name", chart.chartinfo.difficulty_name.as_str().into()),
+        ("level", level.into()),
+        ("init_bpm", initial_bpm.into()),
+        ("judge_rank", 100.into()),
+        ("total", 100.into()),
+        ("back_image", chart.chartinfo.bg_path.as_str().into()),
+        ("eyecatch_image", "".into()),
+        ("banner_image", "".into()),
+        ("preview_music", "".into()),
+        ("resolution", (BMSON_RESOLUTION as u32).into()),
+    ]);
+
+    let bmson = JsonValue::object(vec![
+        ("version", "1.0.0".into()),
+        ("info", info),
+        ("lines", lines.into()),
+        ("bpm_events", bpm_events.into()),
+        ("stop_events", stop_events.into()),
+        ("sound_channels", sound_channels.into()),
+        ("bga", JsonValue::object(vec![
+            ("bga_header", JsonValue::Array(Vec::new())),
+            ("bga_events", JsonValue::Array(Vec::new())),
+            ("layer_events", JsonValue::Array(Vec::new())),
+            ("poor_events", JsonValue::Array(Vec::new())),
+        ])),
+    ]);
+
+    Ok(bmson.to_string())
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use crate::models;
+use crate::models::common::{GameMode, KeyType, TimingChangeType};
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// each side has 4 lanes, the side that has to hit a section's notes has 0 to 3
+const LANE_COUNT: usize = 4;
+/// every section is 16 steps
+const SECTION_BEATS: f32 = 4.0;
+const SECTION_STEPS: u32 = 16;
+
+/// time, column, sustain length
+type FnfNote = (i32, usize, i32);
+
+fn section_note(time: i32, lane: usize, sustain: i32) -> JsonValue {
+    vec![time.into(), lane.into(), sustain.into()].into()
+}
+
+/// writes 4K charts as the player's side and 8K charts as both sides with the opponent's on the left,
+/// bpm changes only happen on sections so they're moved to the start of the section they're in
+pub(crate) fn to_fnf(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let key_count = chart.chartinfo.key_count as usize;
+
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "Friday Night Funkin'".to_string())));
+    }
+    if key_count != LANE_COUNT && key_count != LANE_COUNT * 2 {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidKeyCount(
+            key_count as u8, "4k and 8k".to_string(), "Friday Night Funkin'".to_string())));
+    }
+    let both_sides = key_count == LANE_COUNT * 2;
+
+    let mut notes: Vec<FnfNote> = Vec::with_capacity(chart.hitobjects.rows.len());
+    for (time, _, _, row) in chart.hitobjects.iter_zipped() {
+        for (column, key) in row.iter().enumerate() {
+            match key.key_type {
+                KeyType::Normal | KeyType::Lift => notes.push((*time, column, 0)),
+                KeyType::SliderStart | KeyType::Roll => {
+                    let sustain = key.slider_end_time().map_or(0, |end_time| (end_time - time).max(0));
+                    notes.push((*time, column, sustain));
+                },
+                // mines and fakes don't exist in Friday Night Funkin'
+                _ => {},
+            }
+        }
+    }
+
+    let bpm_changes: Vec<(i32, f32)> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::Bpm && *view.value > 0.0)
+        .map(|view| (*view.time, *view.value))
+        .collect();
+    let initial_bpm = bpm_changes.first().map_or(120.0, |(_, bpm)| *bpm);
+    let bpm_at = |time: i32| bpm_changes.iter()
+        .rev()
+        .find(|(change_time, _)| *change_time <= time)
+        .map_or(initial_bpm, |(_, bpm)| *bpm);
+
+    let mut sections = Vec::new();
+    let mut section_time: f32 = 0.0;
+    let mut previous_bpm = initial_bpm;
+    let mut remaining_notes = notes.as_slice();
+    while !remaining_notes.is_empty() || sections.is_empty() {
+        let bpm = bpm_at(section_time.round() as i32);
+        section_time += SECTION_BEATS * 60000.0 / bpm;
+        let section_end = section_time.round() as i32;
+        let note_count = remaining_notes.iter().take_while(|(time, ..)| *time < section_end).count();
+        let (section_notes, rest) = remaining_notes.split_at(note_count);
+        remaining_notes = rest;
+
+        // the camera follows whoever has to hit the section, so it's the side with the most notes in it
+        let player_note_count = section_notes.iter().filter(|(_, column, _)| !both_sides || *column >= LANE_COUNT).count();
+        let must_hit = player_note_count * 2 >= section_notes.len();
+        let section_notes: Vec<JsonValue> = section_notes.iter()
+            .map(|(time, column, sustain)| {
+                let is_player = !both_sides || *column >= LANE_COUNT;
+                let lane = column % LANE_COUNT + if is_player == must_hit { 0 } else { LANE_COUNT };
+                section_note(*time, lane, *sustain)
+            })
+            .collect();
+
+        sections.push(JsonValue::object(vec![
+            ("sectionNotes", section_notes.into()),
+            ("lengthInSteps", SECTION_STEPS.into()),
+            ("typeOfSection", 0.into()),
+            ("mustHitSection", must_hit.into()),
+            ("bpm", bpm.into()),
+            ("changeBPM", (bpm != previous_bpm).into()),
+            ("altAnim", false.into()),
+        ]));
+        previous_bpm = bpm;
+    }
+
+    let fnf = JsonValue::object(vec![
+        ("song", JsonValue::object(vec![
+            ("song", chart.metadata.title.as_str().into()),
+            ("notes", sections.into()),
+            ("bpm", initial_bpm.into()),
+            ("needsVoices", false.into()),
+            ("player1", "bf".into()),
+            ("player2", "dad".into()),
+            ("gfVersion", "gf".into()),
+            ("speed", 1.into()),
+            ("validScore", true.into()),
+        ])),
+    ]);
+
+    Ok(fnf.to_string())
+}
\ No newline at end of file
@@ -0,0 +1,209 @@
+use crate::models;
+use crate::models::common::{GameMode, KeyType, TimingChangeType};
+use crate::utils::json::JsonValue;
+use crate::errors;
+
+/// what the beat after a whole beat can be split into, the smallest one a beat fits on gets used
+const BEAT_DENOMINATORS: [u32; 14] = [1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96, 192];
+/// beats a note can be off from a split and still be put on it
+const BEAT_SNAP_MARGIN: f32 = 0.001;
+/// beats a stop or delay gets slowed down over, the smallest split Malody has
+const PAUSE_BEATS: f32 = 1.0 / 192.0;
+/// the column counts Malody's key mode has
+const MIN_KEY_COUNT: u8 = 4;
+const MAX_KEY_COUNT: u8 = 10;
+
+/// beat, column, end beat, keysound as (path, volume)
+type McNote = (f32, usize, Option<f32>, Option<(String, u8)>);
+
+/// [beat, numerator, denominator]
+fn to_malody_beat(beat: f32) -> JsonValue {
+    let whole = beat.floor();
+    let fraction = beat - whole;
+
+    let denominator = BEAT_DENOMINATORS.iter()
+        .copied()
+        .find(|denominator| {
+            let split = fraction * *denominator as f32;
+            (split - split.round()).abs() / (*denominator as f32) < BEAT_SNAP_MARGIN
+        })
+        .unwrap_or(192);
+    let numerator = (fraction * denominator as f32).round() as u32;
+
+    // a fraction that rounds up to a whole beat is the next beat
+    let (whole, numerator, denominator) = if numerator >= denominator {
+        (whole + 1.0, 0, 1)
+    } else {
+        (whole, numerator, denominator)
+    };
+
+    vec![(whole as i64).into(), numerator.into(), denominator.into()].into()
+}
+
+/// Malody has no stops or delays, so each one becomes a bpm slow enough for `PAUSE_BEATS` to take as long as
+/// the pause plus what they took before, starting on a stop and ending on a delay so notes on it are hit on
+/// the right side of it. notes less than `PAUSE_BEATS` after a stop or before a delay end up a little off
+fn fold_pauses_into_bpms(chart: &models::chart::Chart) -> Vec<(f32, f32)> {
+    let bpms: Vec<(f32, f32)> = chart.timing_points.bpm_changes_zipped()
+        .map(|(_, beat, change)| (*beat, change.value))
+        .collect();
+    let bpm_at = |beat: f32| bpms.iter()
+        .rev()
+        .find(|(bpm_beat, _)| *bpm_beat <= beat)
+        .or(bpms.first())
+        .map(|(_, bpm)| *bpm);
+
+    let mut segments = bpms.clone();
+    for (_, beat, change) in chart.timing_points.iter_zipped() {
+        let start = match change.change_type {
+            TimingChangeType::Stop => *beat,
+            // a delay at the very start can't go before it so it starts there like a stop
+            TimingChangeType::Delay if *beat >= PAUSE_BEATS => *beat - PAUSE_BEATS,
+            TimingChangeType::Delay => *beat,
+            _ => continue,
+        };
+        let (Some(bpm), Some(end_bpm)) = (bpm_at(start), bpm_at(start + PAUSE_BEATS)) else {
+            continue;
+        };
+        if change.value <= 0.0 {
+            continue;
+        }
+        let pause_length = change.value + PAUSE_BEATS * 60000.0 / bpm;
+        segments.push((start, PAUSE_BEATS * 60000.0 / pause_length));
+        segments.push((start + PAUSE_BEATS, end_bpm));
+    }
+
+    // the pauses come after the bpms they replace on the same beat
+    segments.sort_by(|a, b| a.0.total_cmp(&b.0));
+    segments.dedup_by(|later, earlier| {
+        let is_same_beat = (later.0 - earlier.0).abs() < PAUSE_BEATS / 2.0;
+        if is_same_beat {
+            *earlier = *later;
+        }
+        is_same_beat
+    });
+    segments
+}
+
+pub(crate) fn to_mc(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let key_count = chart.chartinfo.key_count;
+
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "Malody".to_string())));
+    }
+    if !(MIN_KEY_COUNT..=MAX_KEY_COUNT).contains(&key_count) {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidKeyCount(
+            key_count, format!("{MIN_KEY_COUNT}k to {MAX_KEY_COUNT}k"), "Malody".to_string())));
+    }
+
+    let sample_paths = chart.soundbank.as_ref().map(|soundbank| soundbank.get_sample_paths()).unwrap_or_default();
+
+    // the first bpm is placed on the first beat so everything before it has a bpm too
+    let mut time = Vec::new();
+    for (beat, bpm) in fold_pauses_into_bpms(chart) {
+        let beat = if time.is_empty() { beat.min(0.0) } else { beat };
+        time.push(JsonValue::object(vec![
+            ("beat", to_malody_beat(beat)),
+            ("bpm", bpm.into()),
+        ]));
+    }
+    let effect: Vec<JsonValue> = chart.timing_points.sv_changes_zipped()
+        .map(|(_, beat, change)| JsonValue::object(vec![
+            ("beat", to_malody_beat(*beat)),
+            ("scroll", change.value.into()),
+        ]))
+        .collect();
+
+    let mut notes: Vec<McNote> = Vec::with_capacity(chart.hitobjects.rows.len());
+    // the note of the long note each column is holding
+    let mut held_notes: Vec<Option<usize>> = vec![None; key_count as usize];
+    for (_, beat, keysounds, row) in chart.hitobjects.iter_zipped() {
+        for (column, key) in row.iter().enumerate() {
+            let key_sound = keysounds.get_sounds()
+                .get(column)
+                .filter(|keysound| !keysounds.is_empty && keysound.has_custom)
+                .and_then(|keysound| {
+                    let path = sample_paths.get(keysound.sample?).filter(|path| !path.is_empty())?;
+                    Some((path.clone(), keysound.volume))
+                });
+
+            match key.key_type {
+                KeyType::Normal | KeyType::Lift => notes.push((*beat, column, None, key_sound)),
+                KeyType::SliderStart | KeyType::Roll => {
+                    notes.push((*beat, column, None, key_sound));
+                    held_notes[column] = Some(notes.len() - 1);
+                },
+                KeyType::SliderEnd => {
+                    if let Some(note) = held_notes[column].take() {
+                        notes[note].2 = Some(*beat);
+                    }
+                },
+                // mines and fakes don't exist in Malody
+                _ => {},
+            }
+        }
+    }
+
+    let mut note: Vec<JsonValue> = notes.into_iter()
+        .map(|(beat, column, end_beat, key_sound)| {
+            let mut entries = vec![("beat", to_malody_beat(beat))];
+            if let Some(end_beat) = end_beat {
+                entries.push(("endbeat", to_malody_beat(end_beat)));
+            }
+            entries.push(("column", column.into()));
+            if let Some((path, volume)) = key_sound {
+                entries.push(("sound", path.into()));
+                entries.push(("vol", (volume as u32).into()));
+            }
+            JsonValue::object(entries)
+        })
+        .collect();
+    // the song goes last, its offset is where the first beat is in it
+    note.push(JsonValue::object(vec![
+        ("beat", to_malody_beat(0.0)),
+        ("sound", chart.chartinfo.song_path.as_str().into()),
+        ("vol", 100.into()),
+        ("offset", chart.chartinfo.audio_offset.into()),
+        ("type", 1.into()),
+    ]));
+
+    let meta = JsonValue::object(vec![
+        ("$ver", 0.into()),
+        ("creator", chart.metadata.creator.as_str().into()),
+        ("background", chart.chartinfo.bg_path.as_str().into()),
+        ("version", chart.chartinfo.difficulty_name.as_str().into()),
+        ("preview", chart.chartinfo.preview_time.into()),
+        ("id", 0.into()),
+        ("mode", 0.into()),
+        ("time", 0.into()),
+        ("song", JsonValue::object(vec![
+            ("title", chart.metadata.title.as_str().into()),
+            ("artist", chart.metadata.artist.as_str().into()),
+            ("id", 0.into()),
+            ("titleorg", chart.metadata.alt_title.as_str().into()),
+            ("artistorg", chart.metadata.alt_artist.as_str().into()),
+        ])),
+        ("mode_ext", JsonValue::object(vec![
+            ("column", (key_count as u32).into()),
+            ("bar_begin", 0.into()),
+        ])),
+    ]);
+
+    let mc = JsonValue::object(vec![
+        ("meta", meta),
+        ("time", time.into()),
+        ("effect", effect.into()),
+        ("note", note.into()),
+        ("extra", JsonValue::object(vec![
+            ("test", JsonValue::object(vec![
+                ("divide", 4.into()),
+                ("speed", 100.into()),
+                ("save", 0.into()),
+                ("lock", 0.into()),
+                ("edit_mode", 0.into()),
+            ])),
+        ])),
+    ]);
+
+    Ok(mc.to_string())
+}
\ No newline at end of file
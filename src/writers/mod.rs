@@ -1,3 +1,10 @@
 pub mod osu;
 pub mod stepmania;
-pub mod quaver;
\ No newline at end of file
+pub mod quaver;
+pub mod bms;
+pub mod malody;
+pub mod fnf;
+pub mod beat_saber;
+pub mod tja;
+pub mod osz;
+pub mod qp;
\ No newline at end of file
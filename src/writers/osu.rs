@@ -1,23 +1,24 @@
 use crate::models;
 use crate::models::common::{
-    Row, TimingChangeType, KeyType
+    Row, KeyType, TimingChangeType, GameMode
 };
 use crate::models::sound::SoundBank;
 use models::sound::{KeySoundRow, KeySound, HitSoundType};
 use crate::utils::string::add_key_value_template;
 use crate::utils::time::find_sliderend_time;
-#[allow(unused)]
 use crate::errors;
 
+const SLIDER_MULTIPLIER: f32 = 1.4;
+
 #[inline(always)]
 fn bpm_to_beatlength(bpm: &f32) -> f32 {
     60000.0 / bpm
 }
 
+/// osu! only takes svs from 0.01x to 10x
 #[inline(always)]
 fn multiplier_to_beatlength(multiplier: &f32) -> f32 {
-    if *multiplier == 0.0 { return -10000.0 }
-    -100.0 / multiplier.abs()
+    -100.0 / multiplier.abs().clamp(0.01, 10.0)
 }
 
 #[inline(always)]
@@ -33,13 +34,65 @@ fn generate_slider(coords: i32, time: i32, hitsound_str: &str, slider_end_time:
     format!("{},192,{},128,{},{}:0:0:0:{}:{}\n", coords, time, hitsound_str, slider_end_time, volume, custom_sample)
 }
 
+fn generate_drumroll(time: i32, hitsound_str: &str, length: f32, volume: u8, custom_sample: &str) -> String {
+    format!("256,192,{},2,{},L|512:192,1,{:.2},0|0,0:0|0:0,0:0:0:{}:{}\n", time, hitsound_str, length, volume, custom_sample)
+}
+
+fn generate_spinner(time: i32, end_time: i32, volume: u8, custom_sample: &str) -> String {
+    format!("256,192,{},12,0,{},0:0:0:{}:{}\n", time, end_time, volume, custom_sample)
+}
+
+/// pixel length a slider needs to last `duration` ms, from the bpm and sv at its start time
+fn slider_length(time: i32, duration: i32, timing_points: &models::timing_points::TimingPoints) -> f32 {
+    let (bpm, multiplier) = timing_points.bpm_and_sv_at(time);
+    let multiplier = multiplier.clamp(0.1, 10.0);
+    duration as f32 / bpm_to_beatlength(&bpm) * SLIDER_MULTIPLIER * 100.0 * multiplier
+}
+
+fn process_taiko_hitobjects(template: &mut String, chart: &models::chart::Chart) {
+    let soundbank = chart.soundbank.clone().unwrap_or(SoundBank::new());
+    for (time, _, keysounds, row) in chart.hitobjects.iter_zipped() {
+        for (i, key) in row.iter().enumerate() {
+            let (volume, custom_sample) = match keysounds.get_sounds().get(i) {
+                Some(keysound) if !keysounds.is_empty && keysound.has_custom => (
+                    if keysound.volume >= 100 { 0 } else { keysound.volume },
+                    soundbank.get_sound_sample(keysound.sample.unwrap_or(0)).unwrap_or_default(),
+                ),
+                _ => (0, String::new()),
+            };
+
+            // kats are written with a clap and big notes with a finish
+            let end_time = key.slider_end_time().unwrap_or(*time);
+            match key.key_type {
+                KeyType::Don => template.push_str(&generate_normal(256, *time, "0", volume, &custom_sample)),
+                KeyType::Kat => template.push_str(&generate_normal(256, *time, "8", volume, &custom_sample)),
+                KeyType::BigDon => template.push_str(&generate_normal(256, *time, "4", volume, &custom_sample)),
+                KeyType::BigKat => template.push_str(&generate_normal(256, *time, "12", volume, &custom_sample)),
+                KeyType::Drumroll | KeyType::BigDrumroll => {
+                    let hitsound_str = if key.key_type == KeyType::BigDrumroll { "4" } else { "0" };
+                    let length = slider_length(*time, end_time - *time, &chart.timing_points);
+                    template.push_str(&generate_drumroll(*time, hitsound_str, length, volume, &custom_sample));
+                },
+                KeyType::Spinner => template.push_str(&generate_spinner(*time, end_time, volume, &custom_sample)),
+                _ => continue,
+            }
+        }
+    }
+}
+
 fn generate_sb_sample(time: i32, sample_path: &str, volume: u8) -> String {
     format!("Sample,{},0,\"{}\",{}", time, sample_path, volume)
 }
 
+#[allow(clippy::single_match, clippy::needless_borrow)]
 pub(crate) fn to_osu(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
     let mut template = String::from("osu file format v14\n");
     let key_count = chart.chartinfo.key_count;
+    let mode = match chart.chartinfo.game_mode {
+        GameMode::Mania => 3,
+        GameMode::Taiko => 1,
+        game_mode => return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(game_mode, "osu!".to_string()))),
+    };
 
     // General
     template.push_str("\n[General]\n");
@@ -51,9 +104,10 @@ pub(crate) fn to_osu(chart: &models::chart::Chart) -> Result<String, Box<dyn std
         "PreviewTime", ": ", &chart.chartinfo.preview_time.to_string(), "\n");
     template.push_str("Countdown: 0
 SampleSet: Soft
-StackLeniency: 0.7
-Mode: 3
-LetterboxInBreaks: 0
+StackLeniency: 0.7\n");
+    add_key_value_template(&mut template,
+        "Mode", ": ", &mode.to_string(), "\n");
+    template.push_str("LetterboxInBreaks: 0
 SpecialStyle: 0
 WidescreenStoryboard: 1");
     template.push('\n');
@@ -93,12 +147,15 @@ TimelineZoom: 1
     template.push_str("\n[Difficulty]\n");
     add_key_value_template(&mut template,
         "HPDrainRate", ": ", "8.5", "\n");
+    // circle size is the key count in mania and does nothing in taiko
+    let circle_size = if chart.chartinfo.game_mode == GameMode::Taiko { 5 } else { key_count };
     add_key_value_template(&mut template,
-        "CircleSize", ": ", &key_count.to_string(), "\n");
+        "CircleSize", ": ", &circle_size.to_string(), "\n");
     template.push_str("OverallDifficulty:8
-ApproachRate:5
-SliderMultiplier:1.4
-SliderTickRate:1");
+ApproachRate:5\n");
+    add_key_value_template(&mut template,
+        "SliderMultiplier", ":", &SLIDER_MULTIPLIER.to_string(), "\n");
+    template.push_str("SliderTickRate:1");
     template.push('\n');
 
     // Events
@@ -129,25 +186,41 @@ SliderTickRate:1");
 
     // process timing points
     template.push_str("\n[TimingPoints]\n");
-    for timing_point in chart.timing_points.iter_views() {
-        match timing_point.change_type {
-            TimingChangeType::Bpm => {
-                template.push_str(&format!("{},{},4,1,0,100,1,0\n",
-                    timing_point.time,
-                    bpm_to_beatlength(timing_point.value),
-                ));
-            },
-            TimingChangeType::Sv => {
-                template.push_str(&format!("{},{},4,1,0,100,0,0\n",
-                    timing_point.time,
-                    multiplier_to_beatlength(timing_point.value),
-                ));
-            },
-            _ => {}
-        }
+    let mut timing_lines: Vec<(i32, bool, String)> = Vec::with_capacity(chart.timing_points.times.len());
+    let time_signatures: Vec<(i32, u32)> = chart.timing_points.iter_views()
+        .filter(|timing_point| timing_point.change_type == TimingChangeType::TimeSignature)
+        // osu! meters are whole beats and there's at least one of them
+        .map(|timing_point| (*timing_point.time, timing_point.value.round().max(1.0) as u32))
+        .collect();
+    for timing_point in chart.timing_points.bpm_changes_views() {
+        let meter = time_signatures.iter()
+            .rev()
+            .find(|(time, _)| time <= timing_point.time)
+            .map_or(4, |(_, meter)| *meter);
+        timing_lines.push((*timing_point.time, true, format!("{},{},{},1,0,100,1,0\n",
+            timing_point.time,
+            bpm_to_beatlength(timing_point.value),
+            meter,
+        )));
+    }
+    for (time, multiplier) in chart.timing_points.sv_changes_with_stops() {
+        timing_lines.push((time, false, format!("{},{},4,1,0,100,0,0\n",
+            time,
+            multiplier_to_beatlength(&multiplier),
+        )));
+    }
+    // uninherited points have to come before inherited ones on the same time
+    timing_lines.sort_by_key(|(time, uninherited, _)| (*time, !*uninherited));
+    for (_, _, line) in timing_lines {
+        template.push_str(&line);
     }
 
     template.push_str("\n[HitObjects]\n");
+    if chart.chartinfo.game_mode == GameMode::Taiko {
+        process_taiko_hitobjects(&mut template, chart);
+        return Ok(template);
+    }
+
     let soundbank = chart.soundbank.clone().unwrap_or(SoundBank::new());
     let hitobjects: Vec<(&i32, &f32, &KeySoundRow, &Row)> = chart.hitobjects.iter_zipped().collect();
     template.reserve(hitobjects.len() * key_count as usize);
@@ -182,10 +255,12 @@ SliderTickRate:1");
             };
             
             match key.key_type {
-                KeyType::Normal => {
+                // lifts don't exist here so they're hit like normal notes
+                KeyType::Normal | KeyType::Lift => {
                     template.push_str(&generate_normal(coords.into(), **time, &hitsound_str, volume, &custom_sample));
                 },
-                KeyType::SliderStart => {
+                // and rolls are held like normal sliders
+                KeyType::SliderStart | KeyType::Roll => {
                     let slider_end_time = if let Some(time) = key.slider_end_time() {
                         time
                     } else {
@@ -0,0 +1,52 @@
+use crate::models;
+use crate::models::mapset::AssetFile;
+use crate::parsers::osz::normalize_path;
+use crate::writers::osu::to_osu;
+use crate::utils::zip::write_zip;
+
+/// characters that can't be in a file name on Windows
+const INVALID_FILENAME_CHARS: [char; 9] = ['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
+
+/// leaves out characters that can't be in a file name
+pub(crate) fn sanitize_filename(filename: &str) -> String {
+    filename.chars().filter(|c| !INVALID_FILENAME_CHARS.contains(c)).collect()
+}
+
+/// zips charts, given as file names without their extension and contents, with the files they use,
+/// charts with the same name get a number after them and assets with the same path are only packed once
+pub(crate) fn pack_mapset(charts: Vec<(String, String)>, extension: &str, assets: &[AssetFile]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut files: Vec<(String, Vec<u8>)> = Vec::with_capacity(charts.len() + assets.len());
+    let is_taken = |files: &[(String, Vec<u8>)], name: &str| files.iter().any(|(taken, _)| taken.eq_ignore_ascii_case(name));
+
+    for (filename, contents) in charts {
+        let mut name = format!("{filename}{extension}");
+        let mut number = 2;
+        while is_taken(&files, &name) {
+            name = format!("{filename} ({number}){extension}");
+            number += 1;
+        }
+        files.push((name, contents.into_bytes()));
+    }
+
+    for asset in assets {
+        let path = normalize_path(&asset.path);
+        if !path.is_empty() && !is_taken(&files, &path) {
+            files.push((path, asset.data.clone()));
+        }
+    }
+
+    let files: Vec<(&str, &[u8])> = files.iter().map(|(name, data)| (name.as_str(), data.as_slice())).collect();
+    Ok(write_zip(&files)?)
+}
+
+/// packs charts as .osu files named `Artist - Title (Creator) [Difficulty]` like osu! does, along with the files they use
+pub(crate) fn to_osz(charts: &[models::chart::Chart], assets: &[AssetFile]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut files = Vec::with_capacity(charts.len());
+    for chart in charts {
+        let filename = sanitize_filename(&format!("{} - {} ({}) [{}]",
+            chart.metadata.artist, chart.metadata.title, chart.metadata.creator, chart.chartinfo.difficulty_name));
+        files.push((filename, to_osu(chart)?));
+    }
+
+    pack_mapset(files, ".osu", assets)
+}
@@ -0,0 +1,63 @@
+use crate::models;
+use crate::models::mapset::AssetFile;
+use crate::models::sound::{SoundBank, SoundEffect};
+use crate::parsers::osz::normalize_path;
+use crate::writers::osz::{pack_mapset, sanitize_filename};
+use crate::writers::quaver::to_qua;
+
+/// gives every chart the same `CustomAudioSamples` so a sample is listed once in the same spot in every difficulty,
+/// keysounds and sound effects are moved to where their sample ended up
+fn share_samples(charts: &[models::chart::Chart]) -> Vec<models::chart::Chart> {
+    let mut shared = SoundBank::new();
+    // where each chart's samples are in the shared soundbank
+    let sample_indices: Vec<Vec<Option<usize>>> = charts.iter()
+        .map(|chart| chart.soundbank.iter()
+            .flat_map(SoundBank::get_sample_paths)
+            .map(|path| {
+                let path = normalize_path(&path);
+                (!path.is_empty()).then(|| shared.add_sound_sample(path))
+            })
+            .collect())
+        .collect();
+
+    charts.iter().zip(sample_indices).map(|(chart, sample_indices)| {
+        let shared_index = |sample: usize| sample_indices.get(sample).copied().flatten();
+        let mut chart = chart.clone();
+
+        let mut soundbank = shared.clone();
+        if let Some(own_soundbank) = &chart.soundbank {
+            soundbank.audio_tracks = own_soundbank.audio_tracks.clone();
+            for sound_effect in &own_soundbank.sound_effects {
+                if let Some(sample) = shared_index(sound_effect.sample) {
+                    soundbank.add_sound_effect(SoundEffect::new(sound_effect.time, sound_effect.volume, sample));
+                }
+            }
+        }
+        for keysounds in chart.hitobjects.keysounds.iter_mut() {
+            for column in 0..keysounds.len() {
+                let keysound = &mut keysounds[column];
+                if keysound.has_custom {
+                    keysound.sample = keysound.sample.and_then(shared_index);
+                    keysound.has_custom = keysound.sample.is_some();
+                }
+            }
+        }
+
+        if chart.soundbank.is_some() || !soundbank.is_empty() {
+            chart.soundbank = Some(soundbank);
+        }
+        chart
+    }).collect()
+}
+
+/// packs charts as .qua files named `Artist - Title [Difficulty]` along with the files they use into a Quaver mapset (.qp)
+pub(crate) fn to_qp(charts: &[models::chart::Chart], assets: &[AssetFile]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut files = Vec::with_capacity(charts.len());
+    for chart in share_samples(charts) {
+        let filename = sanitize_filename(&format!("{} - {} [{}]",
+            chart.metadata.artist, chart.metadata.title, chart.chartinfo.difficulty_name));
+        files.push((filename, to_qua(&chart)?));
+    }
+
+    pack_mapset(files, ".qua", assets)
+}
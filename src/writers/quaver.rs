@@ -67,6 +67,10 @@ pub(crate) fn to_qua(chart: &models::chart::Chart) -> Result<String, Box<dyn std
     let mut template = String::new();
     let key_count = chart.chartinfo.key_count;
 
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err( Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "Quaver".to_string()) ) );
+    }
+
     // metadata
     add_key_value_template(&mut template,
         "AudioFile", ": ", &chart.chartinfo.song_path, "\n");
@@ -156,12 +160,13 @@ pub(crate) fn to_qua(chart: &models::chart::Chart) -> Result<String, Box<dyn std
 
     // process timing points
     template.push_str("SliderVelocities:");
-    if chart.timing_points.is_sv_empty() {
+    let sv_changes = chart.timing_points.sv_changes_with_stops();
+    if sv_changes.is_empty() {
         template.push_str(" []\n");
     } else {
         template.push('\n');
-        for (time, _, change) in chart.timing_points.sv_changes_zipped() {
-            template.push_str(&generate_sv(*time, change.value));
+        for (time, multiplier) in sv_changes {
+            template.push_str(&generate_sv(time, multiplier));
             template.push('\n');
         }
     }
@@ -183,11 +188,13 @@ pub(crate) fn to_qua(chart: &models::chart::Chart) -> Result<String, Box<dyn std
                     keysounds[i]
                 };
                 match key.key_type {
-                    KeyType::Normal => {
+                    // lifts don't exist here so they're hit like normal notes
+                    KeyType::Normal | KeyType::Lift => {
                         template.push_str(&generate_hitobject(**time, None, i, keysound));
                         template.push('\n');
                     },
-                    KeyType::SliderStart => {
+                    // and rolls are held like normal sliders
+                    KeyType::SliderStart | KeyType::Roll => {
                         let slider_end_time = if let Some(time) = key.slider_end_time() {
                             time
                         } else {
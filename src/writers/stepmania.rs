@@ -1,20 +1,28 @@
 use crate::models;
+use crate::models::chartinfo::StepsInfo;
 use crate::models::common::{
     ChartDefaults,
+    GameMode,
     Key,
     KeyType,
     Measure,
+    TimingChangeType,
 };
+use crate::models::timing_points::{TimeSignature, TimingChange};
 use crate::utils::math::approx_eq;
 use crate::utils::string::add_key_value_template;
 use crate::utils::time::to_seconds;
 use crate::utils::rhythm::{
     MeasureRange,
-    snap_to_nearest_note_type_normed,
+    BEAT_DENOMS,
 };
-#[allow(unused)]
 use crate::errors;
 
+/// svs at or below this are what osu! and Quaver use as stops
+const SV_STOP_THRESHOLD: f32 = 0.01;
+/// how many rows after an sv stop have to stay on the grid for it to become a real stop
+const SV_STOP_CHECKED_ROWS: usize = 16;
+
 #[inline]
 fn sm_row_to_str(row: &[Key]) -> String {
     let mut result = String::with_capacity(row.len());
@@ -27,11 +35,22 @@ fn sm_row_to_str(row: &[Key]) -> String {
             KeyType::Mine => 'M',
             KeyType::Fake => 'F',
             KeyType::Unknown => '0',
+            KeyType::Roll => '4',
+            KeyType::Lift => 'L',
+            _ => '0',
         });
     }
     result
 }
 
+/// beats a row can be off from the grid and still be considered on it
+const ROW_SNAP_MARGIN: f32 = 0.01;
+
+#[inline]
+fn measure_index(beat: f32) -> u32 {
+    ((beat * 24.0).round() / 96.0) as u32
+}
+
 #[inline]
 fn pad_measure(rows: &models::hitobjects::HitObjects, range: &MeasureRange) -> Measure {
     let key_count = rows.iter_zipped().next().map_or(0, |row| row.3.len());
@@ -48,86 +67,57 @@ fn pad_measure(rows: &models::hitobjects::HitObjects, range: &MeasureRange) -> M
     if measure.is_empty() {
         return vec![vec![Key::empty(); key_count]; 4];
     }
-    
 
-    let measure_start_beat = measure[0].1;
-    let normalized_measure: Vec<_> = measure.iter()
-        .map(|row| (row.0, row.1 - measure_start_beat, row.2, row.3))
+    let measure_start_beat = measure_index(*measure[0].1) as f32 * 4.0;
+    let normalized_beats: Vec<f32> = measure.iter()
+        .map(|row| row.1 - measure_start_beat)
         .collect();
 
-    let mut minimal_beat_diff = 4.0;
-    for i in 1..normalized_measure.len() {
-        let diff = normalized_measure[i].1 - normalized_measure[i-1].1;
-        if diff < minimal_beat_diff && diff > 1e-5 {
-            minimal_beat_diff = diff;
-        }
-    }
+    // the least amount of rows that still puts every row on the grid
+    let row_count = BEAT_DENOMS.iter()
+        .copied()
+        .find(|&denom| {
+            let beats_per_row = 4.0 / denom as f32;
+            normalized_beats.iter()
+                .all(|beat| approx_eq(*beat, (beat / beats_per_row).round() * beats_per_row, ROW_SNAP_MARGIN))
+        })
+        .unwrap_or(BEAT_DENOMS[BEAT_DENOMS.len() - 1]);
 
-    let snapped_beat_diff = snap_to_nearest_note_type_normed(minimal_beat_diff);
-    let row_count = (4.0 / snapped_beat_diff).round() as usize;
-    
-    let expected_beats: Vec<f32> = (0..row_count)
-        .map(|k| k as f32 * snapped_beat_diff)
-        .collect();
-
-    let mut padded_measure = Vec::with_capacity(row_count);
-    let mut measure_iter = normalized_measure.iter().peekable();
+    let mut padded_measure = vec![vec![Key::empty(); key_count]; row_count];
 
-    for &expected_beat in &expected_beats {
-        if let Some(row) = measure_iter.peek() {
-            if approx_eq(row.1, expected_beat, 0.15) {
-                padded_measure.push(row.3.clone());
-                measure_iter.next();
-                continue;
+    for (row, beat) in measure.iter().zip(normalized_beats) {
+        let row_index = ((beat * row_count as f32 / 4.0).round().max(0.0) as usize).min(row_count - 1);
+        // rows that end up on the same line get merged
+        for (padded_key, key) in padded_measure[row_index].iter_mut().zip(row.3.iter()) {
+            if key.key_type != KeyType::Empty {
+                *padded_key = *key;
             }
         }
-        padded_measure.push(vec![Key::empty(); key_count]);
     }
 
     padded_measure
 }
 
-pub(crate) fn to_sm(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
-    let mut template = String::new();
-    let mut bpm_template = String::new();
-    let mut notes_template = String::new();
-
-    let mut prev_beat = 0.0;
+fn process_measures(chart: &models::chart::Chart) -> Vec<Measure> {
     let mut prev_measure = 0;
-    let mut prev_measure_beat = 0.0;
     let mut current_measure_index = 0;
 
-    let scale_factor = 24.0;
-    let beats_per_measure = 4.0;
-    let beats_per_measure_scaled = scale_factor * beats_per_measure;
-
     let mut measure_indices: Vec<MeasureRange>  = Vec::with_capacity(85);
     let mut padded_measures: Vec<Measure> = Vec::with_capacity(chart.hitobjects.times.len() * 2);
-    let bpms: Vec<_> = chart.timing_points.bpm_changes_views().collect();
 
-    // get measures
     for (row_index, beat) in chart.hitobjects.beats.iter().enumerate() {
-        
-        let beat_scaled = (beat * scale_factor).round();
-        let measure = (beat_scaled / beats_per_measure_scaled) as u32;
+        let measure = measure_index(*beat);
 
         if measure != prev_measure {
             measure_indices.push(MeasureRange(current_measure_index, row_index, false));
 
-            if beat - prev_measure_beat > 5.0 {
-                let empty_measure_count = ((beat - prev_beat) / 4.0).floor() as usize;
-                
-                for _ in 0..empty_measure_count {
-                    measure_indices.push(MeasureRange(current_measure_index, row_index, true));
-                    current_measure_index = row_index; 
-                }
+            for _ in prev_measure + 1..measure {
+                measure_indices.push(MeasureRange(row_index, row_index, true));
             }
             
             current_measure_index = row_index;
             prev_measure = measure;
-            prev_measure_beat = *beat;
         }
-        prev_beat = *beat;
     }
     measure_indices.push(MeasureRange(current_measure_index, chart.hitobjects.rows.len(), false));
 
@@ -135,31 +125,190 @@ pub(crate) fn to_sm(chart: &models::chart::Chart) -> Result<String, Box<dyn std:
         padded_measures.push(pad_measure(&chart.hitobjects, &measure_range));
     }
 
-    // process bpms
-    let last_bpm_beat = bpms.last().unwrap().beat;
+    padded_measures
+}
+
+fn process_bpms(chart: &models::chart::Chart) -> String {
+    let mut bpm_template = String::new();
+    let bpms: Vec<_> = chart.timing_points.bpm_changes_views().collect();
+
+    let last_bpm_beat = bpms.last().map_or(0.0, |bpm| *bpm.beat);
     for bpm in bpms {
-        if bpm.beat < last_bpm_beat {
+        if *bpm.beat < last_bpm_beat {
             add_key_value_template(&mut bpm_template, &bpm.beat.to_string(), "=", &bpm.value.to_string(), ",\n");
         } else {
             add_key_value_template(&mut bpm_template, &bpm.beat.to_string(), "=", &bpm.value.to_string(), "\n");
         }
     }
 
-    // process notes
-    let last_measure_index = padded_measures.len() - 1;
-    notes_template.push('\n');
-    notes_template.push_str("   ");
-    notes_template.push_str("dance-single:\n"); // TODO: support for multikey later
-    notes_template.push_str("   ");
-    notes_template.push_str(&chart.metadata.creator);
-    notes_template.push_str(":\n");
-    notes_template.push_str("   ");
-    notes_template.push_str("Edit"); // TODO: maybe process difficulty value and choose an sm difficulty??
-    notes_template.push_str(":\n");
-    notes_template.push_str("   ");
-    notes_template.push_str("1:\n");
-    notes_template.push_str("   ");
-    notes_template.push_str("0.000,0.000,0.000,0.000,0.000:\n");
+    bpm_template
+}
+
+/// stepmania puts timing segments on rows (48 per beat), so their beats are snapped to those like the notes are
+#[inline]
+fn snap_to_row(beat: f32) -> f32 {
+    (beat * 48.0).round() / 48.0
+}
+
+/// beat=value for every change of a type, one per line
+fn process_timing_segments<F>(chart: &models::chart::Chart, change_type: TimingChangeType, format_value: F) -> String
+where
+    F: Fn(f32) -> String,
+{
+    chart.timing_points.iter_views()
+        .filter(|view| view.change_type == change_type)
+        .map(|view| format!("{:.3}={}", snap_to_row(*view.beat), format_value(*view.value)))
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+#[inline]
+fn process_stops(chart: &models::chart::Chart) -> String {
+    process_timing_segments(chart, TimingChangeType::Stop, |duration| format!("{:.3}", to_seconds(duration)))
+}
+
+#[inline]
+fn process_delays(chart: &models::chart::Chart) -> String {
+    process_timing_segments(chart, TimingChangeType::Delay, |duration| format!("{:.3}", to_seconds(duration)))
+}
+
+#[inline]
+fn process_warps(chart: &models::chart::Chart) -> String {
+    process_timing_segments(chart, TimingChangeType::Warp, |length| format!("{:.3}", length))
+}
+
+/// beat=numerator=denominator, time signatures keep the denominator they were written with
+fn process_time_signatures(chart: &models::chart::Chart) -> String {
+    let time_signatures = chart.timing_points.iter_zipped()
+        .filter(|(_, _, change)| change.change_type == TimingChangeType::TimeSignature)
+        .map(|(_, beat, change)| {
+            let time_signature = change.time_signature.unwrap_or_else(|| TimeSignature::from_beats(change.value));
+            format!("{:.3}={}={}", snap_to_row(*beat), time_signature.numerator, time_signature.denominator)
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+    if time_signatures.is_empty() { "0.000=4=4".to_string() } else { time_signatures }
+}
+
+fn process_tick_counts(chart: &models::chart::Chart) -> String {
+    let tick_counts = process_timing_segments(chart, TimingChangeType::TickCount, |ticks| format!("{}", ticks as u32));
+    if tick_counts.is_empty() { "0.000=4".to_string() } else { tick_counts }
+}
+
+/// svs are what .ssc calls scrolls, there's no #SPEEDS since speeds change how fast every note on screen moves
+/// and charts don't have those apart from their svs
+fn process_scrolls(chart: &models::chart::Chart) -> String {
+    let scrolls = process_timing_segments(chart, TimingChangeType::Sv, |multiplier| format!("{:.3}", multiplier));
+    if scrolls.is_empty() { "0.000=1.000".to_string() } else { scrolls }
+}
+
+#[inline]
+fn is_on_grid(beat: f32) -> bool {
+    // the 192nd grid fits everything, so it doesn't tell anything
+    BEAT_DENOMS[..BEAT_DENOMS.len() - 1].iter()
+        .any(|&denom| {
+            let beats_per_row = 4.0 / denom as f32;
+            approx_eq(beat, (beat / beats_per_row).round() * beats_per_row, ROW_SNAP_MARGIN)
+        })
+}
+
+/// osu! and Quaver fake stops with a (near) 0x sv that nothing has to be hit during,
+/// those can be turned into real stops (or delays) and everything after them is moved back by the beats they took.
+/// svs that would push the notes after them off the grid are only visual and are left alone.
+/// returns None if the chart doesn't have any
+fn convert_sv_stops(chart: &models::chart::Chart) -> Option<models::chart::Chart> {
+    let timing_points = &chart.timing_points;
+    let hit_times = &chart.hitobjects.times;
+    let hit_beats = &chart.hitobjects.beats;
+
+    let mut bpm_changes: Vec<(i32, f32)> = timing_points.bpm_changes_zipped()
+        .map(|(time, _, change)| (*time, change.value))
+        .collect();
+    bpm_changes.sort_by_key(|(time, _)| *time);
+    let mut scroll_change_times: Vec<i32> = timing_points.iter_zipped()
+        .filter(|(_, _, change)| matches!(change.change_type, TimingChangeType::Bpm | TimingChangeType::Sv))
+        .map(|(time, _, _)| *time)
+        .collect();
+    scroll_change_times.sort_unstable();
+
+    // index of the sv, time, duration, beats it takes, stop or delay
+    let mut candidates: Vec<(usize, i32, i32, f32, TimingChangeType)> = Vec::new();
+    for (i, (time, _, change)) in timing_points.iter_zipped().enumerate() {
+        if change.change_type != TimingChangeType::Sv || change.value > SV_STOP_THRESHOLD {
+            continue;
+        }
+
+        let Some(&end_time) = scroll_change_times.get(scroll_change_times.partition_point(|t| t <= time)) else {
+            continue;
+        };
+        let first_hit_after = hit_times.partition_point(|t| t <= time);
+        if hit_times.get(first_hit_after).is_some_and(|hit_time| *hit_time < end_time) {
+            continue;
+        }
+        // a note right where it ends gets moved onto where it starts, so it has to be hit after the pause
+        let change_type = if hit_times.get(first_hit_after) == Some(&end_time) {
+            TimingChangeType::Delay
+        } else {
+            TimingChangeType::Stop
+        };
+
+        let bpm = bpm_changes.iter()
+            .rev()
+            .find(|(bpm_time, _)| bpm_time <= time)
+            .or(bpm_changes.first())
+            .map_or(0.0, |(_, bpm)| *bpm);
+        let duration = end_time - time;
+        candidates.push((i, *time, duration, duration as f32 * bpm / 60000.0, change_type));
+    }
+    candidates.sort_by_key(|(_, time, _, _, _)| *time);
+
+    // time, beats it takes
+    let mut stops_by_time: Vec<(i32, f32)> = Vec::with_capacity(candidates.len());
+    let mut sv_stops: Vec<(usize, i32, TimingChangeType)> = Vec::with_capacity(candidates.len());
+    let mut total_beat_shift = 0.0;
+    for (i, time, duration, beats, change_type) in candidates {
+        let first_hit_after = hit_times.partition_point(|t| *t <= time);
+        let hit_beats_after = || hit_beats[first_hit_after..].iter().take(SV_STOP_CHECKED_ROWS);
+        let fits_grid = hit_beats_after().all(|beat| is_on_grid(beat - total_beat_shift - beats));
+        let fitted_grid = hit_beats_after().all(|beat| is_on_grid(beat - total_beat_shift));
+        if !fits_grid || (fitted_grid && !is_on_grid(beats)) {
+            continue;
+        }
+
+        total_beat_shift += beats;
+        stops_by_time.push((time, beats));
+        sv_stops.push((i, duration, change_type));
+    }
+
+    if sv_stops.is_empty() {
+        return None;
+    }
+
+    let beat_shift = |time: i32| -> f32 {
+        stops_by_time.iter()
+            .take_while(|(stop_time, _)| *stop_time < time)
+            .map(|(_, beats)| beats)
+            .sum()
+    };
+
+    let mut converted_chart = chart.clone();
+    for (i, beat) in converted_chart.timing_points.beats.iter_mut().enumerate() {
+        *beat -= beat_shift(converted_chart.timing_points.times[i]);
+    }
+    for (i, beat) in converted_chart.hitobjects.beats.iter_mut().enumerate() {
+        *beat -= beat_shift(converted_chart.hitobjects.times[i]);
+    }
+    for (i, duration, change_type) in sv_stops {
+        converted_chart.timing_points.changes[i] = TimingChange::new(change_type, duration as f32);
+    }
+
+    Some(converted_chart)
+}
+
+fn process_notes(padded_measures: &[Measure]) -> String {
+    let mut notes_template = String::new();
+    let last_measure_index = padded_measures.len().saturating_sub(1);
+
     for (measure_index, measure) in padded_measures.iter().enumerate() {
         notes_template.push_str("// Measure ");
         notes_template.push_str(&(measure_index + 1).to_string());
@@ -171,66 +320,251 @@ pub(crate) fn to_sm(chart: &models::chart::Chart) -> Result<String, Box<dyn std:
         if measure_index != last_measure_index { notes_template.push_str(", "); }
     }
 
-    // process template
-    add_key_value_template(&mut template,
+    notes_template
+}
+
+fn get_steps_info(chart: &models::chart::Chart) -> Result<StepsInfo, Box<dyn std::error::Error>> {
+    if chart.chartinfo.game_mode != GameMode::Mania {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(
+            chart.chartinfo.game_mode, "Stepmania".to_string())));
+    }
+
+    let mut steps_info = match &chart.chartinfo.steps_info {
+        Some(steps_info) => steps_info.clone(),
+        None => {
+            let mut steps_info = StepsInfo::empty();
+            steps_info.description = chart.metadata.creator.clone();
+            steps_info
+        }
+    };
+
+    // keep the chart's own steps type if it still fits, otherwise pick one from the key count
+    let key_count = chart.chartinfo.key_count;
+    if StepsInfo::steps_type_key_count(&steps_info.steps_type) != Some(key_count) {
+        match StepsInfo::key_count_steps_type(key_count) {
+            Some(steps_type) => steps_info.steps_type = steps_type.to_string(),
+            None => return Err(Box::new(errors::WriteError::<GameMode>::InvalidKeyCount(
+                key_count, StepsInfo::supported_key_counts(), "Stepmania".to_string()))),
+        }
+    }
+
+    Ok(steps_info)
+}
+
+#[inline]
+fn radar_values_to_str(radar_values: &[f32]) -> String {
+    radar_values.iter()
+        .map(|value| format!("{:.3}", value))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn add_song_template(template: &mut String, chart: &models::chart::Chart) {
+    add_key_value_template(template,
         "#TITLE", ":", &chart.metadata.title, ";\n");
     let subtitle = if chart.metadata.source == ChartDefaults::SOURCE {
         ""
     } else {
         &chart.metadata.source
     };
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#SUBTITLE", ":", subtitle, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#ARTIST",":", &chart.metadata.artist, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#TITLETRANSLIT",":", &chart.metadata.alt_title, ";\n");   
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#SUBTITLETRANSLIT",":", "", ";\n");  
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#ARTISTTRANSLIT",":", &chart.metadata.alt_artist, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#GENRE",":", &chart.metadata.genre, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#CREDIT",":", &chart.metadata.creator, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#BANNER",":", &chart.chartinfo.bg_path, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#BACKGROUND",":", &chart.chartinfo.bg_path, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#LYRICSPATH",":", "", ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#CDTITLE",":", "", ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#MUSIC",":", &chart.chartinfo.song_path, ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#OFFSET",":",  &to_seconds(-chart.chartinfo.audio_offset as f32).to_string(), ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#SAMPLESTART",":",  &to_seconds(chart.chartinfo.preview_time as f32).to_string(), ";\n");
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#SAMPLELENGTH",":",  "12.000", ";\n"); // TODO: maybe add chart length in chart info
-    add_key_value_template(&mut template,
+    add_key_value_template(template,
         "#SELECTABLE",":", "YES", ";\n");
+}
+
+fn process_sm_notes(steps_info: &StepsInfo, padded_measures: &[Measure]) -> String {
+    let mut notes_template = String::new();
+
+    notes_template.push('\n');
+    notes_template.push_str("   ");
+    notes_template.push_str(&steps_info.steps_type);
+    notes_template.push_str(":\n");
+    notes_template.push_str("   ");
+    notes_template.push_str(&steps_info.description);
+    notes_template.push_str(":\n");
+    notes_template.push_str("   ");
+    notes_template.push_str(&steps_info.difficulty);
+    notes_template.push_str(":\n");
+    notes_template.push_str("   ");
+    notes_template.push_str(&steps_info.meter.to_string());
+    notes_template.push_str(":\n");
+    notes_template.push_str("   ");
+    notes_template.push_str(&radar_values_to_str(&steps_info.radar_values));
+    notes_template.push_str(":\n");
+    notes_template.push_str(&process_notes(padded_measures));
+
+    notes_template
+}
+
+fn process_beats_per_measure(chart: &models::chart::Chart) -> String {
+    // measures that aren't a whole number of beats long keep their fraction
+    let time_signatures = process_timing_segments(chart, TimingChangeType::TimeSignature, |beats| format!("{}", (beats * 1000.0).round() / 1000.0));
+    if time_signatures.is_empty() { "0.000=4".to_string() } else { time_signatures }
+}
+
+#[inline]
+fn process_speeds(chart: &models::chart::Chart) -> String {
+    process_timing_segments(chart, TimingChangeType::Sv, |multiplier| format!("{:.3}=0.000", multiplier))
+}
+
+pub(crate) fn to_sm(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let mut template = String::new();
+
+    let padded_measures = process_measures(chart);
+    let bpm_template = process_bpms(chart);
+    let steps_info = get_steps_info(chart)?;
+
+    // process template
+    add_song_template(&mut template, chart);
     add_key_value_template(&mut template,
         "#BPMS",":", &bpm_template, ";\n");
     add_key_value_template(&mut template,
-        "#STOPS",":", "", ";\n");
+        "#STOPS",":", &process_stops(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#DELAYS",":", &process_delays(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#WARPS",":", &process_warps(chart), ";\n");
     add_key_value_template(&mut template,
         "#BGCHANGES",":", "", ";\n");
     add_key_value_template(&mut template,
         "#KEYSOUNDS",":", "", ";\n");
     add_key_value_template(&mut template,
-        "#NOTES", ":", &notes_template, ";\n");
+        "#NOTES", ":", &process_sm_notes(&steps_info, &padded_measures), ";\n");
 
     Ok(template)
 }
 
-#[allow(unused)]
 pub(crate) fn to_sma(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
-    unimplemented!();
+    let mut template = String::new();
+
+    let padded_measures = process_measures(chart);
+    let bpm_template = process_bpms(chart);
+    let steps_info = get_steps_info(chart)?;
+
+    // process template
+    add_song_template(&mut template, chart);
+    add_key_value_template(&mut template,
+        "#BPMS",":", &bpm_template, ";\n");
+    add_key_value_template(&mut template,
+        "#STOPS",":", &process_stops(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#DELAYS",":", &process_delays(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#WARPS",":", &process_warps(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#BEATSPERMEASURE",":", &process_beats_per_measure(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#ROWSPERBEAT",":", "48", ";\n"); // everything is written in beats so this is only informational
+    add_key_value_template(&mut template,
+        "#SPEED",":", &process_speeds(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#BGCHANGES",":", "", ";\n");
+    add_key_value_template(&mut template,
+        "#KEYSOUNDS",":", "", ";\n");
+    add_key_value_template(&mut template,
+        "#NOTES", ":", &process_sm_notes(&steps_info, &padded_measures), ";\n");
+
+    Ok(template)
+}
+
+/// like `to_sm`, but svs that fake stops are written as real stops (or delays), see `convert_sv_stops`
+pub(crate) fn to_sm_with_sv_stops(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let converted_chart = convert_sv_stops(chart);
+    to_sm(converted_chart.as_ref().unwrap_or(chart))
+}
+
+/// like `to_sma`, but svs that fake stops are written as real stops (or delays), see `convert_sv_stops`
+pub(crate) fn to_sma_with_sv_stops(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    let converted_chart = convert_sv_stops(chart);
+    to_sma(converted_chart.as_ref().unwrap_or(chart))
 }
 
-#[allow(unused)]
 pub(crate) fn to_ssc(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
-    unimplemented!();
+    let mut template = String::new();
+
+    let padded_measures = process_measures(chart);
+    let bpm_template = process_bpms(chart);
+    let steps_info = get_steps_info(chart)?;
+
+    // process song template
+    add_key_value_template(&mut template,
+        "#VERSION", ":", "0.83", ";\n");
+    add_song_template(&mut template, chart);
+    add_key_value_template(&mut template,
+        "#BPMS",":", &bpm_template, ";\n");
+    add_key_value_template(&mut template,
+        "#STOPS",":", &process_stops(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#DELAYS",":", &process_delays(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#WARPS",":", &process_warps(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#TIMESIGNATURES",":", &process_time_signatures(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#TICKCOUNTS",":", &process_tick_counts(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#SCROLLS",":", &process_scrolls(chart), ";\n");
+    add_key_value_template(&mut template,
+        "#FAKES",":", "", ";\n");
+    add_key_value_template(&mut template,
+        "#BGCHANGES",":", "", ";\n");
+    add_key_value_template(&mut template,
+        "#KEYSOUNDS",":", "", ";\n");
+
+    // process chart template
+    template.push('\n');
+    add_key_value_template(&mut template,
+        "#NOTEDATA", ":", "", ";\n");
+    let chart_name = if chart.chartinfo.difficulty_name == steps_info.difficulty {
+        ""
+    } else {
+        &chart.chartinfo.difficulty_name
+    };
+    add_key_value_template(&mut template,
+        "#CHARTNAME", ":", chart_name, ";\n");
+    add_key_value_template(&mut template,
+        "#STEPSTYPE", ":", &steps_info.steps_type, ";\n");
+    add_key_value_template(&mut template,
+        "#DESCRIPTION", ":", &steps_info.description, ";\n");
+    add_key_value_template(&mut template,
+        "#DIFFICULTY", ":", &steps_info.difficulty, ";\n");
+    add_key_value_template(&mut template,
+        "#METER", ":", &steps_info.meter.to_string(), ";\n");
+    add_key_value_template(&mut template,
+        "#RADARVALUES", ":", &radar_values_to_str(&steps_info.radar_values), ";\n");
+    add_key_value_template(&mut template,
+        "#CREDIT", ":", &chart.metadata.creator, ";\n");
+    add_key_value_template(&mut template,
+        "#NOTES", ":\n", &process_notes(&padded_measures), ";\n");
+
+    Ok(template)
 }
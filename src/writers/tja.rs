@@ -0,0 +1,208 @@
+use crate::models;
+use crate::models::common::{GameMode, KeyType, TimingChangeType};
+use crate::utils::rhythm::calculate_beat_from_time;
+use crate::errors;
+
+/// the courses a TJA file can have, anything else is written as an Oni
+const COURSES: [&str; 5] = ["Easy", "Normal", "Hard", "Oni", "Edit"];
+const DEFAULT_COURSE: &str = "Oni";
+/// how many notes a measure can be split into, the fewest that fit everything is used
+const DIVISIONS: [usize; 13] = [1, 2, 3, 4, 6, 8, 12, 16, 24, 32, 48, 64, 96];
+const MAX_DIVISIONS_PER_BEAT: usize = 48;
+/// beats an object can be off from a division and still be put on it
+const SNAP_MARGIN: f32 = 0.01;
+
+/// things that get written in a measure, commands go in front of the note they happen on
+enum TjaEvent {
+    Note(char),
+    BpmChange(f32),
+    Scroll(f32),
+}
+
+/// `#MEASURE` for a length in beats, with the smallest denominator that keeps it whole
+fn measure_command(length: f32) -> String {
+    let denominator = [4, 8, 16, 32, 64].into_iter()
+        .find(|denominator| {
+            let numerator = length * *denominator as f32 / 4.0;
+            (numerator - numerator.round()).abs() < 0.001
+        })
+        .unwrap_or(192);
+    format!("#MEASURE {}/{}", (length * denominator as f32 / 4.0).round() as u32, denominator)
+}
+
+/// the course and level from a difficulty name like `Oni 8`
+fn course_and_level(chart: &models::chart::Chart) -> (&'static str, Option<u32>) {
+    let difficulty_name = &chart.chartinfo.difficulty_name;
+    let course = difficulty_name.split_whitespace()
+        .find_map(|word| COURSES.iter().find(|course| course.eq_ignore_ascii_case(word)))
+        .copied()
+        .unwrap_or(DEFAULT_COURSE);
+    let level = chart.chartinfo.steps_info.as_ref()
+        .map(|steps_info| steps_info.meter)
+        .filter(|meter| *meter > 0)
+        .or_else(|| difficulty_name.split_whitespace().last().and_then(|word| word.parse().ok()));
+    (course, level)
+}
+
+/// the fewest divisions of a measure that put every object on one and no two notes on the same one
+fn measure_divisions(events: &[(f32, TjaEvent)], measure_start: f32, measure_length: f32) -> usize {
+    let max_divisions = (measure_length * MAX_DIVISIONS_PER_BEAT as f32).round().max(1.0) as usize;
+    DIVISIONS.iter()
+        .copied()
+        .take_while(|divisions| *divisions < max_divisions)
+        .find(|divisions| {
+            let division_length = measure_length / *divisions as f32;
+            let mut note_divisions = Vec::with_capacity(events.len());
+            events.iter().all(|(beat, event)| {
+                let position = (beat - measure_start) / division_length;
+                if (position - position.round()).abs() * division_length > SNAP_MARGIN {
+                    return false;
+                }
+                if !matches!(event, TjaEvent::Note(_)) {
+                    return true;
+                }
+                let division = position.round() as usize;
+                let is_free = !note_divisions.contains(&division);
+                note_divisions.push(division);
+                is_free
+            })
+        })
+        .unwrap_or(max_divisions)
+}
+
+/// writes taiko charts as a TJA (.tja) file with a single course,
+/// sv changes become `#SCROLL` and time signatures become `#MEASURE`
+pub(crate) fn to_tja(chart: &models::chart::Chart) -> Result<String, Box<dyn std::error::Error>> {
+    if chart.chartinfo.game_mode != GameMode::Taiko {
+        return Err(Box::new(errors::WriteError::<GameMode>::InvalidMode(chart.chartinfo.game_mode, "TJA".to_string())));
+    }
+
+    let (mut bpm_times, mut bpms): (Vec<i32>, Vec<f32>) = chart.timing_points.bpm_changes_zipped()
+        .filter(|(_, _, change)| change.value > 0.0)
+        .map(|(time, _, change)| (*time, change.value))
+        .unzip();
+    if bpms.is_empty() {
+        bpm_times.push(0);
+        bpms.push(120.0);
+    }
+
+    // the first measure starts on the first bpm change, or whole measures before it when there are notes before that
+    let measure_time = 4.0 * 60000.0 / bpms[0];
+    let first_time = chart.hitobjects.times.first().copied().unwrap_or(bpm_times[0]);
+    let lead_measures = ((bpm_times[0] - first_time) as f32 / measure_time).ceil().max(0.0);
+    let start_time = bpm_times[0] - (lead_measures * measure_time).round() as i32;
+    bpm_times[0] = start_time;
+    let beat_at = |time: i32| calculate_beat_from_time(time, start_time, (&bpm_times, &bpms));
+
+    let mut events: Vec<(f32, TjaEvent)> = Vec::with_capacity(chart.hitobjects.times.len() + bpms.len());
+    for (time, bpm) in bpm_times.iter().zip(bpms.iter()).skip(1) {
+        events.push((beat_at(*time), TjaEvent::BpmChange(*bpm)));
+    }
+    for (time, _, change) in chart.timing_points.sv_changes_zipped() {
+        events.push((beat_at(*time), TjaEvent::Scroll(change.value)));
+    }
+    for (time, _, _, row) in chart.hitobjects.iter_zipped() {
+        let Some(key) = row.first() else {
+            continue;
+        };
+        let beat = beat_at(*time);
+        let roll = match key.key_type {
+            KeyType::Don => { events.push((beat, TjaEvent::Note('1'))); continue },
+            KeyType::Kat => { events.push((beat, TjaEvent::Note('2'))); continue },
+            KeyType::BigDon => { events.push((beat, TjaEvent::Note('3'))); continue },
+            KeyType::BigKat => { events.push((beat, TjaEvent::Note('4'))); continue },
+            KeyType::Drumroll => '5',
+            KeyType::BigDrumroll => '6',
+            KeyType::Spinner => '7',
+            _ => continue,
+        };
+        events.push((beat, TjaEvent::Note(roll)));
+        events.push((key.slider_end_time().map_or(beat, beat_at), TjaEvent::Note('8')));
+    }
+    events.sort_by(|a, b| a.0.partial_cmp(&b.0).expect("Comparison between NaN values when sorting events"));
+
+    let time_signatures: Vec<(f32, f32)> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::TimeSignature && *view.value > 0.0)
+        .map(|view| (beat_at(*view.time), *view.value))
+        .collect();
+
+    let mut body = String::from("#START\n");
+    let mut measure_start = 0.0;
+    let mut previous_length = 4.0;
+    let mut remaining_events = events.as_slice();
+    while !remaining_events.is_empty() {
+        let measure_length = time_signatures.iter()
+            .rev()
+            .find(|(beat, _)| *beat <= measure_start + SNAP_MARGIN)
+            .map_or(4.0, |(_, length)| *length);
+        if measure_length != previous_length {
+            body.push_str(&measure_command(measure_length));
+            body.push('\n');
+            previous_length = measure_length;
+        }
+
+        let measure_end = measure_start + measure_length;
+        let event_count = remaining_events.iter().take_while(|(beat, _)| *beat < measure_end - SNAP_MARGIN).count();
+        let (measure_events, rest) = remaining_events.split_at(event_count);
+        remaining_events = rest;
+
+        let divisions = measure_divisions(measure_events, measure_start, measure_length);
+        let division_length = measure_length / divisions as f32;
+        let mut digits = vec!['0'; divisions];
+        let mut commands: Vec<Vec<String>> = vec![Vec::new(); divisions];
+        for (beat, event) in measure_events {
+            let division = (((beat - measure_start) / division_length).round().max(0.0) as usize).min(divisions - 1);
+            match event {
+                TjaEvent::Note(digit) => digits[division] = *digit,
+                TjaEvent::BpmChange(bpm) => commands[division].push(format!("#BPMCHANGE {bpm}")),
+                TjaEvent::Scroll(scroll) => commands[division].push(format!("#SCROLL {scroll}")),
+            }
+        }
+
+        let mut line = String::with_capacity(divisions + 1);
+        for (digit, commands) in digits.into_iter().zip(commands) {
+            if !commands.is_empty() {
+                if !line.is_empty() {
+                    body.push_str(&line);
+                    body.push('\n');
+                    line.clear();
+                }
+                for command in commands {
+                    body.push_str(&command);
+                    body.push('\n');
+                }
+            }
+            line.push(digit);
+        }
+        body.push_str(&line);
+        body.push_str(",\n");
+
+        measure_start = measure_end;
+    }
+    body.push_str("#END\n");
+
+    let (course, level) = course_and_level(chart);
+    let mut template = String::with_capacity(body.len() + 256);
+    template.push_str(&format!("TITLE:{}\n", chart.metadata.title));
+    template.push_str(&format!("SUBTITLE:--{}\n", chart.metadata.artist));
+    template.push_str(&format!("BPM:{}\n", bpms[0]));
+    template.push_str(&format!("WAVE:{}\n", chart.chartinfo.song_path));
+    template.push_str(&format!("OFFSET:{}\n", -start_time as f32 / 1000.0));
+    template.push_str(&format!("DEMOSTART:{}\n", chart.chartinfo.preview_time.max(0) as f32 / 1000.0));
+    template.push_str(&format!("MAKER:{}\n", chart.metadata.creator));
+    if !chart.metadata.genre.is_empty() {
+        template.push_str(&format!("GENRE:{}\n", chart.metadata.genre));
+    }
+    if !chart.chartinfo.bg_path.is_empty() {
+        template.push_str(&format!("BGIMAGE:{}\n", chart.chartinfo.bg_path));
+    }
+    template.push('\n');
+    template.push_str(&format!("COURSE:{course}\n"));
+    if let Some(level) = level {
+        template.push_str(&format!("LEVEL:{level}\n"));
+    }
+    template.push('\n');
+    template.push_str(&body);
+
+    Ok(template)
+}
@@ -0,0 +1,159 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn chart_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = "[Song]
+{
+  Name = \"Ch Test\"
+  Artist = \"Someone\"
+  Charter = \"Charter\"
+  Offset = 0.1
+  Resolution = 192
+  PreviewStart = 5
+  MusicStream = \"song.ogg\"
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 120000
+  768 = TS 3
+  768 = B 240000
+}
+[Events]
+{
+  0 = E \"section Intro\"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = N 1 0
+  192 = N 2 384
+  192 = S 2 768
+  384 = N 5 0
+  768 = N 7 96
+  960 = N 2 0
+  1152 = N 4 192
+  1248 = N 4 0
+}
+[EasySingle]
+{
+  0 = N 0 0
+}
+[ExpertDrums]
+{
+  0 = N 0 0
+}
+";
+    let charts = parse::from_chart_all(raw_chart).unwrap();
+    assert_eq!(charts.len(), 2);
+    let chart = &charts[0];
+    assert_eq!(chart.metadata.title, "Ch Test");
+    assert_eq!(chart.metadata.creator, "Charter");
+    assert_eq!(chart.chartinfo.difficulty_name, "Expert Guitar");
+    assert_eq!(chart.chartinfo.song_path, "song.ogg");
+    assert_eq!(chart.chartinfo.audio_offset, 100);
+    assert_eq!(chart.chartinfo.preview_time, 5000);
+    // open notes get their own column
+    assert_eq!(chart.chartinfo.key_count, 6);
+    assert_eq!(charts[1].chartinfo.difficulty_name, "Easy Guitar");
+    assert_eq!(charts[1].chartinfo.key_count, 5);
+    assert!(chart.warnings.is_empty());
+
+    // 120bpm from 100ms then 240bpm in 3/4 from tick 768
+    assert_eq!(chart.hitobjects.times, vec![100, 600, 1600, 2100, 2225, 2350, 2600, 2725]);
+    let key_type = |row: usize, column: usize| chart.hitobjects.rows[row][column].key_type;
+    assert_eq!((key_type(0, 0), key_type(0, 1)), (KeyType::Normal, KeyType::Normal));
+    assert_eq!(key_type(1, 2), KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][2].slider_end_time(), Some(1600));
+    assert_eq!(key_type(2, 2), KeyType::SliderEnd);
+    assert_eq!(key_type(3, 5), KeyType::SliderStart);
+    assert_eq!(key_type(4, 5), KeyType::SliderEnd);
+    assert_eq!(key_type(5, 2), KeyType::Normal);
+    // a sustain running into the next note on its fret can't be held
+    assert_eq!(key_type(6, 4), KeyType::Normal);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![
+        (100, TimingChangeType::Bpm, 120.0),
+        (2100, TimingChangeType::Bpm, 240.0),
+        (2100, TimingChangeType::TimeSignature, 3.0),
+    ]);
+
+    let osu = write::to_osu(chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    assert!(parse::from_chart("[Song]\n{\n  Resolution = 192\n}").is_err());
+}
+
+#[test]
+fn notes_mid_to_osu_test() {
+    use rgc_chart::models::common::KeyType;
+
+    fn var_len(mut value: u32) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7F) as u8];
+        value >>= 7;
+        while value > 0 {
+            bytes.insert(0, (value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes
+    }
+    fn chunk(id: &[u8], events: &[(u32, &[u8])]) -> Vec<u8> {
+        let data: Vec<u8> = events.iter().flat_map(|(delta, event)| [var_len(*delta), event.to_vec()].concat()).collect();
+        [id, &(data.len() as u32).to_be_bytes(), &data].concat()
+    }
+
+    let tempo_track = chunk(b"MTrk", &[
+        (0, b"\xFF\x03\x08Mid Test"),
+        (0, &[0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]),
+        (960, &[0xFF, 0x51, 0x03, 0x03, 0xD0, 0x90]),
+        (0, &[0xFF, 0x2F, 0x00]),
+    ]);
+    // expert green and easy green, expert red held for 2 beats and an expert open
+    let guitar_track = chunk(b"MTrk", &[
+        (0, b"\xFF\x03\x0BPART GUITAR"),
+        (0, &[0x90, 96, 100]),
+        (0, &[60, 100]),
+        (60, &[0x80, 96, 0]),
+        (0, &[0x80, 60, 0]),
+        (420, &[0x90, 97, 100]),
+        (480, &[0x90, 95, 100]),
+        (240, &[95, 0]),
+        (240, &[0x80, 97, 0]),
+        (0, &[0xFF, 0x2F, 0x00]),
+    ]);
+    let header = [b"MThd".as_slice(), &6u32.to_be_bytes(), &1u16.to_be_bytes(), &2u16.to_be_bytes(), &480u16.to_be_bytes()].concat();
+    let raw_mid = [header, tempo_track, guitar_track].concat();
+
+    let charts = parse::from_notes_mid_all(&raw_mid).unwrap();
+    assert_eq!(charts.len(), 2);
+    assert_eq!(charts[0].metadata.title, "Mid Test");
+    assert_eq!(charts[0].chartinfo.difficulty_name, "Easy Guitar");
+    assert_eq!(charts[0].hitobjects.times, vec![0]);
+
+    let chart = &charts[1];
+    assert_eq!(chart.chartinfo.difficulty_name, "Expert Guitar");
+    assert_eq!(chart.chartinfo.key_count, 6);
+    // 120bpm then 240bpm from beat 2
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1000, 1125, 1250]);
+    let key_type = |row: usize, column: usize| chart.hitobjects.rows[row][column].key_type;
+    // notes shorter than a third of a beat aren't sustains
+    assert_eq!(key_type(0, 0), KeyType::Normal);
+    assert_eq!(key_type(1, 1), KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][1].slider_end_time(), Some(1250));
+    assert_eq!(key_type(2, 5), KeyType::SliderStart);
+    assert_eq!(key_type(3, 5), KeyType::SliderEnd);
+    assert_eq!(key_type(4, 1), KeyType::SliderEnd);
+    assert_eq!(chart.timing_points.bpms(), vec![120.0, 240.0]);
+
+    let osu = write::to_osu(chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    assert!(parse::from_notes_mid(b"not a midi file").is_err());
+}
@@ -0,0 +1,79 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn dtx_to_osu_test() {
+    use rgc_chart::models::common::KeyType;
+
+    let raw_chart = "; Created by DTXCreator
+#TITLE: Dtx Test
+#ARTIST: Someone
+#BPM: 120
+#DLEVEL: 65
+#WAV01: snare.wav ; sounds
+#WAV02: kick.wav
+#WAV03: bgm.ogg
+#VOLUME01: 80
+#BPM01: 240
+
+#00001: 03
+#00012: 0100
+#00013: 00000200
+#00102: 0.5
+#00118: 01
+#00111: 0001
+#00126: 02
+#00208: 01
+#00212: 0001
+";
+    let chart = parse::from_dtx(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Dtx Test");
+    assert_eq!(chart.metadata.artist, "Someone");
+    assert_eq!(chart.chartinfo.difficulty_name, "Level 65");
+    assert_eq!(chart.chartinfo.key_count, 10);
+
+    // 120bpm, measure 1 is 2 beats long and 240bpm from measure 2
+    assert_eq!(chart.hitobjects.times, vec![0, 1000, 2000, 2500, 3500]);
+    let columns = |row: usize| chart.hitobjects.rows[row].iter()
+        .enumerate()
+        .filter(|(_, key)| key.key_type == KeyType::Normal)
+        .map(|(column, _)| column)
+        .collect::<Vec<_>>();
+    assert_eq!(columns(0), vec![3]);
+    assert_eq!(columns(1), vec![5]);
+    // open and closed hi-hats share a column
+    assert_eq!(columns(2), vec![1]);
+    assert_eq!(columns(3), vec![1]);
+    assert_eq!(chart.timing_points.bpms(), vec![120.0, 240.0]);
+
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    let keysound = &chart.hitobjects.keysounds[0][3];
+    assert_eq!(soundbank.get_sound_sample(keysound.sample.unwrap()).unwrap(), "snare.wav");
+    assert_eq!(keysound.volume, 80);
+    // the guitar isn't in the drum layout so it plays on its own
+    let sound_effects: Vec<(i32, String)> = soundbank.sound_effects.iter()
+        .map(|sound_effect| (sound_effect.time, soundbank.get_sound_sample(sound_effect.sample).unwrap()))
+        .collect();
+    assert_eq!(sound_effects, vec![(0, "bgm.ogg".to_string()), (2000, "kick.wav".to_string())]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    // red and green pressed at once
+    let guitar_chart = parse::from_dtx_with_layout(raw_chart, "GR,GG,GB").unwrap();
+    assert_eq!(guitar_chart.chartinfo.key_count, 3);
+    assert_eq!(guitar_chart.hitobjects.times, vec![2000]);
+    assert_eq!(guitar_chart.hitobjects.rows[0].iter().map(|key| key.key_type).collect::<Vec<_>>(),
+        vec![KeyType::Normal, KeyType::Normal, KeyType::Empty]);
+
+    assert!(parse::from_dtx_with_layout(raw_chart, "GR,XX").is_err());
+
+    // cells that aren't two ascii characters are skipped instead of panicking
+    assert!(parse::from_dtx("#BPM: 60\n#00112: 0あ").is_ok());
+
+    // #RANDOM always takes the first branch
+    let chart = parse::from_dtx("#BPM: 60\n#RANDOM 2\n#IF 1\n#00112: 01\n#ELSE\n#00113: 01\n#ENDIF").unwrap();
+    assert_eq!(chart.hitobjects.rows[0][3].key_type, KeyType::Normal);
+    assert_eq!(chart.warnings.len(), 1);
+}
@@ -0,0 +1,78 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn ksh_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = "\u{feff}title=Ksh Test
+artist=Someone
+effect=Charter
+jacket=jacket.png
+difficulty=extended
+level=17
+t=120-240
+m=song.ogg;song_f.ogg
+o=100
+po=5000
+ver=167
+--
+t=120
+1000|00|--
+0200|00|0-
+0200|02|:-
+0000|10|--
+--
+beat=2/4
+0001|S0|--
+// comment
+0000|00|--
+--
+beat=4/4
+t=240
+2000|02|--
+--
+";
+    let chart = parse::from_ksh(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Ksh Test");
+    assert_eq!(chart.metadata.creator, "Charter");
+    assert_eq!(chart.chartinfo.difficulty_name, "Extended 17");
+    assert_eq!(chart.chartinfo.song_path, "song.ogg");
+    assert_eq!(chart.chartinfo.audio_offset, 100);
+    assert_eq!(chart.chartinfo.preview_time, 5000);
+    assert_eq!(chart.chartinfo.key_count, 6);
+
+    // 120bpm from 100ms, measure 1 is 2 beats long and 240bpm from measure 2
+    assert_eq!(chart.hitobjects.times, vec![100, 600, 1100, 1600, 2100, 2600, 3100, 4100]);
+    let key_type = |row: usize, column: usize| chart.hitobjects.rows[row][column].key_type;
+    assert_eq!(key_type(0, 1), KeyType::Normal);
+    assert_eq!(key_type(1, 2), KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][2].slider_end_time(), Some(1600));
+    assert_eq!(key_type(2, 5), KeyType::Normal);
+    assert_eq!(key_type(3, 2), KeyType::SliderEnd);
+    // FX long notes with an effect letter are still long notes
+    assert_eq!(key_type(3, 0), KeyType::SliderStart);
+    assert_eq!(key_type(4, 4), KeyType::Normal);
+    assert_eq!(key_type(5, 0), KeyType::SliderEnd);
+    assert_eq!(key_type(6, 1), KeyType::SliderStart);
+    assert_eq!(key_type(6, 5), KeyType::Normal);
+    assert_eq!(key_type(7, 1), KeyType::SliderEnd);
+    // the lasers are dropped and the caller is told about it
+    assert_eq!(chart.warnings, vec!["K-Shoot Mania lasers can't be placed in columns and were dropped".to_string()]);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![
+        (100, TimingChangeType::Bpm, 120.0),
+        (2100, TimingChangeType::TimeSignature, 2.0),
+        (3100, TimingChangeType::Bpm, 240.0),
+        (3100, TimingChangeType::TimeSignature, 4.0),
+    ]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    assert!(parse::from_ksh("title=No Measures").is_err());
+}
@@ -0,0 +1,92 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn midi_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+    use rgc_chart::models::midi::MidiOptions;
+
+    fn var_len(mut value: u32) -> Vec<u8> {
+        let mut bytes = vec![(value & 0x7F) as u8];
+        value >>= 7;
+        while value > 0 {
+            bytes.insert(0, (value & 0x7F) as u8 | 0x80);
+            value >>= 7;
+        }
+        bytes
+    }
+    fn chunk(events: &[(u32, &[u8])]) -> Vec<u8> {
+        let data: Vec<u8> = events.iter().flat_map(|(delta, event)| [var_len(*delta), event.to_vec()].concat()).collect();
+        [b"MTrk".as_slice(), &(data.len() as u32).to_be_bytes(), &data].concat()
+    }
+
+    // 120bpm in 3/4 then 60bpm from beat 4
+    let tempo_track = chunk(&[
+        (0, b"\xFF\x03\x06Sketch"),
+        (0, &[0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20]),
+        (0, &[0xFF, 0x58, 0x04, 0x03, 0x02, 0x18, 0x08]),
+        (384, &[0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40]),
+        (0, &[0xFF, 0x2F, 0x00]),
+    ]);
+    let piano_track = chunk(&[
+        (0, b"\xFF\x03\x05Piano"),
+        (0, &[0x90, 60, 100]),
+        (24, &[0x80, 60, 0]),
+        (72, &[0x90, 64, 100]),
+        (96, &[0x90, 62, 100]),
+        (0, &[67, 100]),
+        (24, &[0x80, 62, 0]),
+        (0, &[67, 0]),
+        (72, &[0x80, 64, 0]),
+        (192, &[0x90, 60, 100]),
+        (24, &[60, 0]),
+        (0, &[0xFF, 0x2F, 0x00]),
+    ]);
+    let drum_track = chunk(&[
+        (0, b"\xFF\x03\x05Drums"),
+        (288, &[0x99, 60, 100]),
+        (24, &[0x89, 60, 0]),
+        (0, &[0xFF, 0x2F, 0x00]),
+    ]);
+    let header = [b"MThd".as_slice(), &6u32.to_be_bytes(), &1u16.to_be_bytes(), &3u16.to_be_bytes(), &96u16.to_be_bytes()].concat();
+    let raw_midi = [header, tempo_track, piano_track, drum_track].concat();
+
+    let options = MidiOptions::new(vec![1], vec![60, 62, 64], 1.0);
+    let chart = parse::from_midi(&raw_midi, &options).unwrap();
+    assert_eq!(chart.metadata.title, "Sketch");
+    assert_eq!(chart.chartinfo.key_count, 3);
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1000, 1500, 3000]);
+    let key_type = |row: usize, column: usize| chart.hitobjects.rows[row][column].key_type;
+    assert_eq!(key_type(0, 0), KeyType::Normal);
+    // held for 2 beats
+    assert_eq!(key_type(1, 2), KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][2].slider_end_time(), Some(1500));
+    assert_eq!(key_type(2, 1), KeyType::Normal);
+    assert_eq!(key_type(3, 2), KeyType::SliderEnd);
+    assert_eq!(key_type(4, 0), KeyType::Normal);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![
+        (0, TimingChangeType::Bpm, 120.0),
+        (0, TimingChangeType::TimeSignature, 3.0),
+        (2000, TimingChangeType::Bpm, 60.0),
+    ]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    // every track and pitch, from the lowest pitch up
+    let chart = parse::from_midi(&raw_midi, &MidiOptions::default()).unwrap();
+    assert_eq!(chart.chartinfo.key_count, 4);
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1000, 1500, 3000]);
+    assert_eq!(chart.hitobjects.rows[2][1].key_type, KeyType::Normal);
+    assert_eq!(chart.hitobjects.rows[2][3].key_type, KeyType::Normal);
+    // the drum track's note
+    assert_eq!(chart.hitobjects.rows[3][0].key_type, KeyType::Normal);
+    assert_eq!(chart.hitobjects.rows[1][2].key_type, KeyType::Normal);
+
+    assert!(parse::from_midi(&raw_midi, &MidiOptions::new(vec![1], vec![70], 0.0)).is_err());
+}
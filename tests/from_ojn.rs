@@ -0,0 +1,118 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn ojn_to_osu_test() {
+    use rgc_chart::models::common::KeyType;
+
+    fn fixed_string(text: &str, length: usize) -> Vec<u8> {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize(length, 0);
+        bytes
+    }
+    fn package(measure: i32, channel: u16, events: &[[u8; 4]]) -> Vec<u8> {
+        let mut bytes = measure.to_le_bytes().to_vec();
+        bytes.extend(channel.to_le_bytes());
+        bytes.extend((events.len() as u16).to_le_bytes());
+        bytes.extend(events.concat());
+        bytes
+    }
+    // sample id + 1, volume and pan, note type
+    let note = |value: u16, volume_pan: u8, note_type: u8| [value.to_le_bytes()[0], value.to_le_bytes()[1], volume_pan, note_type];
+    let empty = [0; 4];
+
+    let easy = [
+        package(0, 1, &[0.0f32.to_le_bytes(), 240.0f32.to_le_bytes()]),
+        package(0, 2, &[note(1, 0, 0), empty, empty, note(2, 0x80, 2)]),
+        package(1, 2, &[note(2, 0, 3), note(1, 0, 4)]),
+        package(0, 9, &[note(3, 0, 0)]),
+    ].concat();
+    let normal = package(0, 8, &[note(1, 0, 0)]);
+
+    let mut ojn = vec![0; 300];
+    ojn[4..8].copy_from_slice(b"ojn\0");
+    ojn[12..16].copy_from_slice(&1i32.to_le_bytes());
+    ojn[16..20].copy_from_slice(&120.0f32.to_le_bytes());
+    for (difficulty, package_count) in [4i32, 1, 0].into_iter().enumerate() {
+        ojn[64 + difficulty * 4..68 + difficulty * 4].copy_from_slice(&package_count.to_le_bytes());
+    }
+    let offsets = [300, 300 + easy.len(), 300 + easy.len() + normal.len(), 300 + easy.len() + normal.len()];
+    for (i, offset) in offsets.into_iter().enumerate() {
+        ojn[284 + i * 4..288 + i * 4].copy_from_slice(&(offset as i32).to_le_bytes());
+    }
+    ojn[108..172].copy_from_slice(&fixed_string("O2Jam Test", 64));
+    ojn[172..204].copy_from_slice(&fixed_string("Someone", 32));
+    ojn[204..236].copy_from_slice(&fixed_string("Charter", 32));
+    ojn.extend(&easy);
+    ojn.extend(&normal);
+
+    let wav = |name: &str, data: &[u8]| {
+        let mut bytes = fixed_string(name, 52);
+        bytes.extend((data.len() as i32).to_le_bytes());
+        bytes.extend(data);
+        bytes
+    };
+    let wavs = [wav("kick", &[1, 2, 3, 4]), wav("snare", &[1]), wav("bgm", &[1, 2])].concat();
+    let mut ogg = fixed_string("hat.ogg", 32);
+    ogg.extend(3i32.to_le_bytes());
+    ogg.extend([1, 2, 3]);
+    let mut ojm = b"OMC\0".to_vec();
+    ojm.extend(3u16.to_le_bytes());
+    ojm.extend(1u16.to_le_bytes());
+    ojm.extend(20i32.to_le_bytes());
+    ojm.extend(((20 + wavs.len()) as i32).to_le_bytes());
+    ojm.extend(((20 + wavs.len() + ogg.len()) as i32).to_le_bytes());
+    ojm.extend(&wavs);
+    ojm.extend(&ogg);
+
+    let charts = parse::from_ojn_all(&ojn, &ojm).unwrap();
+    assert_eq!(charts.len(), 3);
+    let chart = &charts[0];
+    assert_eq!(chart.metadata.title, "O2Jam Test");
+    assert_eq!(chart.metadata.artist, "Someone");
+    assert_eq!(chart.metadata.creator, "Charter");
+    assert_eq!(chart.metadata.genre, "Rock");
+    assert_eq!(chart.chartinfo.difficulty_name, "Easy");
+    assert_eq!(chart.chartinfo.key_count, 7);
+    assert_eq!(chart.timing_points.bpms(), vec![120.0, 240.0]);
+
+    // the bpm doubles halfway through the first measure
+    assert_eq!(chart.hitobjects.times, vec![0, 1250, 1500, 2000]);
+    let rows = &chart.hitobjects.rows;
+    assert_eq!(rows[0][0].key_type, KeyType::Normal);
+    assert_eq!(rows[1][0].key_type, KeyType::SliderStart);
+    assert_eq!(rows[1][0].slider_end_time(), Some(1500));
+    assert_eq!(rows[2][0].key_type, KeyType::SliderEnd);
+    assert_eq!(rows[3][0].key_type, KeyType::Normal);
+
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    let keysounds = &chart.hitobjects.keysounds;
+    let sample_name = |row: usize| soundbank.get_sound_sample(keysounds[row][0].sample.unwrap()).unwrap();
+    assert_eq!(sample_name(0), "kick");
+    assert_eq!(sample_name(1), "snare");
+    assert_eq!(keysounds[1][0].volume, 50);
+    assert_eq!(sample_name(3), "hat.ogg");
+    assert_eq!(soundbank.sound_effects.len(), 1);
+    assert_eq!(soundbank.get_sound_sample(soundbank.sound_effects[0].sample).unwrap(), "bgm");
+
+    assert_eq!(charts[1].hitobjects.rows.len(), 1);
+    assert_eq!(charts[1].hitobjects.rows[0][6].key_type, KeyType::Normal);
+    assert!(charts[2].hitobjects.rows.is_empty());
+
+    let osu = write::to_osu(chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    // without the .ojm the notes are still there, just without keysounds
+    let chart = parse::from_ojn(&ojn, &[]).unwrap();
+    assert_eq!(chart.hitobjects.times, vec![0, 1250, 1500, 2000]);
+    assert!(chart.hitobjects.keysounds.iter().all(|keysounds| keysounds.is_empty));
+    assert!(parse::from_ojn(b"not an ojn file", &[]).is_err());
+
+    // a corrupt measure index is skipped instead of making room for that many measures
+    let mut corrupt_ojn = ojn.clone();
+    let normal_start = 300 + easy.len();
+    corrupt_ojn[normal_start..normal_start + 4].copy_from_slice(&i32::MAX.to_le_bytes());
+    let charts = parse::from_ojn_all(&corrupt_ojn, &[]).unwrap();
+    assert!(charts[1].hitobjects.rows.is_empty());
+}
@@ -24,6 +24,7 @@ pub fn write_to_file(file_path: &str, content: &str) -> std::io::Result<()> {
     Ok(())
 }
 
+#[allow(clippy::if_same_then_else)]
 pub fn sanitize_str(filename: &str) -> String {
     const FORBIDDEN_CHARS: &[char] = &['/', '\\', '?', '%', '*', ':', '|', '"', '<', '>', '\0', '\n'];
     
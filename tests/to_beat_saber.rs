@@ -0,0 +1,138 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_beat_saber_test() {
+    parse_and_convert!(
+        osu_to_beat_saber,
+        "./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [Disappearance].osu",
+        parse::from_osu,
+        write::to_beat_saber,
+        true
+    );
+}
+
+#[test]
+fn sm_to_beat_saber_test() {
+    parse_and_convert!(
+        sm_to_beat_saber,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_beat_saber,
+        true
+    );
+}
+
+#[test]
+fn qua_to_beat_saber_test() {
+    parse_and_convert!(
+        qua_to_beat_saber,
+        "./tests/Maps/quaver/2366_177_NewNonBiyori/19248.qua",
+        parse::from_qua,
+        write::to_beat_saber,
+        true
+    );
+}
+
+#[test]
+fn beat_saber_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_info = r#"{
+    "_version": "2.0.0",
+    "_songName": "Saber Test",
+    "_songSubName": "Remix",
+    "_songAuthorName": "Artist",
+    "_levelAuthorName": "Mapper",
+    "_beatsPerMinute": 120,
+    "_songTimeOffset": 0.5,
+    "_previewStartTime": 12,
+    "_songFilename": "song.egg",
+    "_coverImageFilename": "cover.jpg",
+    "_difficultyBeatmapSets": [{"_beatmapCharacteristicName": "Standard", "_difficultyBeatmaps": [{"_difficulty": "Expert", "_beatmapFilename": "ExpertStandard.dat"}]}]
+}"#;
+    let raw_v3 = r#"{
+    "version": "3.2.0",
+    "bpmEvents": [{"b": 4, "m": 240}],
+    "colorNotes": [
+        {"b": 0, "x": 0, "y": 0, "c": 0, "d": 1},
+        {"b": 1, "x": 3, "y": 2, "c": 1, "d": 0},
+        {"b": 2, "x": 1, "y": 1, "c": 0, "d": 8},
+        {"b": 6, "x": 2, "c": 1, "d": 1}
+    ],
+    "bombNotes": [{"b": 1, "x": 1, "y": 0}],
+    "obstacles": [
+        {"b": 2, "x": 2, "y": 0, "d": 2, "w": 2, "h": 5},
+        {"b": 3, "x": 2, "y": 0, "d": 1, "w": 1, "h": 5}
+    ]
+}"#;
+    let chart = parse::from_beat_saber(raw_info, raw_v3, 4).unwrap();
+    assert_eq!(chart.metadata.title, "Saber Test");
+    assert_eq!(chart.metadata.alt_title, "Remix");
+    assert_eq!(chart.metadata.creator, "Mapper");
+    assert_eq!(chart.chartinfo.difficulty_name, "Expert");
+    assert_eq!(chart.chartinfo.song_path, "song.egg");
+    assert_eq!(chart.chartinfo.audio_offset, 500);
+    assert_eq!(chart.chartinfo.preview_time, 12000);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![(500, TimingChangeType::Bpm, 120.0), (2500, TimingChangeType::Bpm, 240.0)]);
+
+    // the wall covers lines 2 and 3, the second wall overlaps it and gets dropped
+    assert_eq!(chart.hitobjects.times, vec![500, 1000, 1500, 2500, 3000]);
+    let key_types = |row: usize| -> Vec<KeyType> { chart.hitobjects.rows[row].iter().map(|key| key.key_type).collect() };
+    assert_eq!(key_types(0), vec![KeyType::Normal, KeyType::Empty, KeyType::Empty, KeyType::Empty]);
+    assert_eq!(key_types(1), vec![KeyType::Empty, KeyType::Mine, KeyType::Empty, KeyType::Normal]);
+    assert_eq!(key_types(2), vec![KeyType::Empty, KeyType::Normal, KeyType::SliderStart, KeyType::SliderStart]);
+    assert_eq!(chart.hitobjects.rows[2][2].slider_end_time(), Some(2500));
+    assert_eq!(key_types(3), vec![KeyType::Empty, KeyType::Empty, KeyType::SliderEnd, KeyType::SliderEnd]);
+    assert_eq!(key_types(4), vec![KeyType::Empty, KeyType::Empty, KeyType::Normal, KeyType::Empty]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    // beat 0 is written at the start of the audio, so the first bpm change is a beat in
+    let info = write::to_beat_saber_info(&chart).unwrap();
+    assert!(info.contains("\"_songTimeOffset\":0"));
+    let difficulty = write::to_beat_saber(&chart).unwrap();
+    assert!(difficulty.contains("{\"b\":1,\"m\":120}"));
+    let converted = parse::from_beat_saber(&info, &difficulty, 4).unwrap();
+    assert_eq!(converted.chartinfo.difficulty_name, "Expert");
+    assert_eq!(converted.chartinfo.audio_offset, 0);
+    assert_eq!(converted.timing_points.times, vec![0, 500, 2500]);
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.hitobjects.rows, chart.hitobjects.rows);
+
+    // with 12 keys every cell gets its own column
+    let chart = parse::from_beat_saber(raw_info, raw_v3, 12).unwrap();
+    let columns: Vec<Vec<usize>> = chart.hitobjects.rows.iter()
+        .map(|row| (0..12).filter(|column| row[*column].key_type != KeyType::Empty).collect())
+        .collect();
+    assert_eq!(columns, vec![vec![0], vec![3, 11], vec![4, 6, 9], vec![6, 9], vec![6]]);
+
+    let chart = parse::from_beat_saber(raw_info, raw_v3, 8).unwrap();
+    let converted = parse::from_beat_saber(&write::to_beat_saber_info(&chart).unwrap(), &write::to_beat_saber(&chart).unwrap(), 8).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.hitobjects.rows, chart.hitobjects.rows);
+
+    let raw_v2 = r#"{
+    "_version": "2.0.0",
+    "_notes": [
+        {"_time": 0, "_lineIndex": 0, "_lineLayer": 0, "_type": 0, "_cutDirection": 1},
+        {"_time": 1, "_lineIndex": 3, "_lineLayer": 0, "_type": 3, "_cutDirection": 0},
+        {"_time": 1, "_lineIndex": 3, "_lineLayer": 1, "_type": 1, "_cutDirection": 0}
+    ],
+    "_obstacles": [{"_time": 2, "_lineIndex": 1, "_type": 1, "_duration": 1, "_width": 1}],
+    "_events": [{"_time": 2, "_type": 100, "_value": 0, "_floatValue": 60}]
+}"#;
+    // the note and the bomb end up in the same column and the note is kept
+    let chart = parse::from_beat_saber(raw_info, raw_v2, 4).unwrap();
+    assert_eq!(chart.hitobjects.times, vec![500, 1000, 1500, 2500]);
+    assert_eq!(chart.hitobjects.rows[1][3].key_type, KeyType::Normal);
+    assert_eq!(chart.hitobjects.rows[2][1].key_type, KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[2][1].slider_end_time(), Some(2500));
+
+    assert!(parse::from_beat_saber(raw_info, raw_v2, 13).is_err());
+}
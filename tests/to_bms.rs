@@ -0,0 +1,196 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_bms_test() {
+    parse_and_convert!(
+        osu_to_bms,
+        "./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [Disappearance].osu",
+        parse::from_osu,
+        write::to_bms,
+        true
+    );
+}
+
+#[test]
+fn sm_to_bms_test() {
+    parse_and_convert!(
+        sm_to_bms,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_bms,
+        true
+    );
+}
+
+#[test]
+fn qua_to_bms_test() {
+    parse_and_convert!(
+        qua_to_bms,
+        "./tests/Maps/quaver/2366_177_NewNonBiyori/19248.qua",
+        parse::from_qua,
+        write::to_bms,
+        true
+    );
+}
+
+#[test]
+fn qua_keysounds_to_bms_test() {
+    use rgc_chart::models::common::KeyType;
+
+    let raw_chart = "AudioFile: audio.mp3
+BackgroundFile: bg.jpg
+Mode: Keys7
+Title: Keysound Test
+Artist: Someone
+Creator: Somebody
+DifficultyName: Hard
+CustomAudioSamples:
+- Path: kick.wav
+- Path: snare.wav
+SoundEffects:
+- StartTime: 1000
+  Sample: 2
+  Volume: 80
+TimingPoints:
+- StartTime: 500
+  Bpm: 120
+SliderVelocities: []
+HitObjects:
+- StartTime: 500
+  Lane: 1
+  KeySounds:
+  - Sample: 1
+    Volume: 100
+- StartTime: 1000
+  Lane: 7
+  EndTime: 2000
+  KeySounds:
+  - Sample: 2
+    Volume: 100
+- StartTime: 1250
+  Lane: 4
+  KeySounds: []
+";
+    let chart = parse::from_qua(raw_chart).unwrap();
+    let bms = write::to_bms(&chart).unwrap();
+    assert!(bms.contains("#WAV01 kick.wav\n"));
+    assert!(bms.contains("#WAV02 snare.wav\n"));
+    assert!(bms.contains("#WAV03 audio.mp3\n"));
+
+    // the song starts a beat before the first note so a measure gets added in front
+    let converted = parse::from_bms(&bms).unwrap();
+    assert_eq!(converted.chartinfo.key_count, 8);
+    assert_eq!(converted.hitobjects.times, vec![2000, 2500, 2750, 3500]);
+
+    let rows = &converted.hitobjects.rows;
+    assert_eq!(rows[0][1].key_type, KeyType::Normal);
+    assert_eq!(rows[1][7].key_type, KeyType::SliderStart);
+    assert_eq!(rows[1][7].slider_end_time(), Some(3500));
+    assert_eq!(rows[2][4].key_type, KeyType::Normal);
+    assert_eq!(rows[3][7].key_type, KeyType::SliderEnd);
+
+    let soundbank = converted.soundbank.as_ref().unwrap();
+    let keysounds = &converted.hitobjects.keysounds;
+    assert_eq!(soundbank.get_sound_sample(keysounds[0][1].sample.unwrap()).unwrap(), "kick.wav");
+    assert_eq!(soundbank.get_sound_sample(keysounds[1][7].sample.unwrap()).unwrap(), "snare.wav");
+    assert!(keysounds[2].is_empty);
+
+    let sound_effects: Vec<(i32, String)> = soundbank.sound_effects.iter()
+        .map(|sound_effect| (sound_effect.time, soundbank.get_sound_sample(sound_effect.sample).unwrap()))
+        .collect();
+    assert_eq!(sound_effects, vec![(1500, "audio.mp3".to_string()), (2500, "snare.wav".to_string())]);
+}
+
+#[test]
+fn bms_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = "*---------------------- HEADER FIELD
+#PLAYER 1
+#TITLE Test Song
+#SUBTITLE [Another]
+#ARTIST Someone
+#SUBARTIST obj: Somebody
+#BPM 120
+#LNTYPE 1
+#WAV01 kick.wav
+#WAV02 snare.wav
+#WAV03 bgm.ogg
+#BPM01 240
+#STOP01 48
+
+*---------------------- MAIN DATA FIELD
+#00101:03
+#00111:01000100
+#00116:02
+#00152:0101
+#00219:01
+#00202:0.75
+#00308:01
+#00309:0001
+#00318:01010101
+#RANDOM 2
+#IF 1
+#00412:01
+#ENDIF
+#IF 2
+#00413:01
+#ENDIF
+#ENDRANDOM
+";
+    let chart = parse::from_bms(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Test Song");
+    assert_eq!(chart.metadata.creator, "obj: Somebody");
+    assert_eq!(chart.chartinfo.difficulty_name, "[Another]");
+    assert_eq!(chart.chartinfo.key_count, 8);
+
+    // 120bpm, measure 2 is 3 beats long, 240bpm from measure 3 with a beat long stop in the middle of it
+    assert_eq!(chart.hitobjects.times, vec![2000, 3000, 4000, 5500, 5750, 6000, 6500, 6750]);
+    let key_types = |row: usize| chart.hitobjects.rows[row].iter().map(|key| key.key_type).collect::<Vec<_>>();
+    assert_eq!(&key_types(0)[..3], &[KeyType::Normal, KeyType::Normal, KeyType::SliderStart]);
+    assert_eq!(chart.hitobjects.rows[0][2].slider_end_time(), Some(3000));
+    assert_eq!(key_types(1)[2], KeyType::SliderEnd);
+    assert_eq!(key_types(2)[7], KeyType::Normal);
+    assert_eq!(key_types(3)[6], KeyType::Normal);
+    // #RANDOM always takes the first branch
+    assert_eq!(key_types(7)[2], KeyType::Normal);
+    assert_eq!(chart.warnings.len(), 1);
+
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    let sample_path = |sample: Option<usize>| soundbank.get_sound_sample(sample.unwrap()).unwrap();
+    assert_eq!(sample_path(chart.hitobjects.keysounds[0][1].sample), "kick.wav");
+    assert_eq!(sample_path(chart.hitobjects.keysounds[0][0].sample), "snare.wav");
+    assert_eq!(soundbank.sound_effects.len(), 1);
+    assert_eq!(soundbank.sound_effects[0].time, 2000);
+    assert_eq!(sample_path(Some(soundbank.sound_effects[0].sample)), "bgm.ogg");
+
+    let time_signatures: Vec<(f32, f32)> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::TimeSignature)
+        .map(|view| (*view.beat, *view.value))
+        .collect();
+    assert_eq!(time_signatures, vec![(8.0, 3.0), (11.0, 4.0)]);
+    assert!(write::to_osu(&chart).is_ok());
+
+    // #LNOBJ ends the long note started by the note before it, the second player's scratch makes it 10k
+    let chart = parse::from_bms("#BPM 60\n#WAV01 a.wav\n#LNOBJ ZZ\n#00111:01ZZ\n#00126:01").unwrap();
+    assert_eq!(chart.chartinfo.key_count, 12);
+    assert_eq!(chart.hitobjects.times, vec![4000, 6000]);
+    assert_eq!(chart.hitobjects.rows[0][1].key_type, KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[0][11].key_type, KeyType::Normal);
+    assert_eq!(chart.hitobjects.rows[1][1].key_type, KeyType::SliderEnd);
+
+    // a 10k chart that doesn't use scratch or the second player's first key is still 10k,
+    // it's only pop'n when it says it's for a single player
+    let chart = parse::from_bms("#PLAYER 3\n#BPM 60\n#00111:01\n#00122:01\n#00125:01").unwrap();
+    assert_eq!(chart.chartinfo.key_count, 12);
+    let chart = parse::from_bms("#BPM 60\n#00111:01\n#00122:01\n#00125:01").unwrap();
+    assert_eq!(chart.chartinfo.key_count, 12);
+    let chart = parse::from_bms("#PLAYER 1\n#BPM 60\n#00111:01\n#00122:01\n#00125:01").unwrap();
+    assert_eq!(chart.chartinfo.key_count, 9);
+
+    // cells that aren't two ascii characters are skipped instead of panicking
+    let chart = parse::from_bms("#BPM 60\n#00111:0あ\n#00112:01ああ01").unwrap();
+    assert_eq!(chart.hitobjects.times, vec![4000, 7200]);
+    assert!(chart.warnings.is_empty());
+}
@@ -0,0 +1,166 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_bmson_test() {
+    parse_and_convert!(
+        osu_to_bmson,
+        "./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [Disappearance].osu",
+        parse::from_osu,
+        write::to_bmson,
+        true
+    );
+}
+
+#[test]
+fn sm_to_bmson_test() {
+    parse_and_convert!(
+        sm_to_bmson,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_bmson,
+        true
+    );
+}
+
+#[test]
+fn bmson_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = r#"{
+    "version": "1.0.0",
+    "info": {
+        "title": "Bmson Test",
+        "artist": "Someone",
+        "subartists": ["obj: Somebody"],
+        "genre": "Test",
+        "mode_hint": "beat-7k",
+        "chart_name": "HYPER",
+        "init_bpm": 120,
+        "resolution": 480
+    },
+    "lines": [{"y": 0}, {"y": 1920}, {"y": 2880}],
+    "bpm_events": [{"y": 1920, "bpm": 240}],
+    "stop_events": [{"y": 960, "duration": 480}],
+    "sound_channels": [
+        {"name": "bgm.ogg", "notes": [{"x": 0, "y": 0, "l": 0, "c": false}]},
+        {"name": "a.wav", "notes": [
+            {"x": 8, "y": 480, "l": 0, "c": false},
+            {"x": 1, "y": 960, "l": 960, "c": false}
+        ]},
+        {"name": "b.wav", "notes": [
+            {"x": 2, "y": 1920, "l": 0, "c": true},
+            {"x": null, "y": 2400, "l": 0, "c": false}
+        ]}
+    ]
+}"#;
+    let chart = parse::from_bmson(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Bmson Test");
+    assert_eq!(chart.metadata.creator, "obj: Somebody");
+    assert_eq!(chart.chartinfo.difficulty_name, "HYPER");
+    assert_eq!(chart.chartinfo.key_count, 8);
+
+    // the stop on beat 2 holds for a beat at 120bpm
+    assert_eq!(chart.hitobjects.times, vec![500, 1000, 2500]);
+    let rows = &chart.hitobjects.rows;
+    assert_eq!(rows[0][0].key_type, KeyType::Normal);
+    assert_eq!(rows[1][1].key_type, KeyType::SliderStart);
+    assert_eq!(rows[1][1].slider_end_time(), Some(2500));
+    assert_eq!(rows[2][1].key_type, KeyType::SliderEnd);
+    assert_eq!(rows[2][2].key_type, KeyType::Normal);
+
+    let time_signatures: Vec<f32> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::TimeSignature)
+        .map(|view| *view.value)
+        .collect();
+    assert_eq!(time_signatures, vec![2.0]);
+
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    let keysounds = &chart.hitobjects.keysounds;
+    assert_eq!(soundbank.get_sound_sample(keysounds[0][0].sample.unwrap()).unwrap(), "a.wav");
+    assert_eq!(soundbank.get_sound_sample(keysounds[2][2].sample.unwrap()).unwrap(), "b.wav");
+
+    let sound_effects: Vec<(i32, String)> = soundbank.sound_effects.iter()
+        .map(|sound_effect| (sound_effect.time, soundbank.get_sound_sample(sound_effect.sample).unwrap()))
+        .collect();
+    assert_eq!(sound_effects, vec![(0, "bgm.ogg".to_string()), (2750, "b.wav".to_string())]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    let converted = parse::from_bmson(&write::to_bmson(&chart).unwrap()).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.chartinfo.key_count, 8);
+}
+
+#[test]
+fn qua_keysounds_to_bmson_test() {
+    let raw_chart = "AudioFile: audio.mp3
+BackgroundFile: bg.jpg
+Mode: Keys7
+Title: Keysound Test
+Artist: Someone
+Creator: Somebody
+DifficultyName: Hard
+CustomAudioSamples:
+- Path: kick.wav
+- Path: snare.wav
+SoundEffects:
+- StartTime: 1000
+  Sample: 2
+  Volume: 80
+TimingPoints:
+- StartTime: 500
+  Bpm: 120
+SliderVelocities: []
+HitObjects:
+- StartTime: 500
+  Lane: 1
+  KeySounds:
+  - Sample: 1
+    Volume: 100
+- StartTime: 1000
+  Lane: 7
+  EndTime: 2000
+  KeySounds:
+  - Sample: 2
+    Volume: 100
+- StartTime: 1250
+  Lane: 4
+  KeySounds: []
+";
+    let chart = parse::from_qua(raw_chart).unwrap();
+    let bmson = write::to_bmson(&chart).unwrap();
+    assert!(bmson.contains(r#""mode_hint":"generic-7keys""#));
+    assert!(bmson.contains(r#"{"name":"kick.wav","notes":[{"x":1,"y":960,"l":0,"c":false}]}"#));
+
+    // the song starts a beat before the first note so a measure gets added in front
+    let converted = parse::from_bmson(&bmson).unwrap();
+    assert_eq!(converted.chartinfo.key_count, 7);
+    assert_eq!(converted.hitobjects.times, vec![2000, 2500, 2750, 3500]);
+    assert_eq!(converted.hitobjects.rows[1][6].slider_end_time(), Some(3500));
+
+    let soundbank = converted.soundbank.as_ref().unwrap();
+    let keysounds = &converted.hitobjects.keysounds;
+    assert_eq!(soundbank.get_sound_sample(keysounds[0][0].sample.unwrap()).unwrap(), "kick.wav");
+    assert_eq!(soundbank.get_sound_sample(keysounds[1][6].sample.unwrap()).unwrap(), "snare.wav");
+    assert!(keysounds[2].is_empty);
+
+    let sound_effects: Vec<(i32, String)> = soundbank.sound_effects.iter()
+        .map(|sound_effect| (sound_effect.time, soundbank.get_sound_sample(sound_effect.sample).unwrap()))
+        .collect();
+    assert_eq!(sound_effects, vec![(1500, "audio.mp3".to_string()), (2500, "snare.wav".to_string())]);
+}
+
+#[test]
+fn bmson_nesting_test() {
+    let bmson = |depth: usize| format!(
+        r#"{{"version": "1.0.0", "info": {{"title": "Nesting", "init_bpm": 120, "resolution": 240}}, "sound_channels": [{{"name": "a.wav", "notes": [{{"x": 1, "y": 0, "l": 0, "c": false}}]}}], "extra": {}{}}}"#,
+        "[".repeat(depth), "]".repeat(depth)
+    );
+
+    // deep nesting is an error instead of running out of stack
+    assert!(parse::from_bmson(&bmson(127)).is_ok());
+    assert!(parse::from_bmson(&bmson(128)).is_err());
+    assert!(parse::from_bmson(&bmson(1_000_000)).is_err());
+}
\ No newline at end of file
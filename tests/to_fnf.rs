@@ -0,0 +1,97 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_fnf_test() {
+    parse_and_convert!(
+        osu_to_fnf,
+        "./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [Disappearance].osu",
+        parse::from_osu,
+        write::to_fnf,
+        true
+    );
+}
+
+#[test]
+fn sm_to_fnf_test() {
+    parse_and_convert!(
+        sm_to_fnf,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_fnf,
+        true
+    );
+}
+
+#[test]
+fn qua_to_fnf_test() {
+    parse_and_convert!(
+        qua_to_fnf,
+        "./tests/Maps/quaver/4548_886_Ziqqurat/34785.qua",
+        parse::from_qua,
+        write::to_fnf,
+        true
+    );
+}
+
+#[test]
+fn fnf_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = r#"{"song": {
+    "song": "Fnf Test",
+    "bpm": 150,
+    "needsVoices": true,
+    "player1": "bf",
+    "player2": "dad",
+    "speed": 2.1,
+    "notes": [
+        {"lengthInSteps": 16, "mustHitSection": true, "bpm": 150, "changeBPM": false, "sectionNotes": [[0, 0, 0], [400, 5, 0], [800, 1, 300]]},
+        {"lengthInSteps": 16, "mustHitSection": false, "bpm": 150, "changeBPM": false, "sectionNotes": [[1600, 4, 0], [2000, 2, 0], [2400, -1, 0, "Hey!"]]},
+        {"sectionBeats": 4, "mustHitSection": true, "bpm": 100, "changeBPM": true, "sectionNotes": [[3200, 3, 0]]}
+    ]
+}}"#;
+    let chart = parse::from_fnf(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Fnf Test");
+    assert_eq!(chart.chartinfo.song_path, "Inst.ogg");
+    assert_eq!(chart.chartinfo.key_count, 4);
+    // lanes 0 to 3 are the player's when they have to hit the section and 4 to 7 when they don't
+    assert_eq!(chart.hitobjects.times, vec![0, 800, 1100, 1600, 3200]);
+    let key_type = |row: usize, column: usize| chart.hitobjects.rows[row][column].key_type;
+    assert_eq!(key_type(0, 0), KeyType::Normal);
+    assert_eq!(key_type(1, 1), KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][1].slider_end_time(), Some(1100));
+    assert_eq!(key_type(2, 1), KeyType::SliderEnd);
+    assert_eq!(key_type(3, 0), KeyType::Normal);
+    assert_eq!(key_type(4, 3), KeyType::Normal);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![(0, TimingChangeType::Bpm, 150.0), (3200, TimingChangeType::Bpm, 100.0)]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    let fnf = write::to_fnf(&chart).unwrap();
+    assert!(fnf.contains(r#""sectionNotes":[[0,0,0],[800,1,300]]"#));
+    let converted = parse::from_fnf(&fnf).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.timing_points.times, chart.timing_points.times);
+
+    // the opponent's lanes are on the left
+    let chart = parse::from_fnf_both_sides(raw_chart).unwrap();
+    assert_eq!(chart.chartinfo.key_count, 8);
+    assert_eq!(chart.hitobjects.times, vec![0, 400, 800, 1100, 1600, 2000, 3200]);
+    let columns: Vec<usize> = chart.hitobjects.rows.iter()
+        .map(|row| row.iter().position(|key| key.key_type != KeyType::Empty).unwrap())
+        .collect();
+    assert_eq!(columns, vec![4, 1, 5, 5, 4, 2, 7]);
+
+    let converted = parse::from_fnf_both_sides(&write::to_fnf(&chart).unwrap()).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.hitobjects.rows, chart.hitobjects.rows);
+
+    let qua = std::fs::read_to_string("./tests/Maps/quaver/2366_177_NewNonBiyori/19248.qua").unwrap();
+    assert!(write::to_fnf(&parse::from_qua(&qua).unwrap()).is_err());
+}
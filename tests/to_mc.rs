@@ -0,0 +1,125 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_mc_test() {
+    parse_and_convert!(
+        osu_to_mc,
+        "./tests/Maps/osu/360565_HatsuneMikuNoShoushitsu/cosMo@BousouP feat. Hatsune Miku - Hatsune Miku no Shoushitsu (juankristal) [Disappearance].osu",
+        parse::from_osu,
+        write::to_mc,
+        true
+    );
+}
+
+#[test]
+fn sm_to_mc_test() {
+    parse_and_convert!(
+        sm_to_mc,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_mc,
+        true
+    );
+}
+
+#[test]
+fn qua_to_mc_test() {
+    parse_and_convert!(
+        qua_to_mc,
+        "./tests/Maps/quaver/2366_177_NewNonBiyori/19248.qua",
+        parse::from_qua,
+        write::to_mc,
+        true
+    );
+}
+
+#[test]
+fn mc_to_osu_test() {
+    use rgc_chart::models::common::{KeyType, TimingChangeType};
+
+    let raw_chart = r#"{
+    "meta": {
+        "$ver": 0,
+        "creator": "Someone",
+        "background": "bg.jpg",
+        "version": "4K Hard",
+        "preview": 1000,
+        "mode": 0,
+        "song": {"title": "Malody Test", "artist": "Artist", "titleorg": "Malody テスト"},
+        "mode_ext": {"column": 4, "bar_begin": 0}
+    },
+    "time": [{"beat": [0, 0, 1], "bpm": 120}, {"beat": [4, 0, 1], "bpm": 240}],
+    "effect": [{"beat": [2, 0, 1], "scroll": 0.5}],
+    "note": [
+        {"beat": [0, 0, 1], "column": 0},
+        {"beat": [1, 1, 2], "endbeat": [4, 0, 1], "column": 3, "sound": "clap.wav", "vol": 80},
+        {"beat": [5, 0, 1], "column": 1},
+        {"beat": [0, 0, 1], "sound": "song.ogg", "vol": 100, "offset": 250, "type": 1}
+    ]
+}"#;
+    let chart = parse::from_mc(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Malody Test");
+    assert_eq!(chart.metadata.alt_title, "Malody テスト");
+    assert_eq!(chart.metadata.alt_artist, "Artist");
+    assert_eq!(chart.chartinfo.difficulty_name, "4K Hard");
+    assert_eq!(chart.chartinfo.song_path, "song.ogg");
+    assert_eq!(chart.chartinfo.audio_offset, 250);
+    assert_eq!(chart.chartinfo.key_count, 4);
+
+    assert_eq!(chart.hitobjects.times, vec![250, 1000, 2250, 2500]);
+    let rows = &chart.hitobjects.rows;
+    assert_eq!(rows[0][0].key_type, KeyType::Normal);
+    assert_eq!(rows[1][3].key_type, KeyType::SliderStart);
+    assert_eq!(rows[1][3].slider_end_time(), Some(2250));
+    assert_eq!(rows[2][3].key_type, KeyType::SliderEnd);
+    assert_eq!(rows[3][1].key_type, KeyType::Normal);
+
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    let keysound = &chart.hitobjects.keysounds[1][3];
+    assert_eq!(soundbank.get_sound_sample(keysound.sample.unwrap()).unwrap(), "clap.wav");
+    assert_eq!(keysound.volume, 80);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![
+        (250, TimingChangeType::Bpm, 120.0),
+        (1250, TimingChangeType::Sv, 0.5),
+        (2250, TimingChangeType::Bpm, 240.0),
+    ]);
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert_eq!(parse::from_osu(&osu).unwrap().hitobjects.times, chart.hitobjects.times);
+
+    let mc = write::to_mc(&chart).unwrap();
+    assert!(mc.contains(r#"{"beat":[1,1,2],"endbeat":[4,0,1],"column":3,"sound":"clap.wav","vol":80}"#));
+    let converted = parse::from_mc(&mc).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.chartinfo.audio_offset, 250);
+    assert_eq!(converted.timing_points.times, chart.timing_points.times);
+
+    // only key mode charts have columns
+    assert!(parse::from_mc(&raw_chart.replace(r#""mode": 0"#, r#""mode": 3"#)).is_err());
+
+    // beats too big to be numbers and columns past the key count are left out
+    let broken_chart = raw_chart
+        .replace(r#"{"beat": [5, 0, 1], "column": 1}"#, r#"{"beat": [0, 1e999, 1e999], "column": 1}"#)
+        .replace(r#"{"beat": [0, 0, 1], "column": 0}"#, r#"{"beat": [0, 0, 1], "column": 9}"#);
+    let chart = parse::from_mc(&broken_chart).unwrap();
+    assert_eq!(chart.hitobjects.times, vec![1000, 2250]);
+}
+
+#[test]
+fn sm_stops_and_delays_to_mc_test() {
+    let raw_chart = "#TITLE:Pauses;\n#OFFSET:0;\n#BPMS:0.000=120.000,3.000=240.000;\n#STOPS:1.000=0.500,3.000=0.250;\n#DELAYS:2.000=0.250;\n\
+        #NOTES:dance-single::Hard:10:0,0,0,0,0:\n1000\n0100\n0010\n0001\n,\n1000\n0000\n0000\n0000\n;";
+    let chart = parse::from_sm(raw_chart).unwrap();
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1750, 2250, 2750]);
+
+    // Malody has no stops or delays, they slow the bpm down right on or before them instead
+    let mc = write::to_mc(&chart).unwrap();
+    let converted = parse::from_mc(&mc).unwrap();
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.timing_points.bpms().len(), 7);
+}
\ No newline at end of file
@@ -22,3 +22,102 @@ fn qua_to_osu_test() {
         true
     );
 }
+
+#[test]
+fn ssc_to_osu_test() {
+    parse_and_convert!(
+        ssc_to_osu,
+        "./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.ssc",
+        parse::from_ssc,
+        write::to_osu,
+        true
+    );
+}
+
+
+#[test]
+fn sm_all_to_osu_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.sm").unwrap();
+    let charts = parse::from_sm_all(&raw_chart).unwrap();
+    assert_eq!(charts.len(), 2);
+
+    let expected = [("Hard", 12), ("Challenge", 13)];
+    for (chart, (difficulty, meter)) in charts.iter().zip(expected) {
+        let steps_info = chart.chartinfo.steps_info.as_ref().unwrap();
+        assert_eq!(steps_info.steps_type, "dance-single");
+        assert_eq!(steps_info.difficulty, difficulty);
+        assert_eq!(steps_info.meter, meter);
+        assert_eq!(steps_info.radar_values, vec![1.0, 1.0, 1.0, 0.0, 1.0]);
+        assert_eq!(chart.chartinfo.difficulty_name, difficulty);
+        assert!(write::to_osu(chart).is_ok());
+    }
+
+    // a single chart is the last #NOTES block
+    assert_eq!(parse::from_sm(&raw_chart).unwrap().chartinfo.difficulty_name, "Challenge");
+}
+
+#[test]
+fn ssc_all_to_osu_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.ssc").unwrap();
+    let charts = parse::from_ssc_all(&raw_chart).unwrap();
+    assert_eq!(charts.len(), 2);
+
+    let expected = [("Hard", 12), ("Challenge", 13)];
+    for (chart, (difficulty, meter)) in charts.iter().zip(expected) {
+        let steps_info = chart.chartinfo.steps_info.as_ref().unwrap();
+        assert_eq!(steps_info.difficulty, difficulty);
+        assert_eq!(steps_info.meter, meter);
+        assert!(write::to_osu(chart).is_ok());
+    }
+}
+
+#[test]
+fn taiko_to_osu_test() {
+    use rgc_chart::models::common::{GameMode, KeyType};
+
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [neonat's Muzukashii].osu").unwrap();
+    let chart = parse::from_osu(&raw_chart).unwrap();
+    assert_eq!(chart.chartinfo.game_mode, GameMode::Taiko);
+    assert_eq!(chart.chartinfo.key_count, 1);
+
+    let key_types: Vec<KeyType> = chart.hitobjects.rows.iter().map(|row| row[0].key_type).collect();
+    assert_eq!(key_types.len(), 937);
+    assert_eq!(key_types.iter().filter(|key_type| **key_type == KeyType::Drumroll).count(), 2);
+    assert_eq!(key_types.iter().filter(|key_type| **key_type == KeyType::Spinner).count(), 1);
+    assert!(key_types.contains(&KeyType::Kat));
+    assert!(key_types.contains(&KeyType::BigDon));
+
+    let written = write::to_osu(&chart).unwrap();
+    assert!(written.contains("Mode: 1\n"));
+
+    let reparsed = parse::from_osu(&written).unwrap();
+    assert_eq!(reparsed.hitobjects.times, chart.hitobjects.times);
+    for (row, reparsed_row) in chart.hitobjects.rows.iter().zip(&reparsed.hitobjects.rows) {
+        assert_eq!(row[0].key_type, reparsed_row[0].key_type);
+        let end_time = row[0].slider_end_time().unwrap_or(0);
+        let reparsed_end_time = reparsed_row[0].slider_end_time().unwrap_or(0);
+        assert!((end_time - reparsed_end_time).abs() <= 1);
+    }
+
+    assert!(write::to_sm(&chart).is_err());
+    assert!(write::to_qua(&chart).is_err());
+}
+
+#[test]
+fn osu_meter_and_sv_range_test() {
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Ranges;\n#OFFSET:0;\n#BPMS:0.000=120.000,3.500=120.000;\n#TIMESIGNATURES:0.000=7=8,3.500=1=16;\n\
+        #SCROLLS:0.000=1.000,\n1.000=0.000,\n2.000=20.000;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#NOTES:\n1000\n0100\n0010\n0001\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    let osu = write::to_osu(&chart).unwrap();
+    let timing_points: Vec<&str> = osu.split("[TimingPoints]\n").nth(1).unwrap().lines().take_while(|line| !line.is_empty()).collect();
+    // 3.5 beats is rounded to a meter of 4 and a quarter of a beat is still 1, svs stay between 0.01x and 10x
+    assert_eq!(timing_points, vec![
+        "0,500,4,1,0,100,1,0",
+        "0,-100,4,1,0,100,0,0",
+        "500,-10000,4,1,0,100,0,0",
+        "1000,-10,4,1,0,100,0,0",
+        "1750,500,1,1,0,100,1,0",
+    ]);
+}
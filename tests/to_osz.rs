@@ -0,0 +1,58 @@
+#[allow(dead_code)]
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osz_to_osu_test() {
+    use rgc_chart::models::mapset::AssetFile;
+
+    // the background and Tom1.wav are stand-ins, and the background's name is in a different case than in the charts
+    let raw_osz = std::fs::read("./tests/Maps/osz/1356087_YoruNiKakeru.osz").unwrap();
+    let mapset = parse::from_osz(&raw_osz).unwrap();
+    assert_eq!(mapset.charts.len(), 4);
+
+    let difficulties: Vec<&str> = mapset.charts.iter().map(|chart| chart.chartinfo.difficulty_name.as_str()).collect();
+    assert_eq!(difficulties, vec!["Easy", "Fapu's Normal", "Hard", "Insane"]);
+    let raw_chart = read_file_to_string("./tests/Maps/osu/1356087_YoruNiKakeru/Kisaragi Rumii - Yoru ni Kakeru -THE HOME TAKE ver.- (Cut Ver.) (Irone OSU) [Hard].osu").unwrap();
+    let chart = parse::from_osu(&raw_chart).unwrap();
+    assert_eq!(mapset.charts[2].hitobjects.times, chart.hitobjects.times);
+    assert_eq!(mapset.charts[2].hitobjects.rows, chart.hitobjects.rows);
+
+    let assets: Vec<(&str, bool)> = mapset.assets.iter().map(|asset| (asset.path.as_str(), asset.is_missing)).collect();
+    assert_eq!(assets, vec![
+        ("audio.mp3", true),
+        ("rumii_BG.jpg", false),
+        ("Tom1.wav", false),
+        ("Tom3.wav", true),
+        ("soft-hitclap.wav", true),
+        ("soft-hitwhistle.wav", true),
+        ("Tom2.wav", true),
+    ]);
+
+    let asset_files: Vec<AssetFile> = mapset.assets.iter()
+        .map(|asset| AssetFile::new(asset.path.clone(), asset.path.as_bytes().to_vec()))
+        .collect();
+    let osz = write::to_osz(&mapset.charts, &asset_files).unwrap();
+    let converted = parse::from_osz(&osz).unwrap();
+    assert_eq!(converted.charts.len(), 4);
+    assert!(converted.assets.iter().all(|asset| !asset.is_missing));
+    for (chart, converted_chart) in mapset.charts.iter().zip(&converted.charts) {
+        assert_eq!(converted_chart.chartinfo.difficulty_name, chart.chartinfo.difficulty_name);
+        assert_eq!(converted_chart.hitobjects.times, chart.hitobjects.times);
+    }
+
+    // difficulties with the same name don't overwrite each other
+    let osz = write::to_osz(&[chart.clone(), chart], &[]).unwrap();
+    let converted = parse::from_osz(&osz).unwrap();
+    assert_eq!(converted.charts.len(), 2);
+    assert!(converted.assets.iter().all(|asset| asset.is_missing));
+
+    assert!(parse::from_osz(b"not an archive").is_err());
+
+    // offsets past the end of the archive and names too long for a zip are errors
+    let mut broken_osz = osz.clone();
+    let central_directory_offset = broken_osz.len() - 6;
+    broken_osz[central_directory_offset..central_directory_offset + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(parse::from_osz(&broken_osz).is_err());
+    assert!(write::to_osz(&converted.charts, &[AssetFile::new("a".repeat(65536), vec![0])]).is_err());
+}
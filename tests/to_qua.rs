@@ -21,4 +21,297 @@ fn sm_to_osu_test() {
         write::to_qua,
         true
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn sma_to_qua_test() {
+    parse_and_convert!(
+        sma_to_qua,
+        "./tests/Maps/etterna/MouUtaShikaKikoenai/touhouwhatever.sma",
+        parse::from_sma,
+        write::to_qua,
+        true
+    );
+}
+
+#[test]
+fn ssc_gimmicks_to_qua_test() {
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Gimmicks;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n\
+        #SPEEDS:0.000=1.000=0.000=0,\n2.000=2.000=0.000=0;\n#SCROLLS:0.000=1.000,\n1.000=0.500;\n\
+        #FAKES:3.000=1.000;\n#TIMESIGNATURES:0.000=3=4;\n#TICKCOUNTS:0.000=2;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n\
+        #NOTES:\n1000\n0100\n0010\n0001\n,\n1000\n0000\n0000\n0000\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    // speeds and scrolls multiply each other
+    let svs: Vec<(i32, f32)> = chart.timing_points.sv_changes_with_stops();
+    assert_eq!(svs, vec![(0, 1.0), (500, 0.5), (1000, 1.0)]);
+    assert_eq!(chart.hitobjects.rows[3][3].key_type, models::common::KeyType::Fake);
+
+    let qua_chart = write::to_qua(&chart).unwrap();
+    assert!(qua_chart.contains("- StartTime: 500\n  Multiplier: 0.5"));
+    assert!(qua_chart.contains("- StartTime: 1000\n  Multiplier: 1"));
+
+    let ssc_chart = write::to_ssc(&chart).unwrap();
+    assert!(ssc_chart.contains("#SCROLLS:0.000=1.000,\n1.000=0.500,\n2.000=1.000;"));
+    assert!(ssc_chart.contains("#TIMESIGNATURES:0.000=3=4;"));
+    assert!(ssc_chart.contains("#TICKCOUNTS:0.000=2;"));
+    assert!(ssc_chart.contains("000F\n"));
+}
+
+#[test]
+fn osu_standard_converted_to_qua_test() {
+    use rgc_chart::models::common::{GameMode, KeyType};
+
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [neonat's Normal].osu").unwrap();
+    assert!(parse::from_osu(&raw_chart).is_err());
+    assert!(parse::from_osu_as_mania(&raw_chart, 0).is_err());
+
+    let chart = parse::from_osu_as_mania(&raw_chart, 7).unwrap();
+    assert_eq!(chart.chartinfo.game_mode, GameMode::Mania);
+    assert_eq!(chart.chartinfo.key_count, 7);
+    assert!(chart.hitobjects.rows.iter().all(|row| row.len() == 7));
+    assert!(chart.warnings.is_empty());
+
+    let keys: Vec<KeyType> = chart.hitobjects.rows.iter().flatten().map(|key| key.key_type).collect();
+    let sliders = keys.iter().filter(|key_type| **key_type == KeyType::SliderStart).count();
+    assert!(sliders > 0);
+    assert_eq!(sliders, keys.iter().filter(|key_type| **key_type == KeyType::SliderEnd).count());
+
+    // the conversion is seeded from the beatmap, so it comes out the same every time
+    let again = parse::from_osu_as_mania(&raw_chart, 7).unwrap();
+    assert_eq!(chart.hitobjects.times, again.hitobjects.times);
+    assert_eq!(chart.hitobjects.rows, again.hitobjects.rows);
+
+    assert!(write::to_qua(&chart).is_ok());
+
+    // catch beatmaps are converted the same way
+    let catch_chart = parse::from_osu_as_mania(&raw_chart.replace("Mode: 0", "Mode: 2"), 4).unwrap();
+    assert_eq!(catch_chart.chartinfo.key_count, 4);
+    assert!(write::to_qua(&catch_chart).is_ok());
+}
+
+#[test]
+fn osu_standard_conversion_patterns_test() {
+    use rgc_chart::models::common::KeyType;
+
+    let raw_chart = "osu file format v14
+
+[General]
+AudioFilename: audio.mp3
+Mode: 0
+
+[Metadata]
+Title:Patterns
+Artist:Someone
+Creator:Someone
+Version:Patterns
+
+[Difficulty]
+HPDrainRate:5
+CircleSize:5
+OverallDifficulty:5
+ApproachRate:5
+SliderMultiplier:1.4
+SliderTickRate:1
+
+[TimingPoints]
+0,500,4,1,0,100,1,0
+
+[HitObjects]
+0,192,1000,1,0,0:0:0:0:
+0,192,1090,1,0,0:0:0:0:
+0,192,1180,1,0,0:0:0:0:
+0,192,1270,1,0,0:0:0:0:
+0,192,1360,1,0,0:0:0:0:
+0,192,1450,1,0,0:0:0:0:
+0,192,1540,1,0,0:0:0:0:
+0,192,1630,1,0,0:0:0:0:
+256,192,3000,8,0,4000,0:0:0:0:
+0,192,5000,2,0,L|14:192,2,14
+";
+
+    let columns = |chart: &rgc_chart::Chart| -> Vec<(i32, usize, KeyType)> {
+        chart.hitobjects.times.iter().zip(&chart.hitobjects.rows)
+            .flat_map(|(time, row)| row.iter().enumerate()
+                .filter(|(_, key)| key.key_type != KeyType::Empty)
+                .map(|(column, key)| (*time, column, key.key_type)))
+            .collect()
+    };
+
+    // the seed is round(5 + 5) * 20 + (int)(5 * 41.2) + round(5) = 411
+    let chart = parse::from_osu_as_mania(raw_chart, 4).unwrap();
+    assert_eq!(columns(&chart), [
+        // the first circle goes where its x is, circles 90ms apart then walk up and down the columns
+        (1000, 0, KeyType::Normal),
+        (1090, 1, KeyType::Normal),
+        (1180, 2, KeyType::Normal),
+        (1270, 3, KeyType::Normal),
+        (1360, 2, KeyType::Normal),
+        (1450, 1, KeyType::Normal),
+        (1540, 0, KeyType::Normal),
+        (1630, 1, KeyType::Normal),
+        // the spinner is held in a random column, the second number drawn is 0.238
+        (3000, 0, KeyType::SliderStart),
+        (4000, 0, KeyType::SliderEnd),
+        // the slider is 100ms long and goes back once, so it's held in a random column other than the last circle's.
+        // 0.436 lands on that column, so 0.897 picks the last one
+        (5000, 3, KeyType::SliderStart),
+        (5100, 3, KeyType::SliderEnd),
+    ]);
+
+    // 7k+1 keeps its first column for short spinners with a finish
+    let chart = parse::from_osu_as_mania(&raw_chart.replace("3000,8,0,4000", "3000,8,4,3500"), 8).unwrap();
+    assert!(columns(&chart).contains(&(3000, 0, KeyType::SliderStart)));
+}
+
+#[test]
+fn qp_to_qua_test() {
+    use rgc_chart::models::mapset::AssetFile;
+
+    // the background is a stand-in, only its name matters
+    let raw_qp = std::fs::read("./tests/Maps/qp/2366_177_NewNonBiyori.qp").unwrap();
+    let mapset = parse::from_qp(&raw_qp).unwrap();
+    let difficulties: Vec<&str> = mapset.charts.iter().map(|chart| chart.chartinfo.difficulty_name.as_str()).collect();
+    assert_eq!(difficulties, vec!["Insane", "wotaku's Hard"]);
+    let raw_chart = read_file_to_string("./tests/Maps/quaver/2366_177_NewNonBiyori/20604.qua").unwrap();
+    assert_eq!(mapset.charts[1].hitobjects.times, parse::from_qua(&raw_chart).unwrap().hitobjects.times);
+
+    let assets: Vec<(&str, bool)> = mapset.assets.iter().map(|asset| (asset.path.as_str(), asset.is_missing)).collect();
+    assert_eq!(assets, vec![("the new non non biyori disco groove.mp3", true), ("bg.jpg", false)]);
+
+    let qp = write::to_qp(&mapset.charts, &[AssetFile::new("the new non non biyori disco groove.mp3".to_string(), vec![0; 16])]).unwrap();
+    let converted = parse::from_qp(&qp).unwrap();
+    assert_eq!(converted.charts.len(), 2);
+    assert_eq!(converted.charts[0].hitobjects.times, mapset.charts[0].hitobjects.times);
+    let assets: Vec<(&str, bool)> = converted.assets.iter().map(|asset| (asset.path.as_str(), asset.is_missing)).collect();
+    assert_eq!(assets, vec![("the new non non biyori disco groove.mp3", false), ("bg.jpg", true)]);
+
+    let qua_with_samples = |samples: &str, keysounds: [u32; 2]| format!("AudioFile: audio.mp3
+Mode: Keys4
+Title: Samples
+Artist: Someone
+Creator: Someone
+DifficultyName: {samples}
+CustomAudioSamples:
+{samples}
+SoundEffects:
+- StartTime: 500
+  Sample: 1
+TimingPoints:
+- StartTime: 0
+  Bpm: 120
+SliderVelocities: []
+HitObjects:
+- StartTime: 1000
+  Lane: 1
+  KeySounds:
+  - Sample: {}
+- StartTime: 1500
+  Lane: 2
+  KeySounds:
+  - Sample: {}
+", keysounds[0], keysounds[1]);
+    let first = parse::from_qua(&qua_with_samples("- Path: kick.wav\n- Path: snare.wav", [2, 1])).unwrap();
+    let second = parse::from_qua(&qua_with_samples("- Path: snare.wav\n- Path: hat.wav", [1, 2])).unwrap();
+
+    // every difficulty gets the same samples and the keysounds follow them
+    let converted = parse::from_qp(&write::to_qp(&[first, second], &[]).unwrap()).unwrap();
+    for chart in &converted.charts {
+        assert_eq!(chart.soundbank.as_ref().unwrap().get_sample_paths(), vec!["kick.wav", "snare.wav", "hat.wav"]);
+    }
+    let samples = |chart: usize| -> Vec<Option<usize>> {
+        let chart = &converted.charts[chart];
+        chart.hitobjects.keysounds.iter()
+            .zip(&chart.hitobjects.rows)
+            .map(|(keysounds, row)| row.iter().position(|key| key.key_type != models::common::KeyType::Empty).and_then(|column| keysounds[column].sample))
+            .collect()
+    };
+    assert_eq!(samples(0), vec![Some(1), Some(0)]);
+    assert_eq!(samples(1), vec![Some(1), Some(2)]);
+    assert_eq!(converted.charts[1].soundbank.as_ref().unwrap().sound_effects[0].sample, 1);
+
+    let assets: Vec<&str> = converted.assets.iter().map(|asset| asset.path.as_str()).collect();
+    assert_eq!(assets, vec!["audio.mp3", "kick.wav", "snare.wav", "hat.wav"]);
+}
+
+#[test]
+fn qua_sample_index_test() {
+    let raw_chart = "AudioFile: audio.mp3
+Mode: Keys4
+Title: Samples
+Artist: Someone
+Creator: Someone
+DifficultyName: Samples
+CustomAudioSamples:
+- Path: kick.wav
+- Path: snare.wav
+SoundEffects:
+- StartTime: 500
+  Sample: 2
+TimingPoints:
+- StartTime: 0
+  Bpm: 120
+SliderVelocities: []
+HitObjects:
+- StartTime: 1000
+  Lane: 1
+  KeySounds:
+  - Sample: 1
+";
+
+    // .qua samples are counted from 1
+    let chart = parse::from_qua(raw_chart).unwrap();
+    let soundbank = chart.soundbank.as_ref().unwrap();
+    assert_eq!(soundbank.get_sound_sample(chart.hitobjects.keysounds[0][0].sample.unwrap()).unwrap(), "kick.wav");
+    assert_eq!(soundbank.get_sound_sample(soundbank.sound_effects[0].sample).unwrap(), "snare.wav");
+
+    let qua = write::to_qua(&chart).unwrap();
+    assert!(qua.contains("- StartTime: 500\n  Sample: 2\n"));
+    assert!(qua.contains("  - Sample: 1\n"));
+}
+
+#[test]
+fn qp_inflate_limit_test() {
+    // a .qp with a single deflated file, the crc is left at 0
+    fn single_file_qp(name: &str, data: &[u8], size: u32) -> Vec<u8> {
+        let sizes = [0u32, data.len() as u32, size].iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<u8>>();
+        let mut qp = Vec::new();
+        qp.extend(0x04034b50u32.to_le_bytes());
+        qp.extend([20, 0, 0, 0, 8, 0, 0, 0, 0, 0]);
+        qp.extend(&sizes);
+        qp.extend((name.len() as u16).to_le_bytes());
+        qp.extend([0, 0]);
+        qp.extend(name.as_bytes());
+        qp.extend(data);
+
+        let central_directory_offset = qp.len() as u32;
+        qp.extend(0x02014b50u32.to_le_bytes());
+        qp.extend([20, 0, 20, 0, 0, 0, 8, 0, 0, 0, 0, 0]);
+        qp.extend(&sizes);
+        qp.extend((name.len() as u16).to_le_bytes());
+        qp.extend([0; 12]);
+        qp.extend(0u32.to_le_bytes());
+        qp.extend(name.as_bytes());
+
+        let central_directory_size = qp.len() as u32 - central_directory_offset;
+        qp.extend(0x06054b50u32.to_le_bytes());
+        qp.extend([0, 0, 0, 0, 1, 0, 1, 0]);
+        qp.extend(central_directory_size.to_le_bytes());
+        qp.extend(central_directory_offset.to_le_bytes());
+        qp.extend([0, 0]);
+        qp
+    }
+
+    // 114 bytes of deflate that turn into 100000 zeros
+    let mut zeros = vec![237, 193, 49, 1, 0, 0, 0, 194, 160, 245, 79, 109, 13, 15, 160];
+    zeros.extend(std::iter::repeat_n(0, 96));
+    zeros.extend([128, 87, 3]);
+
+    // inflating stops once the file is bigger than it says it is
+    let error = parse::from_qp(&single_file_qp("bomb.qua", &zeros, 16)).unwrap_err().to_string();
+    assert!(error.contains("bigger than 16 bytes"), "{error}");
+    // with the right size it gets as far as the crc
+    let error = parse::from_qp(&single_file_qp("bomb.qua", &zeros, 100000)).unwrap_err().to_string();
+    assert!(error.contains("corrupted"), "{error}");
+}
@@ -21,4 +21,132 @@ fn qua_to_sm_test() {
         write::to_sm,
         true
     );
-}
\ No newline at end of file
+}
+
+#[test]
+fn ssc_to_sm_test() {
+    parse_and_convert!(
+        ssc_to_sm,
+        "./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.ssc",
+        parse::from_ssc,
+        write::to_sm,
+        true
+    );
+}
+
+#[test]
+fn osu_7k_to_sm_test() {
+    parse_and_convert!(
+        osu_7k_to_sm,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [7K HD].osu",
+        parse::from_osu,
+        write::to_sm,
+        true
+    );
+}
+
+#[test]
+fn sm_steps_type_key_count_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [7K HD].osu").unwrap();
+    let mut chart = parse::from_osu(&raw_chart).unwrap();
+
+    let sm_chart = write::to_sm(&chart).unwrap();
+    assert!(sm_chart.contains("kb7-single:"));
+    let parsed_chart = parse::from_sm(&sm_chart).unwrap();
+    assert_eq!(parsed_chart.chartinfo.key_count, 7);
+    assert!(parsed_chart.hitobjects.rows.iter().all(|row| row.len() == 7));
+
+    chart.chartinfo.key_count = 11;
+    assert!(write::to_sm(&chart).is_err());
+}
+
+#[test]
+fn sm_stops_to_sm_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/etterna/Kil_ChineseTea/ct.sm").unwrap();
+    let chart = parse::from_sm(&raw_chart).unwrap();
+
+    let sm_chart = write::to_sm(&chart).unwrap();
+    assert!(sm_chart.contains("#STOPS:31.000=0.001,\n31.250=0.001,"));
+    assert!(sm_chart.contains("272.000=0.002;"));
+
+    let parsed_chart = parse::from_sm(&sm_chart).unwrap();
+    assert_eq!(parsed_chart.hitobjects.times, chart.hitobjects.times);
+}
+
+#[test]
+fn sm_delays_and_warps_to_ssc_test() {
+    let raw_chart = "#TITLE:Gimmicks;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#STOPS:1.000=0.500;\n#DELAYS:2.000=0.250;\n#WARPS:3.000=0.500;\n\
+        #NOTES:dance-single::Hard:10:0,0,0,0,0:\n1000\n0100\n0010\n0001\n,\n1000\n0000\n0000\n0000\n;";
+    let chart = parse::from_sm(raw_chart).unwrap();
+    // the stop comes after the note on beat 1, the delay before the note on beat 2
+    // and the warp skips half of the 4th beat
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1750, 2250, 2500]);
+
+    let ssc_chart = write::to_ssc(&chart).unwrap();
+    assert!(ssc_chart.contains("#STOPS:1.000=0.500;"));
+    assert!(ssc_chart.contains("#DELAYS:2.000=0.250;"));
+    assert!(ssc_chart.contains("#WARPS:3.000=0.500;"));
+    assert_eq!(parse::from_ssc(&ssc_chart).unwrap().hitobjects.times, chart.hitobjects.times);
+}
+
+#[test]
+fn osu_sv_stops_to_sm_test() {
+    let raw_chart = read_file_to_string("./tests/Maps/osu/1062298_MarisaStole/IOSYS - Marisa wa Taihen na Mono wo Nusunde Ikimashita (notapplicable) [SVanghai Doll].osu").unwrap();
+    let chart = parse::from_osu(&raw_chart).unwrap();
+
+    // svs are only turned into stops when asked for
+    let sm_chart = write::to_sm(&chart).unwrap();
+    assert!(sm_chart.contains("#DELAYS:;"));
+
+    let sm_chart = write::to_sm_with_sv_stops(&chart).unwrap();
+    assert!(sm_chart.contains("#DELAYS:254.062=0.066,"));
+    let parsed_chart = parse::from_sm(&sm_chart).unwrap();
+    assert_eq!(parsed_chart.hitobjects.rows.len(), chart.hitobjects.rows.len());
+
+    let sma_chart = write::to_sma_with_sv_stops(&chart).unwrap();
+    assert!(sma_chart.contains("#DELAYS:254.062=0.066,"));
+}
+
+#[test]
+fn sm_rolls_and_lifts_test() {
+    use models::common::KeyType;
+
+    let raw_chart = "#TITLE:Rolls;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n\
+        #NOTES:dance-single::Hard:10:0,0,0,0,0:\n4200\n0000\nL000\n3300\n;";
+    let chart = parse::from_sm(raw_chart).unwrap();
+    assert_eq!(chart.hitobjects.rows[0][0].key_type, KeyType::Roll);
+    assert_eq!(chart.hitobjects.rows[0][1].key_type, KeyType::SliderStart);
+    assert_eq!(chart.hitobjects.rows[1][0].key_type, KeyType::Lift);
+
+    let sm_chart = write::to_sm(&chart).unwrap();
+    assert!(sm_chart.contains("4200\n0000\nL000\n3300\n"));
+
+    // rolls become normal sliders and lifts normal notes
+    let osu_chart = parse::from_osu(&write::to_osu(&chart).unwrap()).unwrap();
+    assert_eq!(osu_chart.hitobjects.rows[0][0].key_type, KeyType::SliderStart);
+    assert_eq!(osu_chart.hitobjects.rows[1][0].key_type, KeyType::Normal);
+    let qua_chart = parse::from_qua(&write::to_qua(&chart).unwrap()).unwrap();
+    assert_eq!(qua_chart.hitobjects.rows[0][0].key_type, KeyType::SliderStart);
+    assert_eq!(qua_chart.hitobjects.rows[1][0].key_type, KeyType::Normal);
+}
+
+#[test]
+fn sm_bpms_and_stops_test() {
+    use models::common::TimingChangeType;
+
+    let raw_chart = "#TITLE:Stops;\n#OFFSET:0;\n#BPMS:0.000=120.000,2.000=60.000;\n#STOPS:1.000=0.750;\n\
+        #NOTES:dance-single::Hard:10:0,0,0,0,0:\n1000\n0100\n0010\n0001\n;";
+    let chart = parse::from_sm(raw_chart).unwrap();
+
+    // the stop keeps its length in ms and starts at the note on its beat
+    let timing_points: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing_points, vec![
+        (0, TimingChangeType::Bpm, 120.0),
+        (500, TimingChangeType::Stop, 750.0),
+        (1750, TimingChangeType::Bpm, 60.0),
+    ]);
+    // notes after the stop are pushed back by it
+    assert_eq!(chart.hitobjects.times, vec![0, 500, 1750, 2750]);
+}
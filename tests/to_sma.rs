@@ -0,0 +1,51 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_sma_test() {
+    parse_and_convert!(
+        osu_to_sma,
+        "./tests/Maps/osu/1888601_LunaticEyes/COOL&CREATE - Lunatic Eyes ~ Invisible Full Moon (Cut Ver.) (TheFunk) [Blood Moon].osu",
+        parse::from_osu,
+        write::to_sma,
+        true
+    );
+}
+
+#[test]
+fn sm_to_sma_test() {
+    parse_and_convert!(
+        sm_to_sma,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_sma,
+        true
+    );
+}
+
+#[test]
+fn ssc_beats_per_measure_to_sma_test() {
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Meters;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#TIMESIGNATURES:0.000=4=4,4.010=3=4;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#NOTES:\n1000\n0100\n0010\n0001\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    // time signatures are snapped to rows like every other timing segment
+    let sma_chart = write::to_sma(&chart).unwrap();
+    assert!(sma_chart.contains("#BEATSPERMEASURE:0.000=4,\n4.000=3;"));
+}
+
+#[test]
+fn ssc_scrolls_to_sma_test() {
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Scrolls;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#SCROLLS:0.000=1.000,\n4.010=0.500;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#NOTES:\n1000\n0100\n0010\n0001\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    // speeds are snapped to rows like every other timing segment
+    let sma_chart = write::to_sma(&chart).unwrap();
+    assert!(sma_chart.contains("#SPEED:0.000=1.000=0.000,\n4.000=0.500=0.000;"));
+
+    // svs are written as scrolls, so there are no speeds to write
+    let ssc_chart = write::to_ssc(&chart).unwrap();
+    assert!(ssc_chart.contains("#SCROLLS:0.000=1.000,\n4.000=0.500;"));
+    assert!(!ssc_chart.contains("#SPEEDS"));
+}
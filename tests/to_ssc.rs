@@ -0,0 +1,161 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn osu_to_ssc_test() {
+    parse_and_convert!(
+        osu_to_ssc,
+        "./tests/Maps/osu/1888601_LunaticEyes/COOL&CREATE - Lunatic Eyes ~ Invisible Full Moon (Cut Ver.) (TheFunk) [Blood Moon].osu",
+        parse::from_osu,
+        write::to_ssc,
+        true
+    );
+}
+
+#[test]
+fn sm_to_ssc_test() {
+    parse_and_convert!(
+        sm_to_ssc,
+        "./tests/Maps/etterna/Kil_ChineseTea/ct.sm",
+        parse::from_sm,
+        write::to_ssc,
+        true
+    );
+}
+
+#[test]
+fn sm_pack_to_ssc_test() {
+    use rgc_chart::models::mapset::{AssetFile, Mapset};
+
+    // UNOwenWasHer only has a .dwi so it isn't a song
+    let pack = parse::from_sm_pack("./tests/Maps/etterna").unwrap();
+    assert!(pack.failed_songs.is_empty());
+    let songs = pack.mapsets;
+    let assets = |song: &Mapset| -> Vec<(String, bool)> {
+        song.assets.iter().map(|asset| (asset.path.clone(), asset.is_missing)).collect()
+    };
+    let backgrounds: Vec<&str> = songs.iter().map(|song| song.charts[0].chartinfo.bg_path.as_str()).collect();
+    assert_eq!(backgrounds, vec!["chinese-bg.PNG", "uta-bg.png", "molc-BG.jpg"]);
+
+    // the .ssc is picked over the .sm and the background nobody set is found by its name
+    let song = parse::from_sm_folder("./tests/Maps/etterna/MysticOrientalLoveConsultant").unwrap();
+    let raw_chart = read_file_to_string("./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.ssc").unwrap();
+    let charts = parse::from_ssc_all(&raw_chart).unwrap();
+    assert_eq!(song.charts.len(), charts.len());
+    assert_eq!(song.charts[1].hitobjects.times, charts[1].hitobjects.times);
+    assert_eq!(assets(&song), vec![
+        ("Mystic Oriental Love Consultant.ogg".to_string(), false),
+        ("molc-BG.jpg".to_string(), false),
+    ]);
+    assert_eq!(assets(&songs[2]), assets(&song));
+    assert_eq!(assets(&songs[1]), vec![("MouUtaShikaKikoenai.mp3".to_string(), true), ("uta-bg.png".to_string(), false)]);
+
+    let raw_sm = read_file_to_string("./tests/Maps/etterna/Kil_ChineseTea/ct.sm").unwrap()
+        .lines()
+        .map(|line| if line.starts_with("#MUSIC:") { "#MUSIC:CHINESE TEA.MP3;" } else { line })
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let files = vec![
+        AssetFile::new("ct.sm".to_string(), raw_sm.into_bytes()),
+        AssetFile::new("Chinese Tea.mp3".to_string(), vec![0; 16]),
+        AssetFile::new("chinese-bg.PNG".to_string(), vec![0; 16]),
+    ];
+    let song = parse::from_sm_folder_files(&files).unwrap();
+    assert_eq!(song.charts[0].chartinfo.song_path, "Chinese Tea.mp3");
+    assert!(song.assets.iter().all(|asset| !asset.is_missing));
+
+    // a song that can't be parsed doesn't stop the rest of the pack
+    let mut packed: Vec<AssetFile> = files.iter()
+        .map(|file| AssetFile::new(format!("Kil_ChineseTea/{}", file.path), file.data.clone()))
+        .collect();
+    packed.push(AssetFile::new("Broken/broken.sm".to_string(), Vec::new()));
+    let pack = parse::from_sm_pack_files(&packed).unwrap();
+    assert_eq!(pack.mapsets.len(), 1);
+    let failed_folders: Vec<&str> = pack.failed_songs.iter().map(|song| song.folder.as_str()).collect();
+    assert_eq!(failed_folders, vec!["Broken"]);
+    assert!(parse::from_sm_folder_files(&files[1..]).is_err());
+    assert!(parse::from_sm_pack_files(&files).is_err());
+
+    let ssc = write::to_ssc(&songs[1].charts[0]).unwrap();
+    assert_eq!(parse::from_ssc(&ssc).unwrap().chartinfo.bg_path, "uta-bg.png");
+}
+
+#[cfg(unix)]
+#[test]
+fn sm_pack_symlink_test() {
+    let pack_path = std::env::temp_dir().join(format!("rgc_chart_sm_pack_{}", std::process::id()));
+    let song_path = pack_path.join("Song");
+    std::fs::create_dir_all(&song_path).unwrap();
+    std::fs::copy("./tests/Maps/etterna/MysticOrientalLoveConsultant/asdf.sm", song_path.join("asdf.sm")).unwrap();
+    // a link back up to the pack would be gone through forever if links were followed
+    std::os::unix::fs::symlink(&pack_path, song_path.join("loop")).unwrap();
+
+    let pack = parse::from_sm_pack(&pack_path);
+    std::fs::remove_dir_all(&pack_path).unwrap();
+    assert_eq!(pack.unwrap().mapsets.len(), 1);
+}
+
+#[test]
+fn ssc_time_signatures_test() {
+    use models::common::TimingChangeType;
+
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Meters;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#TIMESIGNATURES:0.000=6=8,3.000=7=8,6.500=4=4;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#NOTES:\n1000\n0100\n0010\n0001\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    // numerator, denominator and the beats a measure lasts
+    let time_signatures = |chart: &Chart| -> Vec<(u32, u32, f32)> {
+        chart.timing_points.changes.iter()
+            .filter(|change| change.change_type == TimingChangeType::TimeSignature)
+            .map(|change| {
+                let time_signature = change.time_signature.unwrap();
+                (time_signature.numerator, time_signature.denominator, change.value)
+            })
+            .collect()
+    };
+    assert_eq!(time_signatures(&chart), vec![(6, 8, 3.0), (7, 8, 3.5), (4, 4, 4.0)]);
+
+    // 6/8 isn't written back as 3/4
+    let ssc_chart = write::to_ssc(&chart).unwrap();
+    assert!(ssc_chart.contains("#TIMESIGNATURES:0.000=6=8,\n3.000=7=8,\n6.500=4=4;"));
+    assert_eq!(time_signatures(&parse::from_ssc(&ssc_chart).unwrap()), time_signatures(&chart));
+
+    // .sma only has the beats a measure lasts
+    let sma_chart = write::to_sma(&chart).unwrap();
+    assert!(sma_chart.contains("#BEATSPERMEASURE:0.000=3,\n3.000=3.5,\n6.500=4;"));
+    assert_eq!(time_signatures(&parse::from_sma(&sma_chart).unwrap()), vec![(3, 4, 3.0), (7, 8, 3.5), (4, 4, 4.0)]);
+}
+
+#[test]
+fn ssc_fake_holds_test() {
+    use models::common::KeyType;
+
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Fakes;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#FAKES:1.000=2.000;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#NOTES:\n2000\n0240\n0301\n3030\n,\n0001\n0000\n0000\n0000\n;";
+    let chart = parse::from_ssc(raw_chart).unwrap();
+
+    let rows: Vec<Vec<KeyType>> = chart.hitobjects.rows.iter()
+        .map(|row| row.iter().map(|key| key.key_type).collect())
+        .collect();
+    // the hold and the roll starting in the fake lose their ends, the hold from before it is still judged
+    assert_eq!(rows, vec![
+        vec![KeyType::SliderStart, KeyType::Empty, KeyType::Empty, KeyType::Empty],
+        vec![KeyType::Empty, KeyType::Fake, KeyType::Fake, KeyType::Empty],
+        vec![KeyType::Empty, KeyType::Empty, KeyType::Empty, KeyType::Fake],
+        vec![KeyType::SliderEnd, KeyType::Empty, KeyType::Empty, KeyType::Empty],
+        vec![KeyType::Empty, KeyType::Empty, KeyType::Empty, KeyType::Normal],
+    ]);
+}
+
+#[test]
+fn ssc_chart_timing_test() {
+    let raw_chart = "#VERSION:0.83;\n#TITLE:Chart Timing;\n#OFFSET:0;\n#BPMS:0.000=120.000;\n#STOPS:1.000=1.000;\n#WARPS:2.000=1.000;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Easy;\n#METER:1;\n#NOTES:\n1000\n0100\n0010\n0001\n;\n\
+        #NOTEDATA:;\n#STEPSTYPE:dance-single;\n#DIFFICULTY:Hard;\n#METER:10;\n#BPMS:0.000=60.000;\n#NOTES:\n1000\n0100\n0010\n0001\n;";
+    let charts = parse::from_ssc_all(raw_chart).unwrap();
+
+    // the song's stop and warp
+    assert_eq!(charts[0].hitobjects.times, vec![0, 500, 2000, 2000]);
+    // a chart with its own bpms doesn't keep any of the song's timing
+    assert_eq!(charts[1].hitobjects.times, vec![0, 1000, 2000, 3000]);
+}
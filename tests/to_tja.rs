@@ -0,0 +1,164 @@
+mod test_stuff;
+use test_stuff::*;
+
+#[test]
+fn taiko_to_tja_test() {
+    parse_and_convert!(
+        taiko_to_tja,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [neonat's Muzukashii].osu",
+        parse::from_osu,
+        write::to_tja,
+        true
+    );
+}
+
+#[test]
+fn taiko_oni_to_tja_test() {
+    parse_and_convert!(
+        taiko_oni_to_tja,
+        "./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [IMT's Inner Oni].osu",
+        parse::from_osu,
+        write::to_tja,
+        true
+    );
+}
+
+#[test]
+fn tja_to_osu_test() {
+    use rgc_chart::models::common::{GameMode, KeyType, TimingChangeType};
+
+    let raw_chart = "TITLE:Tja Test
+SUBTITLE:--Someone
+BPM:120
+WAVE:song.ogg
+OFFSET:-1.0
+DEMOSTART:20.5
+MAKER:Charter
+
+COURSE:Oni
+LEVEL:8
+BALLOON:5
+
+#START
+1020,
+#SCROLL 1.5
+3040
+#BPMCHANGE 240
+5008, // the roll ends halfway through the last beat
+#MEASURE 3/4
+#GOGOSTART
+70008000,
+#GOGOEND
+,
+#END
+
+COURSE:0
+LEVEL:2
+
+#START
+1,
+#END
+";
+    let charts = parse::from_tja_all(raw_chart).unwrap();
+    assert_eq!(charts.len(), 2);
+    assert_eq!(charts[1].chartinfo.difficulty_name, "Easy 2");
+    assert_eq!(charts[1].hitobjects.times, vec![1000]);
+
+    let chart = parse::from_tja(raw_chart).unwrap();
+    assert_eq!(chart.metadata.title, "Tja Test");
+    assert_eq!(chart.metadata.artist, "Someone");
+    assert_eq!(chart.metadata.creator, "Charter");
+    assert_eq!(chart.chartinfo.difficulty_name, "Oni 8");
+    assert_eq!(chart.chartinfo.game_mode, GameMode::Taiko);
+    assert_eq!(chart.chartinfo.key_count, 1);
+    assert_eq!(chart.chartinfo.audio_offset, 1000);
+    assert_eq!(chart.chartinfo.preview_time, 20500);
+
+    let timing: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(timing, vec![
+        (1000, TimingChangeType::Bpm, 120.0),
+        (3000, TimingChangeType::Sv, 1.5),
+        (4000, TimingChangeType::Bpm, 240.0),
+        (4500, TimingChangeType::TimeSignature, 3.0),
+    ]);
+
+    assert_eq!(chart.hitobjects.times, vec![1000, 2000, 3000, 3500, 4000, 4500]);
+    let key_types: Vec<KeyType> = chart.hitobjects.rows.iter().map(|row| row[0].key_type).collect();
+    assert_eq!(key_types, vec![KeyType::Don, KeyType::Kat, KeyType::BigDon, KeyType::BigKat, KeyType::Drumroll, KeyType::Spinner]);
+    assert_eq!(chart.hitobjects.rows[4][0].slider_end_time(), Some(4375));
+    assert_eq!(chart.hitobjects.rows[5][0].slider_end_time(), Some(4875));
+
+    let osu = write::to_osu(&chart).unwrap();
+    assert!(osu.contains("Mode: 1\n"));
+    let converted = parse::from_osu(&osu).unwrap();
+    assert_eq!(converted.chartinfo.game_mode, GameMode::Taiko);
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    let converted_key_types: Vec<KeyType> = converted.hitobjects.rows.iter().map(|row| row[0].key_type).collect();
+    assert_eq!(converted_key_types, key_types);
+
+    let tja = write::to_tja(&chart).unwrap();
+    assert!(tja.contains("#MEASURE 3/4\n"));
+    let converted = parse::from_tja(&tja).unwrap();
+    assert_eq!(converted.chartinfo.difficulty_name, "Oni 8");
+    assert_eq!(converted.timing_points.times, chart.timing_points.times);
+    assert_eq!(converted.hitobjects.times, chart.hitobjects.times);
+    assert_eq!(converted.hitobjects.rows, chart.hitobjects.rows);
+
+    // osu!taiko charts keep their notes going through TJA
+    let raw_osu = read_file_to_string("./tests/Maps/osu/165991_PlusDanshi/Reol - +Danshi (lZenxl) [neonat's Muzukashii].osu").unwrap();
+    let chart = parse::from_osu(&raw_osu).unwrap();
+    let converted = parse::from_tja(&write::to_tja(&chart).unwrap()).unwrap();
+    assert_eq!(converted.hitobjects.times.len(), chart.hitobjects.times.len());
+    for ((time, row), (converted_time, converted_row)) in chart.hitobjects.times.iter().zip(&chart.hitobjects.rows)
+        .zip(converted.hitobjects.times.iter().zip(&converted.hitobjects.rows))
+    {
+        assert!((time - converted_time).abs() <= 2, "{time} and {converted_time}");
+        assert_eq!(row[0].key_type, converted_row[0].key_type);
+    }
+
+    let raw_qua = read_file_to_string("./tests/Maps/quaver/4548_886_Ziqqurat/34785.qua").unwrap();
+    assert!(write::to_tja(&parse::from_qua(&raw_qua).unwrap()).is_err());
+}
+
+#[test]
+fn tja_branches_and_delays_test() {
+    use rgc_chart::models::common::TimingChangeType;
+
+    let raw_chart = "TITLE:Branches
+BPM:60
+
+COURSE:Oni
+LEVEL:8
+
+#START
+1000,
+#BRANCHSTART p,50,80
+#N
+1000,
+#E
+1100,
+#M
+1010,
+#BRANCHEND
+#BRANCHSTART p,50,80
+#N
+1000,
+#E
+1001,
+#BRANCHEND
+#DELAY 0.5
+1000,
+#END
+";
+    let chart = parse::from_tja(raw_chart).unwrap();
+    // only the master branch is played, or the expert one when there's no master one
+    assert_eq!(chart.hitobjects.times, vec![0, 4000, 6000, 8000, 11000, 12500]);
+
+    let delays: Vec<(i32, TimingChangeType, f32)> = chart.timing_points.iter_views()
+        .filter(|view| view.change_type == TimingChangeType::Delay)
+        .map(|view| (*view.time, view.change_type, *view.value))
+        .collect();
+    assert_eq!(delays, vec![(12500, TimingChangeType::Delay, 500.0)]);
+}
@@ -0,0 +1,9 @@
+export async function osu_to_ssc(raw_chart) {
+  let chart = await wasm.parse_from_osu(raw_chart)
+  let converted = await wasm.write_to_ssc(chart)
+}
+
+export async function ssc_to_osu(raw_chart) {
+  let chart = await wasm.parse_from_ssc(raw_chart)
+  let converted = await wasm.write_to_osu(chart)
+}
@@ -48,6 +48,7 @@ async function runJsTests() {
         const to_osu = await import('./js/to_osu.js');
         const to_sm = await import('./js/to_sm.js');
         const to_qua = await import('./js/to_qua.js');
+        const to_ssc = await import('./js/to_ssc.js');
 
 
         await measureTime(to_osu.sm_to_osu,
@@ -70,6 +71,13 @@ async function runJsTests() {
         await measureTime(to_qua.sm_to_qua,
             await fetchFromUrl('/Maps/etterna/Kil_ChineseTea/ct.sm'))
             .catch(err => console.error(err));
+
+        await measureTime(to_ssc.osu_to_ssc,
+            await fetchFromUrl('/Maps/osu/1688622_EverGreen/ClumsyRecord - Ever Green feat. Ganeme (FAMoss) [Misfortune Lunatic].osu'))
+            .catch(err => console.error(err));
+        await measureTime(to_ssc.ssc_to_osu,
+            await fetchFromUrl('/Maps/etterna/MysticOrientalLoveConsultant/asdf.ssc'))
+            .catch(err => console.error(err));
         
     } catch (err) {
         console.error("JS test initialization error:", err);